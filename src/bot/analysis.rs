@@ -0,0 +1,83 @@
+use crate::bot::greedy::GreedyBot;
+use crate::game::{Game, LegalMove, Position};
+
+/// The value of every cell of the board for the active player: the best evaluation of a legal
+/// move that covers it. Cells no legal move covers have no value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heatmap {
+    width: u16,
+    values: Vec<Option<i32>>,
+}
+
+impl Heatmap {
+    pub fn value(&self, position: &Position) -> Option<i32> {
+        let (column, row) = position.cell()?;
+        if column >= self.width as usize {
+            return None;
+        }
+        self.values.get(row * self.width as usize + column).copied().flatten()
+    }
+
+    /// The lowest and highest value on the board, `None` if no cell has a value.
+    pub fn range(&self) -> Option<(i32, i32)> {
+        let values = self.values.iter().flatten();
+        Some((*values.clone().min()?, *values.max()?))
+    }
+}
+
+/// Rates the cells of the board with the evaluation of the greedy bot.
+pub fn heatmap(game: &Game) -> Heatmap {
+    let width = game.width();
+    let mut values = vec![None; width as usize * game.height() as usize];
+    for candidate in game.legal_moves() {
+        let value = GreedyBot::evaluate(game, &candidate);
+        for position in candidate.blocks.positions() {
+            let (column, row) = position.cell().expect("Legal moves are on the board");
+            let cell = &mut values[row * width as usize + column];
+            *cell = Some(cell.map_or(value, |best: i32| best.max(value)));
+        }
+    }
+    Heatmap { width, values }
+}
+
+/// The best moves of the active player by the evaluation of the greedy bot, best first. Equally
+/// rated moves keep the order they are generated in.
+pub fn suggestions(game: &Game, count: usize) -> Vec<LegalMove> {
+    let mut moves = game.legal_moves()
+        .into_iter()
+        .map(|candidate| (GreedyBot::evaluate(game, &candidate), candidate))
+        .collect::<Vec<_>>();
+    moves.sort_by_key(|(value, _)| -value);
+    moves.into_iter().take(count).map(|(_, candidate)| candidate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Piece, Player, Players};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_rate_cells_covered_by_legal_moves() {
+        let domino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2);
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino])]);
+        let game = Game::new(GameRules { num_players: 1, ..GameRules::with_board_size(3, 3) }, players);
+
+        let heatmap = heatmap(&game);
+        // dominoes starting in a corner cover the corners and the cells next to them
+        assert!(heatmap.value(&Position { x: 0, y: 0 }).is_some());
+        assert!(heatmap.value(&Position { x: 1, y: 0 }).is_some());
+        assert_eq!(heatmap.value(&Position { x: 1, y: 1 }), None);
+        assert_eq!(heatmap.value(&Position { x: 3, y: 0 }), None);
+        let (lowest, highest) = heatmap.range().unwrap();
+        assert!(lowest <= highest);
+
+        let suggestions = suggestions(&game, 2);
+        assert_eq!(suggestions.len(), 2);
+        assert!(GreedyBot::evaluate(&game, &suggestions[0]) >= GreedyBot::evaluate(&game, &suggestions[1]));
+        assert_eq!(suggestions[0].blocks.positions().map(|position| heatmap.value(&position)).max().flatten(), Some(highest));
+    }
+}
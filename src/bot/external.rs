@@ -0,0 +1,172 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::{Agent, largest_draft_piece};
+use crate::game::{Game, LegalMove, Position};
+
+/// An engine running in its own process, so that it can be written in any language. It talks a
+/// line protocol over stdin and stdout, similar to UCI in chess:
+///
+/// - `position <game>` asks for a move of the active player, with the game as JSON on one line in
+///   the format of save files. The engine answers `bestmove <piece name> <orientation> <x> <y>`
+///   or `bestmove pass`.
+/// - `pick <game>` asks for a piece from the draft pool, answered by `bestpick <pool index>`.
+/// - `quit` is sent before the engine is stopped.
+///
+/// An engine that fails to answer or answers with an illegal move passes, a draft pick falls
+/// back to the largest piece. An engine that does not answer in time is stopped, and passes for
+/// the rest of the game.
+pub struct ExternalEngine {
+    process: Child,
+    input: ChildStdin,
+    /// The lines the engine writes, read on a thread so that waiting for them can time out.
+    output: Receiver<String>,
+    timeout: Duration,
+}
+
+/// How long an engine may take to answer by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl ExternalEngine {
+    /// Starts the shell command as the engine.
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut process = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // the output would end up in the middle of the terminal UI
+            .stderr(Stdio::null())
+            .spawn()?;
+        let input = process.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(process.stdout.take().expect("stdout is piped"));
+        let (sender, output) = mpsc::channel();
+        // ends once the engine closes its output, e.g. when it is stopped
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(ExternalEngine { process, input, output, timeout: DEFAULT_TIMEOUT })
+    }
+
+    /// Sets how long the engine may take to answer a request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the request for the game and returns the arguments of the answer with the expected
+    /// keyword.
+    fn request(&mut self, request: &str, game: &Game, answer: &str) -> io::Result<String> {
+        // the engine must not see the moves the other players committed to in this round
+        let game = serde_json::to_string(&game.as_seen_by(game.active_player_index()))?;
+        writeln!(self.input, "{request} {game}")?;
+        self.input.flush()?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let line = match self.output.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.process.kill();
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "the engine did not answer in time"));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the engine stopped"));
+                }
+            };
+            // engines may log other lines, like UCI engines do with 'info'
+            if let Some(arguments) = line.trim().strip_prefix(answer).and_then(|rest| rest.strip_prefix(' ')) {
+                return Ok(arguments.trim().to_string());
+            }
+        }
+    }
+}
+
+impl Agent for ExternalEngine {
+    fn choose_move(&mut self, game: &Game, _player: usize) -> Option<LegalMove> {
+        let arguments = self.request("position", game, "bestmove").ok()?;
+        parse_move(game, &arguments)
+    }
+
+    fn choose_draft_pick(&mut self, game: &Game, _player: usize) -> usize {
+        let pool_size = game.draft().map_or(0, |draft| draft.pool().len());
+        self.request("pick", game, "bestpick")
+            .ok()
+            .and_then(|arguments| arguments.parse::<usize>().ok())
+            .filter(|pool_index| *pool_index < pool_size)
+            .unwrap_or_else(|| largest_draft_piece(game))
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        // the engine may be gone already
+        let _ = writeln!(self.input, "quit");
+        let _ = self.input.flush();
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// The move of `bestmove <piece name> <orientation> <x> <y>`, `None` for a pass or a move that is
+/// not legal.
+fn parse_move(game: &Game, arguments: &str) -> Option<LegalMove> {
+    let [piece_name, orientation, x, y] = arguments.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let piece_index = game.active_player_pieces().iter().position(|piece| piece.name() == piece_name)?;
+    let position = Position { x: x.parse().ok()?, y: y.parse().ok()? };
+    game.legal_move(piece_index, orientation.parse().ok()?, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_play_the_moves_of_the_engine() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, 1);
+        let player = game.active_player_index();
+        let script = "n=0; while read request game; do n=$((n + 1)); echo 'info thinking';
+            if [ $n = 1 ]; then echo 'bestmove I1 0 0 0'; else echo 'bestmove X5 0 0 0'; fi; done";
+        let mut engine = ExternalEngine::spawn(script).unwrap();
+
+        let chosen = engine.choose_move(&game, player).unwrap();
+        assert_eq!(game.active_player_pieces()[chosen.piece_index].name(), "I1");
+        assert_eq!(chosen.position, Position { x: 0, y: 0 });
+        // the X cannot cover a corner, so the engine passes
+        assert_eq!(engine.choose_move(&game, player), None);
+    }
+
+    #[test]
+    fn should_pass_when_the_engine_does_not_answer_in_time() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, 1);
+        let mut engine = ExternalEngine::spawn("sleep 60").unwrap().with_timeout(Duration::from_millis(100));
+
+        let started = Instant::now();
+        assert_eq!(engine.choose_move(&game, game.active_player_index()), None);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
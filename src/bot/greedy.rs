@@ -0,0 +1,76 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::bot::Agent;
+use crate::game::{Game, LegalMove};
+
+/// Every square placed is a point that cannot be lost anymore.
+const SIZE_WEIGHT: i32 = 3;
+/// New anchors are places where later pieces can go.
+const GAINED_ANCHOR_WEIGHT: i32 = 1;
+/// Covering the anchors of others takes those places away from them.
+const BLOCKED_ANCHOR_WEIGHT: i32 = 2;
+
+/// Plays the move with the best immediate gain in squares placed, anchors gained and anchors of
+/// other players blocked, without looking further ahead. Ties go to the first move found. To be
+/// beatable it can be told to play a random move every now and then.
+pub struct GreedyBot {
+    blunder_percent: u8,
+    rng: StdRng,
+}
+
+impl GreedyBot {
+    pub fn new(blunder_percent: u8, seed: u64) -> Self {
+        GreedyBot { blunder_percent, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn evaluate(game: &Game, candidate: &LegalMove) -> i32 {
+        let change = game.anchor_change(&candidate.blocks);
+        SIZE_WEIGHT * candidate.blocks.count() as i32
+            + GAINED_ANCHOR_WEIGHT * change.gained as i32
+            + BLOCKED_ANCHOR_WEIGHT * change.blocked as i32
+    }
+}
+
+impl Agent for GreedyBot {
+    fn choose_move(&mut self, game: &Game, _player: usize) -> Option<LegalMove> {
+        let mut moves = game.legal_moves();
+        if !moves.is_empty() && self.rng.gen_range(0..100) < self.blunder_percent {
+            return Some(moves.swap_remove(self.rng.gen_range(0..moves.len())));
+        }
+        moves.into_iter()
+            .map(|candidate| (Self::evaluate(game, &candidate), candidate))
+            .reduce(|best, next| if next.0 > best.0 { next } else { best })
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::bot::random::RandomBot;
+    use crate::bot::take_turn;
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_beat_the_random_bot() {
+        let pieces = read_standard_piece_set().unwrap();
+        for seed in 0..3 {
+            let players = Players::new(vec![
+                Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+                Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces.clone()),
+            ]);
+            let mut game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, seed);
+            let mut bots: [Box<dyn Agent>; 2] = [Box::new(GreedyBot::new(0, seed)), Box::new(RandomBot::new(seed))];
+            while !game.is_over() {
+                take_turn(bots[game.active_player_index()].as_mut(), &mut game);
+            }
+            assert!(game.score(0) > game.score(1), "Greedy bot lost with seed {seed}");
+        }
+    }
+}
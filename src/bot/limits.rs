@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+/// Caps on the resources of the searching bots, on top of the budget of their kind. Time and
+/// nodes are checked while searching, so a bot stops in the middle of a playout once it is out of
+/// time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    /// Playouts of the tree search or positions visited by the other searches.
+    pub nodes: Option<u64>,
+    /// Size of the transposition table of the max^n and paranoid searches.
+    pub memory_megabytes: Option<usize>,
+}
+
+/// What is left of the limits during one search.
+pub(crate) struct Budget {
+    deadline: Option<Instant>,
+    nodes_left: Option<u64>,
+}
+
+/// The search used up its budget, its results are incomplete.
+pub(crate) struct Exhausted;
+
+impl Budget {
+    pub fn start(time: Option<Duration>, nodes: Option<u64>) -> Self {
+        Budget { deadline: time.map(|time| Instant::now() + time), nodes_left: nodes }
+    }
+
+    /// A budget that never runs out.
+    pub fn unlimited() -> Self {
+        Budget { deadline: None, nodes_left: None }
+    }
+
+    /// Whether the deadline has passed, without spending a node.
+    pub fn is_over_time(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Spends a node, or fails if the budget is used up.
+    pub fn spend(&mut self) -> Result<(), Exhausted> {
+        if self.is_over_time() || self.nodes_left == Some(0) {
+            return Err(Exhausted);
+        }
+        if let Some(nodes_left) = &mut self.nodes_left {
+            *nodes_left -= 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_run_out_of_nodes_and_time() {
+        let mut budget = Budget::start(None, Some(2));
+        assert!(budget.spend().is_ok());
+        assert!(budget.spend().is_ok());
+        assert!(budget.spend().is_err());
+
+        let mut budget = Budget::start(Some(Duration::ZERO), None);
+        assert!(budget.is_over_time());
+        assert!(budget.spend().is_err());
+        assert!(Budget::unlimited().spend().is_ok());
+    }
+}
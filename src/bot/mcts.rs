@@ -0,0 +1,284 @@
+use std::num::NonZeroUsize;
+use std::thread;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::bot::Agent;
+use crate::bot::limits::{Budget, Limits};
+use crate::game::{Game, LegalMove, Position};
+
+/// Playouts per move if nothing else is configured, enough for a few hundred milliseconds per
+/// move in a release build.
+pub const DEFAULT_PLAYOUTS: u32 = 300;
+/// Plies a playout goes on before the position is scored. Full playouts are too slow and too
+/// random to tell moves apart early in the game.
+const PLAYOUT_DEPTH: usize = 8;
+/// How often a playout tries a random piece on a random anchor before it enumerates all moves.
+const SAMPLE_ATTEMPTS: usize = 20;
+const EXPLORATION: f64 = 1.4;
+/// Trees grown per move, whatever the number of cores, so that equal seeds lead to equal moves on
+/// every machine.
+const TREES: u64 = 8;
+
+/// Monte Carlo tree search over the legal moves, the hard opponent. Every playout clones the game,
+/// descends the tree by UCT, adds one move and plays random moves from there. With more than two
+/// players every node is scored from the view of the player who made its move.
+///
+/// The playouts are spread over `TREES` trees, which are grown on as many cores as there are, and
+/// the visits of the moves at the roots are added up in the end.
+pub struct MctsBot {
+    limits: Limits,
+    rng: StdRng,
+}
+
+/// Grows one of the trees of a search.
+struct Worker {
+    rng: StdRng,
+}
+
+/// `None` stands for a pass, which is the only move of a player without a legal placement.
+#[derive(Clone)]
+struct Node {
+    played: Option<LegalMove>,
+    mover: usize,
+    untried: Vec<Option<LegalMove>>,
+    children: Vec<Node>,
+    visits: u32,
+    reward: f64,
+}
+
+impl MctsBot {
+    /// The node limit is the number of playouts, `DEFAULT_PLAYOUTS` if there is none.
+    pub fn new(limits: Limits, seed: u64) -> Self {
+        MctsBot { limits, rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Worker {
+    /// Runs playouts on the tree below `root` until the budget is used up, at least one.
+    fn search(&mut self, root: &mut Node, game: &Game, mut budget: Budget) {
+        while budget.spend().is_ok() || root.children.is_empty() {
+            self.iterate(root, &mut game.clone(), &budget);
+        }
+    }
+
+    /// Runs one playout below `node` on `game`, which is in the position of the node, and returns
+    /// the rewards of all players.
+    fn iterate(&mut self, node: &mut Node, game: &mut Game, budget: &Budget) -> Vec<f64> {
+        let rewards = if game.is_over() {
+            rewards(game)
+        } else if !node.untried.is_empty() {
+            let played = node.untried.swap_remove(self.rng.gen_range(0..node.untried.len()));
+            let mover = game.active_player_index();
+            play(game, played.as_ref());
+            let mut child = Node::new(played, mover, game);
+            let rewards = self.playout(game, budget);
+            child.update(&rewards);
+            node.children.push(child);
+            rewards
+        } else {
+            let parent_visits = node.visits;
+            let child = node.children.iter_mut()
+                .max_by(|a, b| a.uct(parent_visits).total_cmp(&b.uct(parent_visits)))
+                .expect("Nodes without untried moves have children");
+            play(game, child.played.as_ref());
+            self.iterate(child, game, budget)
+        };
+        node.update(&rewards);
+        rewards
+    }
+
+    /// Plays random moves until the playout depth is reached or the time is up.
+    fn playout(&mut self, game: &mut Game, budget: &Budget) -> Vec<f64> {
+        for _ in 0..PLAYOUT_DEPTH {
+            if game.is_over() || budget.is_over_time() {
+                break;
+            }
+            match self.sample_move(game) {
+                Some((piece_index, orientation, position)) => {
+                    game.place_piece(piece_index, orientation, position).expect("Only legal moves are played");
+                }
+                None => game.pass()
+            }
+        }
+        rewards(game)
+    }
+
+    /// A random legal move as piece index, orientation and position. Random pieces on random
+    /// anchors are tried first, since enumerating every move is the expensive part of a playout.
+    fn sample_move(&mut self, game: &Game) -> Option<(usize, usize, Position)> {
+        let anchors = game.anchors(game.active_player_index()).positions().collect::<Vec<_>>();
+        let pieces = game.active_player_pieces();
+        if !anchors.is_empty() && !pieces.is_empty() {
+            for _ in 0..SAMPLE_ATTEMPTS {
+                let anchor = &anchors[self.rng.gen_range(0..anchors.len())];
+                let piece_index = self.rng.gen_range(0..pieces.len());
+                let orientations = pieces[piece_index].orientations();
+                let orientation = self.rng.gen_range(0..orientations.len());
+                let blocks = orientations[orientation].blocks().collect::<Vec<_>>();
+                let position = anchor - &blocks[self.rng.gen_range(0..blocks.len())];
+                if game.can_place(piece_index, orientation, &position).is_ok() {
+                    return Some((piece_index, orientation, position));
+                }
+            }
+        }
+        let mut moves = game.legal_moves();
+        (!moves.is_empty())
+            .then(|| moves.swap_remove(self.rng.gen_range(0..moves.len())))
+            .map(|sampled| (sampled.piece_index, sampled.orientation, sampled.position))
+    }
+}
+
+impl Agent for MctsBot {
+    fn choose_move(&mut self, game: &Game, player: usize) -> Option<LegalMove> {
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        self.search_on(game, player, cores)
+    }
+}
+
+impl MctsBot {
+    /// The move of the search, with the trees spread over `cores` threads.
+    fn search_on(&mut self, game: &Game, player: usize, cores: usize) -> Option<LegalMove> {
+        let root = Node::new(None, player, game);
+        if root.untried.iter().all(Option::is_none) {
+            return None;
+        }
+        let playouts = self.limits.nodes.unwrap_or(DEFAULT_PLAYOUTS as u64);
+        let num_trees = TREES.min(playouts).max(1);
+        let seeds = (0..num_trees).map(|_| self.rng.gen::<u64>()).collect::<Vec<_>>();
+        let trees_per_thread = seeds.len().div_ceil(cores);
+        let time = self.limits.time;
+        // scoped std threads rather than rayon: a handful of threads per move is all the search
+        // needs, which does not justify another dependency
+        let roots = thread::scope(|scope| {
+            let threads = seeds.chunks(trees_per_thread)
+                .enumerate()
+                .map(|(chunk_index, seeds)| {
+                    let (root, game) = (root.clone(), game.clone());
+                    scope.spawn(move || seeds.iter()
+                        .enumerate()
+                        .map(|(offset, seed)| {
+                            let tree = (chunk_index * trees_per_thread + offset) as u64;
+                            // the trees of a thread share its time, the first trees take the
+                            // playouts that do not divide evenly
+                            let budget = Budget::start(
+                                time.map(|time| time / seeds.len() as u32),
+                                Some(playouts / num_trees + u64::from(tree < playouts % num_trees))
+                            );
+                            let mut root = root.clone();
+                            Worker { rng: StdRng::seed_from_u64(*seed) }.search(&mut root, &game, budget);
+                            root
+                        })
+                        .collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>();
+            threads.into_iter().flat_map(|thread| thread.join().expect("Workers do not panic")).collect::<Vec<_>>()
+        });
+
+        let mut visits: Vec<(Option<LegalMove>, u32)> = vec![];
+        for child in roots.into_iter().flat_map(|root| root.children) {
+            match visits.iter_mut().find(|(played, _)| *played == child.played) {
+                Some((_, total)) => *total += child.visits,
+                None => visits.push((child.played, child.visits))
+            }
+        }
+        // ties go to the move of the earliest tree, so that equal seeds lead to equal moves
+        visits.into_iter()
+            .reduce(|best, next| if next.1 > best.1 { next } else { best })
+            .and_then(|(played, _)| played)
+    }
+}
+
+impl Node {
+    fn new(played: Option<LegalMove>, mover: usize, game: &Game) -> Self {
+        let untried = match game.is_over() {
+            true => vec![],
+            false => {
+                let moves = game.legal_moves();
+                match moves.is_empty() {
+                    true => vec![None],
+                    false => moves.into_iter().map(Some).collect()
+                }
+            }
+        };
+        Node { played, mover, untried, children: vec![], visits: 0, reward: 0.0 }
+    }
+
+    fn update(&mut self, rewards: &[f64]) {
+        self.visits += 1;
+        self.reward += rewards[self.mover];
+    }
+
+    fn uct(&self, parent_visits: u32) -> f64 {
+        self.reward / self.visits as f64 + EXPLORATION * ((parent_visits as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+fn play(game: &mut Game, played: Option<&LegalMove>) {
+    match played {
+        Some(played) => {
+            game.place_piece(played.piece_index, played.orientation, played.position.clone()).expect("Only legal moves are played");
+        }
+        None => game.pass()
+    }
+}
+
+/// The scores scaled to 0 for the worst and 1 for the best player.
+fn rewards(game: &Game) -> Vec<f64> {
+    let scores = (0..game.players().len()).map(|player_index| game.score(player_index)).collect::<Vec<_>>();
+    let best = *scores.iter().max().expect("Games have players");
+    let worst = *scores.iter().min().expect("Games have players");
+    scores.iter()
+        .map(|score| match best == worst {
+            true => 0.5,
+            false => (score - worst) as f64 / (best - worst) as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_cover_the_board_with_the_larger_piece() {
+        let pieces = read_standard_piece_set().unwrap()
+            .into_iter()
+            .filter(|piece| piece.size() <= 2)
+            .collect::<Vec<_>>();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let mut game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(2, 1) }, players, 1);
+
+        // the domino covers the whole board, so the other player cannot place anything
+        let mover = game.active_player_index();
+        let chosen = MctsBot::new(Limits { nodes: Some(50), ..Limits::default() }, 1).choose_move(&game, mover).unwrap();
+        assert!(game.place_piece(chosen.piece_index, chosen.orientation, chosen.position).unwrap());
+        assert!(game.is_over());
+        assert!(game.score(mover) > game.score(1 - mover));
+    }
+
+    #[test]
+    fn should_choose_the_same_move_whatever_the_number_of_cores() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, 1);
+        let mover = game.active_player_index();
+        let limits = Limits { nodes: Some(60), ..Limits::default() };
+
+        let on_one_core = MctsBot::new(limits, 7).search_on(&game, mover, 1);
+        assert_eq!(MctsBot::new(limits, 7).search_on(&game, mover, 3), on_one_core);
+    }
+}
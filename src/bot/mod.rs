@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::bot::greedy::GreedyBot;
+use crate::bot::limits::Limits;
+use crate::bot::mcts::{DEFAULT_PLAYOUTS, MctsBot};
+use crate::bot::opening_book::{BookBot, standard_book};
+use crate::bot::random::RandomBot;
+use crate::bot::search::{DEFAULT_SEARCH_MILLIS, SearchBot, SearchMode};
+use crate::game::{Game, LegalMove, PlayerKind};
+
+pub mod analysis;
+#[cfg(feature = "serde")]
+pub mod external;
+pub mod greedy;
+pub mod limits;
+pub mod mcts;
+pub mod opening_book;
+pub mod random;
+pub mod search;
+pub mod simulation;
+pub mod transposition;
+pub mod turn;
+
+/// Makes the decisions for a player who is not sitting at the keyboard. All bots of the game are
+/// agents, other engines can be plugged in by implementing this trait and seating them with
+/// `Seat::Agent`.
+pub trait Agent: Send {
+    /// The placement to make for `player`, who is the active player, `None` to pass.
+    fn choose_move(&mut self, game: &Game, player: usize) -> Option<LegalMove>;
+
+    /// The piece `player` picks from the draft pool, as an index into the pool. Takes the largest
+    /// piece unless the agent knows better.
+    fn choose_draft_pick(&mut self, game: &Game, _player: usize) -> usize {
+        largest_draft_piece(game)
+    }
+}
+
+/// The index of the largest piece in the draft pool.
+pub fn largest_draft_piece(game: &Game) -> usize {
+    game.draft()
+        .and_then(|draft| draft.pool().iter().enumerate().max_by_key(|(_, piece)| piece.size()))
+        .map_or(0, |(pool_index, _)| pool_index)
+}
+
+/// Who makes the moves for a player.
+pub enum Seat {
+    /// The player moves with the keyboard.
+    Human,
+    Agent(Box<dyn Agent>),
+    /// The player moves at another machine of a network game. The host seats a client there,
+    /// the other clients get the moves with the state of the game.
+    Remote,
+}
+
+impl Seat {
+    pub fn into_agent(self) -> Option<Box<dyn Agent>> {
+        match self {
+            Seat::Human | Seat::Remote => None,
+            Seat::Agent(agent) => Some(agent),
+        }
+    }
+}
+
+/// How strong a bot plays, for players who do not want to pick an engine and its budget.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Difficulty {
+    /// The greedy bot, but every other move or so is random.
+    Easy,
+    /// The greedy bot with an occasional blunder.
+    Medium,
+    /// Monte Carlo tree search at full strength.
+    Hard,
+}
+
+impl Difficulty {
+    pub fn player_kind(self) -> PlayerKind {
+        match self {
+            Difficulty::Easy => PlayerKind::GreedyBot { blunder_percent: 40 },
+            Difficulty::Medium => PlayerKind::GreedyBot { blunder_percent: 10 },
+            Difficulty::Hard => PlayerKind::MctsBot { playouts: DEFAULT_PLAYOUTS },
+        }
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("expected 'easy', 'medium' or 'hard', found '{string}'"))
+        }
+    }
+}
+
+impl std::str::FromStr for PlayerKind {
+    type Err = String;
+
+    /// Bots that search take their budget after a colon, e.g. `mcts:500` for 500 playouts or
+    /// `paranoid:1000` for a second per move.
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (name, budget) = match string.split_once(':') {
+            Some((name, budget)) => {
+                let budget = budget.parse::<u32>()
+                    .ok()
+                    .filter(|budget| *budget > 0)
+                    .ok_or_else(|| format!("expected a positive budget, found '{budget}'"))?;
+                (name, Some(budget))
+            }
+            None => (string, None)
+        };
+        match (name, budget) {
+            ("human", None) => Ok(PlayerKind::Human),
+            ("random", None) => Ok(PlayerKind::RandomBot),
+            ("greedy", None) => Ok(PlayerKind::GreedyBot { blunder_percent: 0 }),
+            ("mcts", budget) => Ok(PlayerKind::MctsBot { playouts: budget.unwrap_or(DEFAULT_PLAYOUTS) }),
+            ("maxn", budget) => Ok(PlayerKind::MaxNBot { millis: budget.unwrap_or(DEFAULT_SEARCH_MILLIS) }),
+            ("paranoid", budget) => Ok(PlayerKind::ParanoidBot { millis: budget.unwrap_or(DEFAULT_SEARCH_MILLIS) }),
+            _ => name.parse::<Difficulty>()
+                .ok()
+                .filter(|_| budget.is_none())
+                .map(Difficulty::player_kind)
+                .ok_or_else(|| format!("expected 'human', 'easy', 'medium', 'hard', 'random', 'greedy', 'mcts[:<playouts>]', 'maxn[:<ms>]' or 'paranoid[:<ms>]', found '{string}'"))
+        }
+    }
+}
+
+/// The seat of a player of the given kind. Bots that draw random numbers are seeded with `seed` so
+/// that they play the same way in the same situation. With `opening_book` set, all bots but the
+/// random one play the first moves from the standard book. The `limits` replace the budget of
+/// the kind where they are set.
+pub fn for_player(kind: PlayerKind, seed: u64, opening_book: bool, limits: Limits) -> Seat {
+    let search_limits = |millis: u32| Limits { time: limits.time.or(Some(Duration::from_millis(millis as u64))), ..limits };
+    let agent: Box<dyn Agent> = match kind {
+        PlayerKind::Human => return Seat::Human,
+        PlayerKind::RandomBot => return Seat::Agent(Box::new(RandomBot::new(seed))),
+        PlayerKind::GreedyBot { blunder_percent } => Box::new(GreedyBot::new(blunder_percent, seed)),
+        PlayerKind::MctsBot { playouts } => Box::new(MctsBot::new(Limits { nodes: limits.nodes.or(Some(playouts as u64)), ..limits }, seed)),
+        PlayerKind::MaxNBot { millis } => Box::new(SearchBot::new(SearchMode::MaxN, search_limits(millis))),
+        PlayerKind::ParanoidBot { millis } => Box::new(SearchBot::new(SearchMode::Paranoid, search_limits(millis))),
+    };
+    match opening_book {
+        true => Seat::Agent(Box::new(BookBot::new(standard_book(), agent))),
+        false => Seat::Agent(agent)
+    }
+}
+
+/// What a bot wants to do with its turn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision {
+    DraftPick(usize),
+    Place(LegalMove),
+    Pass,
+}
+
+/// Lets the agent decide on the turn of the active player without changing the game, e.g. on a
+/// copy of the game on another thread.
+pub fn decide(agent: &mut dyn Agent, game: &Game) -> Decision {
+    let player = game.active_player_index();
+    if game.draft().is_some() {
+        return Decision::DraftPick(agent.choose_draft_pick(game, player));
+    }
+    agent.choose_move(game, player).map_or(Decision::Pass, Decision::Place)
+}
+
+/// Plays a decision for the active player.
+pub fn apply(decision: Decision, game: &mut Game) {
+    match decision {
+        Decision::DraftPick(pool_index) => game.draft_pick(pool_index).expect("Bots pick from the pool"),
+        Decision::Place(chosen) => {
+            game.place_piece(chosen.piece_index, chosen.orientation, chosen.position).expect("Bots only play legal moves");
+        }
+        Decision::Pass => game.pass()
+    }
+}
+
+/// Lets the agent take the turn of the active player: a draft pick, a placement or a pass.
+pub fn take_turn(agent: &mut dyn Agent, game: &mut Game) {
+    let decision = decide(agent, game);
+    apply(decision, game);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_difficulties_and_bots() {
+        assert_eq!("easy".parse::<PlayerKind>(), Ok(PlayerKind::GreedyBot { blunder_percent: 40 }));
+        assert_eq!("hard".parse::<PlayerKind>(), Ok(PlayerKind::MctsBot { playouts: DEFAULT_PLAYOUTS }));
+        assert_eq!("mcts:50".parse::<PlayerKind>(), Ok(PlayerKind::MctsBot { playouts: 50 }));
+        assert!("hard:50".parse::<PlayerKind>().is_err());
+        assert!("mcts:0".parse::<PlayerKind>().is_err());
+    }
+}
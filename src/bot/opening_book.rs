@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::bot::Agent;
+use crate::game::{Game, LegalMove, Position};
+
+/// Known good moves for the first rounds, keyed on the position hash of the game.
+pub struct OpeningBook {
+    entries: HashMap<u64, BookMove>,
+}
+
+struct BookMove {
+    piece_name: String,
+    orientation: usize,
+    position: Position,
+}
+
+/// The book shipped with the game, parsed on first use.
+pub fn standard_book() -> &'static OpeningBook {
+    static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+    BOOK.get_or_init(|| OpeningBook::parse(include_str!("../res/opening_book")).expect("The standard opening book is valid"))
+}
+
+impl OpeningBook {
+    /// Parses one move per line as `<hash> <piece name> <orientation> <x> <y>` with the hash in
+    /// hexadecimal. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut entries = HashMap::new();
+        for (line_index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (hash, book_move) = parse_entry(line).map_err(|error| format!("line {}: {error}", line_index + 1))?;
+            if entries.insert(hash, book_move).is_some() {
+                return Err(format!("line {}: position {hash:016x} is already in the book", line_index + 1));
+            }
+        }
+        Ok(OpeningBook { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The book move for the active player, if the position is in the book and the move is legal
+    /// in this game. Hashes do not cover the rules, so a move for another board is ignored.
+    pub fn lookup(&self, game: &Game) -> Option<LegalMove> {
+        let book_move = self.entries.get(&game.position_hash())?;
+        let piece_index = game.active_player_pieces().iter().position(|piece| piece.name() == book_move.piece_name)?;
+        game.legal_move(piece_index, book_move.orientation, book_move.position.clone())
+    }
+}
+
+fn parse_entry(line: &str) -> Result<(u64, BookMove), String> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    let [hash, piece_name, orientation, x, y] = fields[..] else {
+        return Err(format!("expected '<hash> <piece> <orientation> <x> <y>', found '{line}'"));
+    };
+    let hash = u64::from_str_radix(hash, 16).map_err(|_| format!("invalid position hash '{hash}'"))?;
+    let number = |field: &str| field.parse::<usize>().map_err(|_| format!("invalid number '{field}'"));
+    let book_move = BookMove {
+        piece_name: piece_name.to_string(),
+        orientation: number(orientation)?,
+        position: Position { x: number(x)? as i32, y: number(y)? as i32 },
+    };
+    Ok((hash, book_move))
+}
+
+/// Plays from the opening book while the game is in it and leaves the rest to another agent.
+pub struct BookBot {
+    book: &'static OpeningBook,
+    agent: Box<dyn Agent>,
+}
+
+impl BookBot {
+    pub fn new(book: &'static OpeningBook, agent: Box<dyn Agent>) -> Self {
+        BookBot { book, agent }
+    }
+}
+
+impl Agent for BookBot {
+    fn choose_move(&mut self, game: &Game, player: usize) -> Option<LegalMove> {
+        self.book.lookup(game).or_else(|| self.agent.choose_move(game, player))
+    }
+
+    fn choose_draft_pick(&mut self, game: &Game, player: usize) -> usize {
+        self.agent.choose_draft_pick(game, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    fn game(width: u16, height: u16) -> Game {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(["Bob", "Alice", "Eve", "Pete"].into_iter()
+            .map(|name| Player::new(name.to_string(), Color::Green, Color::LightGreen, pieces.clone()))
+            .collect());
+        Game::with_seed(GameRules::with_board_size(width, height), players, 1)
+    }
+
+    #[test]
+    fn should_follow_the_standard_book_for_two_rounds() {
+        let book = standard_book();
+        assert_eq!(book.len(), 32);
+        let mut game = game(20, 20);
+        for _ in 0..8 {
+            let book_move = book.lookup(&game).expect("Every position of the line is in the book");
+            assert!(game.place_piece(book_move.piece_index, book_move.orientation, book_move.position).unwrap());
+        }
+        assert!(book.lookup(&game).is_none());
+    }
+
+    #[test]
+    fn should_skip_book_moves_that_do_not_fit_the_board() {
+        // the empty board has the same hash on every board size
+        assert!(standard_book().lookup(&game(14, 14)).is_none());
+    }
+
+    #[test]
+    fn should_reject_duplicate_positions() {
+        assert!(OpeningBook::parse("# comment\n\n00ff W5 0 0 0").is_ok_and(|book| book.len() == 1));
+        assert_eq!(
+            OpeningBook::parse("00ff W5 0 0 0\n00ff Z5 0 0 0").err(),
+            Some("line 2: position 00000000000000ff is already in the book".to_string())
+        );
+    }
+}
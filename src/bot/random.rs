@@ -0,0 +1,64 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::bot::Agent;
+use crate::game::{Game, LegalMove};
+
+/// Plays a uniformly random legal move, mostly useful as a sparring partner and in tests.
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        RandomBot { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for RandomBot {
+    fn choose_move(&mut self, game: &Game, _player: usize) -> Option<LegalMove> {
+        let mut moves = game.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        Some(moves.swap_remove(self.rng.gen_range(0..moves.len())))
+    }
+
+    fn choose_draft_pick(&mut self, game: &Game, _player: usize) -> usize {
+        let pool_size = game.draft().map_or(1, |draft| draft.pool().len());
+        self.rng.gen_range(0..pool_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::bot::take_turn;
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_play_random_bots_to_the_end() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let mut game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, 3);
+        let mut bot = RandomBot::new(3);
+        let mut turns = 0;
+        while !game.is_over() {
+            let moves = game.legal_moves();
+            let played = game.replay().len();
+            take_turn(&mut bot, &mut game);
+            assert_eq!(game.replay().len(), played + usize::from(!moves.is_empty()));
+            turns += 1;
+            assert!(turns < 100, "The game does not end");
+        }
+        assert!(game.replay().len() > 10);
+    }
+}
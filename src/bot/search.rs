@@ -0,0 +1,213 @@
+use crate::bot::Agent;
+use crate::bot::analysis::suggestions;
+use crate::bot::limits::{Budget, Exhausted, Limits};
+use crate::bot::transposition::{Bound, DEFAULT_TABLE_MEGABYTES, TranspositionTable};
+use crate::game::{Game, LegalMove};
+
+/// Time per move if nothing else is configured.
+pub const DEFAULT_SEARCH_MILLIS: u32 = 500;
+/// Only the most promising moves by the greedy evaluation are searched, the full width of a
+/// Blokus position rules out any depth worth having.
+const BEAM_WIDTH: usize = 8;
+/// Iterative deepening stops here even if there is time left, e.g. in small endgames.
+const MAX_DEPTH: usize = 8;
+const SQUARE_WEIGHT: i32 = 3;
+const ANCHOR_WEIGHT: i32 = 1;
+
+/// How the moves of the other players are predicted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SearchMode {
+    /// Max^n: every player plays the move that is best for themselves.
+    MaxN,
+    /// Paranoid: all other players play the move that is worst for the bot, which allows alpha
+    /// beta pruning and reduces the game to two players.
+    Paranoid,
+}
+
+/// Searches ahead with iterative deepening over a beam of the greedy bot's favourite moves.
+/// Positions are rated for every player by the squares placed and the anchors left. Searched
+/// positions are kept in a transposition table for the following depths and moves.
+pub struct SearchBot {
+    mode: SearchMode,
+    limits: Limits,
+    table: TranspositionTable,
+}
+
+/// `None` stands for a pass, which is the only move of a player without a legal placement.
+type Candidate = Option<LegalMove>;
+
+impl SearchBot {
+    pub fn new(mode: SearchMode, limits: Limits) -> Self {
+        let table = TranspositionTable::with_megabytes(limits.memory_megabytes.unwrap_or(DEFAULT_TABLE_MEGABYTES));
+        SearchBot { mode, limits, table }
+    }
+
+    fn search(&mut self, game: &Game, depth: usize, root: usize, budget: &mut Budget) -> Result<Vec<i32>, Exhausted> {
+        match self.mode {
+            SearchMode::MaxN => max_n(game, depth, budget, &mut self.table),
+            SearchMode::Paranoid => paranoid(game, depth, root, i32::MIN, i32::MAX, budget, &mut self.table).map(|value| {
+                let mut values = vec![0; game.players().len()];
+                values[root] = value;
+                values
+            })
+        }
+    }
+}
+
+impl Agent for SearchBot {
+    fn choose_move(&mut self, game: &Game, player: usize) -> Option<LegalMove> {
+        let root = player;
+        let candidates = candidates(game);
+        let mut budget = Budget::start(self.limits.time, self.limits.nodes);
+        let mut best = None;
+        for depth in 1..=MAX_DEPTH {
+            // the first depth always finishes, so that there is a move to play
+            let mut unlimited = Budget::unlimited();
+            let depth_budget = if depth == 1 { &mut unlimited } else { &mut budget };
+            let values = candidates.iter()
+                .map(|candidate| self.search(&after(game, candidate), depth - 1, root, depth_budget).map(|values| values[root]))
+                .collect::<Result<Vec<_>, Exhausted>>();
+            let Ok(values) = values else {
+                break;
+            };
+            best = values.iter()
+                .enumerate()
+                .reduce(|best, next| if next.1 > best.1 { next } else { best })
+                .map(|(index, _)| index);
+        }
+        best.and_then(|index| candidates[index].clone())
+    }
+}
+
+fn max_n(game: &Game, depth: usize, budget: &mut Budget, table: &mut TranspositionTable) -> Result<Vec<i32>, Exhausted> {
+    if depth == 0 || game.is_over() {
+        return Ok(evaluate(game));
+    }
+    let hash = game.position_hash();
+    if let Some(entry) = table.get(hash, depth) {
+        return Ok(entry.values.clone());
+    }
+    budget.spend()?;
+    let mover = game.active_player_index();
+    let mut best: Option<Vec<i32>> = None;
+    for candidate in candidates(game) {
+        let values = max_n(&after(game, &candidate), depth - 1, budget, table)?;
+        if best.as_ref().is_none_or(|best| values[mover] > best[mover]) {
+            best = Some(values);
+        }
+    }
+    let best = best.expect("Every position has a move or a pass");
+    table.insert(hash, depth, best.clone(), Bound::Exact);
+    Ok(best)
+}
+
+/// The evaluation of `root` minus the average of the others, which `root` maximizes and everybody
+/// else minimizes.
+fn paranoid(game: &Game, depth: usize, root: usize, mut alpha: i32, mut beta: i32, budget: &mut Budget, table: &mut TranspositionTable) -> Result<i32, Exhausted> {
+    if depth == 0 || game.is_over() {
+        let values = evaluate(game);
+        let others = values.iter().enumerate().filter(|(index, _)| *index != root).map(|(_, value)| value).sum::<i32>();
+        return Ok(values[root] - others / (values.len() as i32 - 1).max(1));
+    }
+    let hash = game.position_hash();
+    let window = (alpha, beta);
+    if let Some(entry) = table.get(hash, depth) {
+        let value = entry.values[0];
+        match entry.bound {
+            Bound::Exact => return Ok(value),
+            Bound::Lower => alpha = alpha.max(value),
+            Bound::Upper => beta = beta.min(value),
+        }
+        if alpha >= beta {
+            return Ok(value);
+        }
+    }
+    budget.spend()?;
+    let maximizing = game.active_player_index() == root;
+    let mut best = if maximizing { i32::MIN } else { i32::MAX };
+    for candidate in candidates(game) {
+        let value = paranoid(&after(game, &candidate), depth - 1, root, alpha, beta, budget, table)?;
+        if maximizing {
+            best = best.max(value);
+            alpha = alpha.max(best);
+        } else {
+            best = best.min(value);
+            beta = beta.min(best);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    // values outside the window only bound the real value, since the search was cut short
+    let bound = match best {
+        _ if best <= window.0 => Bound::Upper,
+        _ if best >= window.1 => Bound::Lower,
+        _ => Bound::Exact
+    };
+    table.insert(hash, depth, vec![best], bound);
+    Ok(best)
+}
+
+/// The best moves of the active player by the greedy evaluation, or a pass if there are none.
+fn candidates(game: &Game) -> Vec<Candidate> {
+    let moves = suggestions(game, BEAM_WIDTH);
+    if moves.is_empty() {
+        return vec![None];
+    }
+    moves.into_iter().map(Some).collect()
+}
+
+fn after(game: &Game, candidate: &Candidate) -> Game {
+    let mut game = game.clone();
+    match candidate {
+        Some(candidate) => {
+            game.place_piece(candidate.piece_index, candidate.orientation, candidate.position.clone()).expect("Only legal moves are searched");
+        }
+        None => game.pass()
+    }
+    game
+}
+
+fn evaluate(game: &Game) -> Vec<i32> {
+    (0..game.players().len())
+        .map(|player_index| SQUARE_WEIGHT * game.squares_placed(player_index) as i32 + ANCHOR_WEIGHT * game.anchors(player_index).count() as i32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratatui::style::Color;
+
+    use crate::game::{Piece, Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    fn game(rules: GameRules, pieces: Vec<Piece>) -> Game {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        Game::with_seed(GameRules { num_players: 2, ..rules }, players, 1)
+    }
+
+    #[test]
+    fn should_cover_the_board_with_the_larger_piece() {
+        let pieces = read_standard_piece_set().unwrap().into_iter().filter(|piece| piece.size() <= 2).collect::<Vec<_>>();
+        for mode in [SearchMode::MaxN, SearchMode::Paranoid] {
+            let mut game = game(GameRules::with_board_size(2, 1), pieces.clone());
+            let chosen = SearchBot::new(mode, Limits { time: Some(Duration::from_secs(1)), ..Limits::default() }).choose_move(&game, game.active_player_index()).unwrap();
+            assert!(game.place_piece(chosen.piece_index, chosen.orientation, chosen.position).unwrap());
+            assert!(game.is_over(), "{mode:?} did not take the whole board");
+        }
+    }
+
+    #[test]
+    fn should_move_without_time_to_search() {
+        let game = game(GameRules::with_board_size(14, 14), read_standard_piece_set().unwrap());
+        assert!(SearchBot::new(SearchMode::Paranoid, Limits { time: Some(Duration::ZERO), nodes: Some(0), ..Limits::default() }).choose_move(&game, game.active_player_index()).is_some());
+    }
+}
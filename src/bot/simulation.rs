@@ -0,0 +1,88 @@
+use crate::bot::{self, Agent};
+use crate::game::Game;
+
+/// Aggregated outcomes of games between bots, per seat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationResults {
+    pub games: usize,
+    /// Games won per seat, a game won by several seats counts for each of them in equal parts.
+    pub wins: Vec<f64>,
+    pub total_scores: Vec<i64>,
+    pub total_moves: usize,
+}
+
+impl SimulationResults {
+    pub fn new(num_players: usize) -> Self {
+        SimulationResults { games: 0, wins: vec![0.0; num_players], total_scores: vec![0; num_players], total_moves: 0 }
+    }
+
+    /// Adds the outcome of a finished game.
+    pub fn record(&mut self, game: &Game) {
+        let scores = (0..game.players().len()).map(|player_index| game.score(player_index)).collect::<Vec<_>>();
+        let best = *scores.iter().max().expect("Games have players");
+        let winners = scores.iter().filter(|score| **score == best).count();
+        for (player_index, score) in scores.iter().enumerate() {
+            if *score == best {
+                self.wins[player_index] += 1.0 / winners as f64;
+            }
+            self.total_scores[player_index] += *score as i64;
+        }
+        self.games += 1;
+        self.total_moves += game.moves_played();
+    }
+
+    pub fn win_rate(&self, player_index: usize) -> f64 {
+        self.wins[player_index] / self.games.max(1) as f64
+    }
+
+    pub fn average_score(&self, player_index: usize) -> f64 {
+        self.total_scores[player_index] as f64 / self.games.max(1) as f64
+    }
+
+    /// The average number of pieces placed per game.
+    pub fn average_length(&self) -> f64 {
+        self.total_moves as f64 / self.games.max(1) as f64
+    }
+}
+
+/// Plays the game to the end with the agent of the active player on every turn.
+pub fn play_out(game: &mut Game, agents: &mut [Box<dyn Agent>]) {
+    while !game.is_over() {
+        bot::take_turn(agents[game.active_player_index()].as_mut(), game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::bot::greedy::GreedyBot;
+    use crate::bot::random::RandomBot;
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_aggregate_results_of_played_out_games() {
+        let pieces = read_standard_piece_set().unwrap();
+        let mut results = SimulationResults::new(2);
+        for seed in 0..2 {
+            let players = Players::new(vec![
+                Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+                Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces.clone()),
+            ]);
+            let mut game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, seed);
+            play_out(&mut game, &mut [Box::new(GreedyBot::new(0, seed)), Box::new(RandomBot::new(seed))]);
+            assert!(game.is_over());
+            results.record(&game);
+        }
+
+        assert_eq!(results.games, 2);
+        assert_eq!(results.wins.iter().sum::<f64>(), 2.0);
+        assert!(results.win_rate(0) > results.win_rate(1));
+        assert!(results.average_score(0) > results.average_score(1));
+        assert!(results.average_length() > 0.0);
+    }
+}
@@ -0,0 +1,76 @@
+use std::mem::size_of;
+
+/// Table size if nothing else is configured.
+pub const DEFAULT_TABLE_MEGABYTES: usize = 16;
+
+/// Results of searched positions by their Zobrist hash, so that a position reached through
+/// different move orders, or again at the next depth of iterative deepening, is not searched
+/// twice. Every slot holds one position and newer results replace older ones. Hashes only cover
+/// the board, positions with the same cells covered by different pieces share their entry.
+pub(crate) struct TranspositionTable {
+    slots: Vec<Option<Entry>>,
+}
+
+pub(crate) struct Entry {
+    hash: u64,
+    /// How many plies deep the position was searched.
+    pub depth: usize,
+    /// The values of all players, or the single value of a paranoid search.
+    pub values: Vec<i32>,
+    pub bound: Bound,
+}
+
+/// How a stored value relates to the real value of the position, alpha beta pruning cuts
+/// searches short once the value is known to be outside the window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Bound {
+    Exact,
+    /// The value is at least the stored one.
+    Lower,
+    /// The value is at most the stored one.
+    Upper,
+}
+
+impl TranspositionTable {
+    pub fn with_megabytes(megabytes: usize) -> Self {
+        // the values of up to four players live next to the entry
+        let entry_size = size_of::<Option<Entry>>() + 4 * size_of::<i32>();
+        let num_slots = (megabytes * 1024 * 1024 / entry_size).max(1);
+        TranspositionTable { slots: (0..num_slots).map(|_| None).collect() }
+    }
+
+    /// The entry of the position, if it was searched at least `depth` plies deep.
+    pub fn get(&self, hash: u64, depth: usize) -> Option<&Entry> {
+        self.slots[self.slot(hash)].as_ref().filter(|entry| entry.hash == hash && entry.depth >= depth)
+    }
+
+    pub fn insert(&mut self, hash: u64, depth: usize, values: Vec<i32>, bound: Bound) {
+        let slot = self.slot(hash);
+        self.slots[slot] = Some(Entry { hash, depth, values, bound });
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash % self.slots.len() as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_return_entries_searched_deep_enough() {
+        let mut table = TranspositionTable::with_megabytes(1);
+        table.insert(42, 2, vec![1, 2], Bound::Exact);
+        assert_eq!(table.get(42, 2).map(|entry| entry.values.clone()), Some(vec![1, 2]));
+        assert!(table.get(42, 3).is_none());
+        assert!(table.get(43, 1).is_none());
+
+        // a single slot makes every position replace the one before
+        let mut table = TranspositionTable::with_megabytes(0);
+        table.insert(42, 2, vec![1], Bound::Lower);
+        table.insert(43, 1, vec![2], Bound::Upper);
+        assert!(table.get(42, 1).is_none());
+        assert_eq!(table.get(43, 1).map(|entry| entry.bound), Some(Bound::Upper));
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::bot::{self, Agent, Decision};
+use crate::game::Game;
+
+/// A bot thinking about its turn on a worker thread, so that the UI keeps rendering or the server
+/// keeps serving meanwhile. The bot moves to the worker and comes back with its decision.
+pub struct BotTurn {
+    player_index: usize,
+    /// The position the bot is thinking about, a decision for any other position is stale.
+    position_hash: u64,
+    moves_played: usize,
+    receiver: Receiver<(Box<dyn Agent>, Decision)>,
+}
+
+impl BotTurn {
+    pub fn start(mut bot: Box<dyn Agent>, game: &Game) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let copy = game.clone();
+        thread::spawn(move || {
+            let decision = bot::decide(bot.as_mut(), &copy);
+            // the UI or the match may be gone already
+            let _ = sender.send((bot, decision));
+        });
+        BotTurn {
+            player_index: game.active_player_index(),
+            position_hash: game.position_hash(),
+            moves_played: game.moves_played(),
+            receiver,
+        }
+    }
+
+    pub fn player_index(&self) -> usize {
+        self.player_index
+    }
+
+    /// The bot and its decision once it is done thinking.
+    pub fn try_finish(&self) -> Option<(Box<dyn Agent>, Decision)> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Whether the game is still in the position the bot was thinking about, it may have changed
+    /// e.g. because the bot ran out of time.
+    pub fn is_current(&self, game: &Game) -> bool {
+        game.active_player_index() == self.player_index
+            && game.position_hash() == self.position_hash
+            && game.moves_played() == self.moves_played
+    }
+}
+
+/// Takes the turns whose bots are done thinking out of `turns`, with the bots and their decisions.
+/// A turn that went stale keeps running until its bot comes back, without holding up the turns of
+/// the other players.
+pub fn take_finished(turns: &mut Vec<BotTurn>) -> Vec<(BotTurn, Box<dyn Agent>, Decision)> {
+    let mut finished = vec![];
+    let mut index = 0;
+    while index < turns.len() {
+        match turns[index].try_finish() {
+            Some((bot, decision)) => finished.push((turns.remove(index), bot, decision)),
+            None => index += 1
+        }
+    }
+    finished
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::Sender;
+    use std::time::Duration;
+
+    use ratatui::style::Color;
+
+    use crate::game::{LegalMove, Piece, Player, Players, Position};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    /// Passes once it is told to.
+    struct Waiting(Receiver<()>);
+
+    impl Agent for Waiting {
+        fn choose_move(&mut self, _game: &Game, _player: usize) -> Option<LegalMove> {
+            let _ = self.0.recv();
+            None
+        }
+    }
+
+    fn waiting_turn(game: &Game) -> (BotTurn, Sender<()>) {
+        let (sender, receiver) = mpsc::channel();
+        (BotTurn::start(Box::new(Waiting(receiver)), game), sender)
+    }
+
+    #[test]
+    fn should_only_take_the_turns_whose_bots_came_back() {
+        let piece = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece.clone()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![piece]),
+        ]);
+        let mut game = Game::new(GameRules { num_players: 2, ..GameRules::with_board_size(3, 3) }, players);
+        let (stale, _stuck) = waiting_turn(&game);
+        game.pass();
+        let (current, done) = waiting_turn(&game);
+        let mut turns = vec![stale, current];
+
+        done.send(()).unwrap();
+        let finished = loop {
+            let finished = take_finished(&mut turns);
+            if !finished.is_empty() {
+                break finished;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].0.is_current(&game));
+        assert_eq!(finished[0].2, Decision::Pass);
+        assert!(!turns[0].is_current(&game), "The stale turn keeps running");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::game::Position;
+
+/// A set of board cells stored as one `u64` per row, bit `x` of row `y` standing for the cell at
+/// `(x, y)`. Boards are therefore limited to 64 columns.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bitboard {
+    rows: Vec<u64>,
+}
+
+pub const MAX_BOARD_WIDTH: u16 = u64::BITS as u16;
+
+impl Bitboard {
+    pub fn new(height: u16) -> Self {
+        Bitboard { rows: vec![0; height as usize] }
+    }
+
+    pub fn height(&self) -> u16 {
+        self.rows.len() as u16
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        position.cell().is_some_and(|(x, y)| {
+            self.rows.get(y).is_some_and(|row| x < MAX_BOARD_WIDTH as usize && row & (1 << x) != 0)
+        })
+    }
+
+    pub fn insert(&mut self, position: &Position) {
+        let (x, y) = position.cell().expect("Position outside of the bitboard");
+        self.rows[y] |= 1 << x;
+    }
+
+    pub fn intersects(&self, other: &Bitboard) -> bool {
+        self.rows.iter().zip(&other.rows).any(|(row, other_row)| row & other_row != 0)
+    }
+
+    pub fn union_with(&mut self, other: &Bitboard) {
+        self.rows.iter_mut().zip(&other.rows).for_each(|(row, other_row)| *row |= other_row);
+    }
+
+    /// Keeps only the cells that are also part of `other`.
+    pub fn intersect_with(&mut self, other: &Bitboard) {
+        self.rows.iter_mut().zip(&other.rows).for_each(|(row, other_row)| *row &= other_row);
+    }
+
+    /// Removes all cells of `other` from this set.
+    pub fn subtract(&mut self, other: &Bitboard) {
+        self.rows.iter_mut().zip(&other.rows).for_each(|(row, other_row)| *row &= !other_row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|row| *row == 0)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.rows.iter().map(|row| row.count_ones()).sum()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item=Position> + '_ {
+        self.rows.iter().enumerate().flat_map(|(y, row)| {
+            (0..MAX_BOARD_WIDTH)
+                .filter(move |x| row & (1 << x) != 0)
+                .map(move |x| Position { x: x as i32, y: y as i32 })
+        })
+    }
+
+    /// All cells sharing an edge with a cell of this set, limited to the first `width` columns.
+    pub fn edge_neighbours(&self, width: u16) -> Bitboard {
+        let column_mask = Self::column_mask(width);
+        let rows = (0..self.rows.len())
+            .map(|y| {
+                let horizontal = (self.rows[y] << 1) | (self.rows[y] >> 1);
+                let vertical = self.row_above(y) | self.row_below(y);
+                (horizontal | vertical) & column_mask
+            })
+            .collect();
+        Bitboard { rows }
+    }
+
+    /// All cells touching a cell of this set at a corner, limited to the first `width` columns.
+    /// This may include cells that also share an edge with another cell of the set.
+    pub fn diagonal_neighbours(&self, width: u16) -> Bitboard {
+        let column_mask = Self::column_mask(width);
+        let rows = (0..self.rows.len())
+            .map(|y| {
+                let adjacent_rows = self.row_above(y) | self.row_below(y);
+                ((adjacent_rows << 1) | (adjacent_rows >> 1)) & column_mask
+            })
+            .collect();
+        Bitboard { rows }
+    }
+
+    fn row_above(&self, y: usize) -> u64 {
+        if y > 0 { self.rows[y - 1] } else { 0 }
+    }
+
+    fn row_below(&self, y: usize) -> u64 {
+        self.rows.get(y + 1).copied().unwrap_or(0)
+    }
+
+    fn column_mask(width: u16) -> u64 {
+        if width >= MAX_BOARD_WIDTH { u64::MAX } else { (1 << width) - 1 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitboard(height: u16, positions: &[(i32, i32)]) -> Bitboard {
+        let mut bitboard = Bitboard::new(height);
+        positions.iter().for_each(|(x, y)| bitboard.insert(&Position { x: *x, y: *y }));
+        bitboard
+    }
+
+    #[test]
+    fn should_compute_edge_neighbours() {
+        let neighbours = bitboard(3, &[(0, 1)]).edge_neighbours(3);
+        assert_eq!(neighbours, bitboard(3, &[(0, 0), (1, 1), (0, 2)]));
+    }
+
+    #[test]
+    fn should_compute_diagonal_neighbours_within_width() {
+        let neighbours = bitboard(3, &[(2, 1)]).diagonal_neighbours(3);
+        assert_eq!(neighbours, bitboard(3, &[(1, 0), (1, 2)]));
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::game::Position;
+use crate::game::bitboard::MAX_BOARD_WIDTH;
+
+/// The shape of a board: its size and the cells nobody may occupy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoardMask {
+    pub width: u16,
+    pub height: u16,
+    pub blocked: Vec<Position>,
+}
+
+/// Parses a board drawn with `.` for free and `#` for blocked cells, one line per row.
+pub fn parse_board_mask(source: &str) -> Result<BoardMask, String> {
+    let rows = source.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>();
+    let width = rows.first().map(|row| row.trim_end().chars().count()).ok_or("line 1: the board is empty")?;
+    if width > MAX_BOARD_WIDTH as usize {
+        return Err(format!("line 1: the board is {width} columns wide, the maximum is {MAX_BOARD_WIDTH}"));
+    }
+
+    let mut blocked = vec![];
+    for (y, row) in rows.iter().enumerate() {
+        let row = row.trim_end();
+        if row.chars().count() != width {
+            return Err(format!("line {}: expected {width} cells, found {}", y + 1, row.chars().count()));
+        }
+        for (x, cell) in row.chars().enumerate() {
+            match cell {
+                '.' => (),
+                '#' => blocked.push(Position { x: x as i32, y: y as i32 }),
+                other => return Err(format!("line {}: unexpected '{other}' in column {}, expected '.' or '#'", y + 1, x + 1))
+            }
+        }
+    }
+    Ok(BoardMask { width: width as u16, height: rows.len() as u16, blocked })
+}
+
+/// Blocks `count` random cells, rounded down to a multiple of four. Every blocked cell is
+/// mirrored into the other three quadrants so that no starting corner is favoured, and the
+/// corners themselves stay free.
+pub fn generate_board_mask(width: u16, height: u16, count: usize, rng: &mut impl Rng) -> BoardMask {
+    let (width_i, height_i) = (width as i32, height as i32);
+    let symmetric = |Position { x, y }: &Position| [
+        Position { x: *x, y: *y },
+        Position { x: width_i - 1 - x, y: *y },
+        Position { x: *x, y: height_i - 1 - y },
+        Position { x: width_i - 1 - x, y: height_i - 1 - y },
+    ];
+    let candidates = (0..(height_i + 1) / 2)
+        .flat_map(|y| (0..(width_i + 1) / 2).map(move |x| Position { x, y }))
+        .filter(|position| *position != Position { x: 0, y: 0 })
+        .collect::<Vec<_>>();
+
+    let mut blocked = HashSet::new();
+    for _ in 0..(count / 4).min(candidates.len()) {
+        let candidate = loop {
+            let candidate = &candidates[rng.gen_range(0..candidates.len())];
+            if !blocked.contains(candidate) {
+                break candidate;
+            }
+        };
+        blocked.extend(symmetric(candidate));
+    }
+    let mut blocked = blocked.into_iter().collect::<Vec<_>>();
+    blocked.sort_by_key(|position| (position.y, position.x));
+    BoardMask { width, height, blocked }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_board_mask() {
+        let mask = parse_board_mask("...\n.#.\n..#\n").unwrap();
+        assert_eq!((mask.width, mask.height), (3, 3));
+        assert_eq!(mask.blocked, vec![Position { x: 1, y: 1 }, Position { x: 2, y: 2 }]);
+
+        assert_eq!(parse_board_mask("...\n..\n"), Err("line 2: expected 3 cells, found 2".to_string()));
+        assert_eq!(parse_board_mask("..x\n"), Err("line 1: unexpected 'x' in column 3, expected '.' or '#'".to_string()));
+    }
+
+    #[test]
+    fn should_generate_symmetric_masks_without_blocked_corners() {
+        let mask = generate_board_mask(20, 20, 12, &mut StdRng::seed_from_u64(1));
+        assert_eq!(mask.blocked.len(), 12);
+        for Position { x, y } in &mask.blocked {
+            assert!(mask.blocked.contains(&Position { x: 19 - x, y: 19 - y }));
+            assert_ne!((x % 19, y % 19), (0, 0));
+        }
+    }
+}
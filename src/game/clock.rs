@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+/// What happens to a player whose time runs out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TimeoutAction {
+    /// The turn passes to the next player, the player keeps playing with no time left.
+    Pass,
+    /// The player is out of the game.
+    Forfeit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+    pub budget: Duration,
+    /// Time odds: budgets for individual seats that replace `budget`. Seats without an entry get
+    /// the default budget.
+    pub seat_budgets: Vec<Duration>,
+    pub increment: Duration,
+    pub timeout_action: TimeoutAction,
+}
+
+impl TimeControl {
+    pub fn budget_for(&self, player_index: usize) -> Duration {
+        self.seat_budgets.get(player_index).copied().unwrap_or(self.budget)
+    }
+
+    /// Whether some seats start with a different budget than others.
+    pub fn has_time_odds(&self, num_players: usize) -> bool {
+        (1..num_players).any(|player_index| self.budget_for(player_index) != self.budget_for(0))
+    }
+}
+
+/// A chess clock with one time budget per player, only the clock of the player to move runs.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    time_control: TimeControl,
+    remaining: Vec<Duration>,
+    running: Option<(usize, Instant)>,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl, num_players: usize) -> Self {
+        Clock {
+            remaining: (0..num_players).map(|player_index| time_control.budget_for(player_index)).collect(),
+            time_control,
+            running: None,
+        }
+    }
+
+    /// Continues a clock from a snapshot, the clock of `running` restarts at `now`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn resume(time_control: TimeControl, remaining: Vec<Duration>, running: Option<usize>, now: Instant) -> Self {
+        Clock { time_control, remaining, running: running.map(|player_index| (player_index, now)) }
+    }
+
+    /// The remaining time of every player at `now` and the player whose clock is running.
+    #[cfg(feature = "serde")]
+    pub(crate) fn snapshot(&self, now: Instant) -> (Vec<Duration>, Option<usize>) {
+        let remaining = (0..self.remaining.len()).map(|player_index| self.remaining(player_index, now)).collect();
+        (remaining, self.running.map(|(player_index, _)| player_index))
+    }
+
+    pub fn time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+
+    pub fn start(&mut self, player_index: usize, now: Instant) {
+        self.stop(now);
+        self.running = Some((player_index, now));
+    }
+
+    /// Stops the running clock, charging the elapsed time and crediting the increment if the
+    /// player did not run out of time.
+    pub fn stop(&mut self, now: Instant) {
+        if let Some((player_index, _)) = self.running {
+            let remaining = self.remaining(player_index, now);
+            self.remaining[player_index] = if remaining.is_zero() { remaining } else { remaining + self.time_control.increment };
+            self.running = None;
+        }
+    }
+
+    pub fn remaining(&self, player_index: usize, now: Instant) -> Duration {
+        match self.running {
+            Some((running_player, started)) if running_player == player_index => {
+                self.remaining[player_index].saturating_sub(now.saturating_duration_since(started))
+            }
+            _ => self.remaining[player_index]
+        }
+    }
+
+    /// The player whose clock is running and has no time left.
+    pub fn expired_player(&self, now: Instant) -> Option<usize> {
+        self.running
+            .map(|(player_index, _)| player_index)
+            .filter(|player_index| self.remaining(*player_index, now).is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock() -> Clock {
+        let time_control = TimeControl {
+            budget: Duration::from_secs(60),
+            seat_budgets: vec![],
+            increment: Duration::from_secs(2),
+            timeout_action: TimeoutAction::Pass,
+        };
+        Clock::new(time_control, 2)
+    }
+
+    #[test]
+    fn should_only_run_clock_of_active_player() {
+        let mut clock = clock();
+        let start = Instant::now();
+        clock.start(0, start);
+        clock.start(1, start + Duration::from_secs(10));
+
+        let now = start + Duration::from_secs(15);
+        assert_eq!(clock.remaining(0, now), Duration::from_secs(52));
+        assert_eq!(clock.remaining(1, now), Duration::from_secs(55));
+    }
+
+    #[test]
+    fn should_report_expired_player() {
+        let mut clock = clock();
+        let start = Instant::now();
+        clock.start(1, start);
+
+        assert_eq!(clock.expired_player(start + Duration::from_secs(59)), None);
+        assert_eq!(clock.expired_player(start + Duration::from_secs(60)), Some(1));
+
+        clock.stop(start + Duration::from_secs(61));
+        assert_eq!(clock.remaining(1, start + Duration::from_secs(61)), Duration::ZERO);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn should_resume_from_snapshot_without_charging_the_pause() {
+        let mut clock = clock();
+        let start = Instant::now();
+        clock.start(1, start);
+        let (remaining, running) = clock.snapshot(start + Duration::from_secs(10));
+        assert_eq!(remaining, vec![Duration::from_secs(60), Duration::from_secs(50)]);
+        assert_eq!(running, Some(1));
+
+        let resumed_at = start + Duration::from_secs(3600);
+        let resumed = Clock::resume(clock.time_control().clone(), remaining, running, resumed_at);
+        assert_eq!(resumed.remaining(1, resumed_at + Duration::from_secs(5)), Duration::from_secs(45));
+        assert_eq!(resumed.expired_player(resumed_at + Duration::from_secs(50)), Some(1));
+    }
+
+    #[test]
+    fn should_start_seats_with_their_own_budget() {
+        let time_control = TimeControl {
+            budget: Duration::from_secs(600),
+            seat_budgets: vec![Duration::from_secs(300), Duration::from_secs(900)],
+            increment: Duration::ZERO,
+            timeout_action: TimeoutAction::Pass,
+        };
+        let clock = Clock::new(time_control, 3);
+        let now = Instant::now();
+
+        assert_eq!(clock.remaining(0, now), Duration::from_secs(300));
+        assert_eq!(clock.remaining(1, now), Duration::from_secs(900));
+        assert_eq!(clock.remaining(2, now), Duration::from_secs(600));
+        assert!(clock.time_control().has_time_odds(3));
+    }
+}
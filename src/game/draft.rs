@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use crate::game::{Game, Piece};
+
+/// The shared pool of the draft variant. Players take turns picking one piece at a time into
+/// their racks until the pool is empty, then the game starts with the player who drafted first.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Draft {
+    pool: Vec<Piece>,
+    first_player: usize,
+}
+
+impl Draft {
+    pub(crate) fn new(pool: Vec<Piece>, first_player: usize) -> Self {
+        Draft { pool, first_player }
+    }
+
+    pub fn pool(&self) -> &[Piece] {
+        &self.pool
+    }
+}
+
+impl Game {
+    /// The draft that has to be finished before the first piece is placed, if there is one.
+    pub fn draft(&self) -> Option<&Draft> {
+        self.draft.as_ref()
+    }
+
+    /// Moves a piece from the pool into the rack of the active player and hands the pick to the
+    /// next player. Picking the last piece starts the game, with fresh clocks.
+    pub fn draft_pick(&mut self, pool_index: usize) -> Result<(), String> {
+        let draft = self.draft.as_mut().ok_or("There is no draft in progress")?;
+        if pool_index >= draft.pool.len() {
+            return Err(format!("There is no piece {pool_index} in the pool"));
+        }
+        let piece = draft.pool.remove(pool_index);
+        let next_player = match draft.pool.is_empty() {
+            true => draft.first_player,
+            false => (self.players.active_player_index + 1) % self.players.players.len()
+        };
+        self.draft.take_if(|draft| draft.pool.is_empty());
+        self.active_player_mut().available_pieces.push(piece);
+
+        let player_index = self.players.active_player_index;
+        self.notify(|observer, game| observer.on_draft_pick(game, player_index, pool_index));
+
+        self.players.active_player_index = next_player;
+        if let Some(time_control) = self.clock().filter(|_| self.draft.is_none()).map(|clock| clock.time_control().clone()) {
+            self.start_clock(time_control, Instant::now());
+        }
+        self.notify(|observer, game| observer.on_turn_changed(game, next_player));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players, Position};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_draft_racks_from_the_pool() {
+        let monomino = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let domino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2);
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![monomino.clone()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino.clone()]),
+        ]);
+        let mut game = Game::new(GameRules { draft: true, ..GameRules::with_board_size(4, 4) }, players);
+        let first_player = game.active_player_index();
+        assert_eq!(game.draft().unwrap().pool().len(), 2);
+        assert!(game.players().iter().all(|player| player.available_pieces.is_empty()));
+        assert!(!game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+
+        let first_pick = game.draft().unwrap().pool()[1].clone();
+        game.draft_pick(1).unwrap();
+        assert_eq!(game.active_player_index(), 1 - first_player);
+        assert!(game.draft_pick(1).is_err());
+        game.draft_pick(0).unwrap();
+
+        assert!(game.draft().is_none());
+        assert_eq!(game.active_player_index(), first_player);
+        assert_eq!(game.players()[first_player].available_pieces, vec![first_pick]);
+        assert!(!game.is_over());
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt::{Display, Formatter};
+
+use crate::game::Position;
+
+/// Why a move cannot be played.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum GameError {
+    NoSuchPiece(usize),
+    NoSuchOrientation { piece: String, orientation: usize },
+    OutOfBounds(Position),
+    /// The piece covers a cell that is already occupied.
+    Overlap,
+    /// The piece shares an edge with another piece of the same player.
+    EdgeContact,
+    /// The piece neither covers a starting corner nor touches a corner of the player's pieces.
+    NoCornerContact,
+    /// The orientation mirrors the piece, which the rules do not allow.
+    FlipsNotAllowed,
+    /// Pieces can only be placed once every piece has been drafted.
+    DraftInProgress,
+}
+
+impl GameError {
+    /// Whether the move could never be played, as opposed to being illegal in the current
+    /// position.
+    pub fn is_invalid_input(&self) -> bool {
+        matches!(self, GameError::NoSuchPiece(_) | GameError::NoSuchOrientation { .. } | GameError::OutOfBounds(_))
+    }
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::NoSuchPiece(index) => write!(f, "No piece at index {index}"),
+            GameError::NoSuchOrientation { piece, orientation } => write!(f, "Piece {piece} has no orientation {orientation}"),
+            GameError::OutOfBounds(Position { x, y }) => write!(f, "Out of bounds ({x}, {y})"),
+            GameError::Overlap => write!(f, "The piece overlaps another piece"),
+            GameError::EdgeContact => write!(f, "The piece touches a piece of the same color along an edge"),
+            GameError::NoCornerContact => write!(f, "The piece does not touch a corner of a piece of the same color"),
+            GameError::FlipsNotAllowed => write!(f, "Pieces may not be mirrored"),
+            GameError::DraftInProgress => write!(f, "The draft is not finished yet"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use crate::game::{Piece, Position};
+
+/// A change to the rack of a player before the game starts, to even out players of different
+/// strength.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Handicap {
+    /// The piece with this name is taken out of the rack.
+    RemovePiece(String),
+    /// The player gets another single square.
+    ExtraMonomino,
+}
+
+impl Handicap {
+    pub fn apply(&self, rack: &mut Vec<Piece>) -> Result<(), String> {
+        match self {
+            Handicap::RemovePiece(name) => {
+                let index = rack.iter()
+                    .position(|piece| piece.name() == name)
+                    .ok_or_else(|| format!("There is no piece '{name}' to remove"))?;
+                rack.remove(index);
+            }
+            Handicap::ExtraMonomino => {
+                let mut monomino = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+                monomino.id = rack.iter().map(Piece::id).max().map_or(0, |id| id + 1);
+                monomino.name = "I1".to_string();
+                rack.push(monomino);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `-<piece name>` removes a piece, `+1` grants an extra single square.
+impl FromStr for Handicap {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.split_at_checked(1) {
+            Some(("-", name)) if !name.is_empty() => Ok(Handicap::RemovePiece(name.to_string())),
+            Some(("+", "1")) => Ok(Handicap::ExtraMonomino),
+            _ => Err(format!("expected '-<piece>' or '+1', found '{string}'"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::piece_set::read_standard_piece_set;
+
+    use super::*;
+
+    #[test]
+    fn should_transform_rack() {
+        let mut rack = read_standard_piece_set().unwrap();
+        "-X5".parse::<Handicap>().unwrap().apply(&mut rack).unwrap();
+        "+1".parse::<Handicap>().unwrap().apply(&mut rack).unwrap();
+
+        assert_eq!(rack.len(), 21);
+        assert!(rack.iter().all(|piece| piece.name() != "X5"));
+        assert_eq!(rack.iter().filter(|piece| piece.size() == 1).count(), 2);
+        assert_eq!(Handicap::RemovePiece("X5".to_string()).apply(&mut rack), Err("There is no piece 'X5' to remove".to_string()));
+        assert!("X5".parse::<Handicap>().is_err());
+    }
+}
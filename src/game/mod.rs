@@ -0,0 +1,1320 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::{random, Rng, SeedableRng};
+use rand::rngs::StdRng;
+use ratatui::style::Color;
+
+use crate::game::bitboard::{Bitboard, MAX_BOARD_WIDTH};
+use crate::game::clock::{Clock, TimeControl, TimeoutAction};
+use crate::game::draft::Draft;
+use crate::game::error::GameError;
+use crate::game::observer::GameObserver;
+pub use crate::game::piece::{Orientation, Piece};
+use crate::game::replay::{Move, RandomDecision, Replay, ReplayMetadata};
+use crate::game::rules::{GameRules, Scoring, Start};
+use crate::game::simultaneous::Commitment;
+use crate::game::stats::{AnchorChange, OccupancyStats};
+
+pub mod bitboard;
+pub mod board_mask;
+pub mod clock;
+pub mod draft;
+pub mod error;
+pub mod handicap;
+pub mod observer;
+pub mod piece;
+pub mod piece_set;
+pub mod puzzle;
+pub mod replay;
+pub mod rules;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod simultaneous;
+pub mod stats;
+pub mod zobrist;
+
+/// The purpose under which the draw of the starting player is recorded.
+pub const STARTING_PLAYER_DRAW: &str = "starting player";
+
+pub struct Game {
+    rules: GameRules,
+    pub(crate) board: Board,
+    players: Players,
+    history: Vec<Move>,
+    seed: u64,
+    rng: StdRng,
+    random_decisions: Vec<RandomDecision>,
+    /// Who moved first, or drafted first in the draft variant.
+    starting_player: usize,
+    clock: Option<Clock>,
+    observers: Vec<Box<dyn GameObserver>>,
+    /// The pieces taken out of the racks, one for every move in `history`, so that moves can be
+    /// taken back.
+    taken_pieces: Vec<TakenPiece>,
+    draft: Option<Draft>,
+    /// The hidden moves of the current round of the simultaneous variant.
+    commitments: Vec<Commitment>,
+    revealed: Vec<Commitment>,
+}
+
+/// A piece that was placed, together with its former position in the rack.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct TakenPiece {
+    rack_index: usize,
+    piece: Piece,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Board {
+    pub(crate) width: u16,
+    pub(crate) height: u16,
+    player_boards: Vec<PlayerBoard>,
+    occupancy: Bitboard,
+    /// Cells nobody may occupy, they are also part of `occupancy`.
+    blocked: Bitboard,
+    /// Whether pieces only have to avoid other pieces, see `GameRules::free_placement`.
+    free_placement: bool,
+    placements: Vec<Placement>,
+    hash: u64,
+}
+
+/// The cells covered by one player, the cells they may never cover because they share an edge
+/// with their pieces, and the free cells diagonal to their pieces where the next piece may attach.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct PlayerBoard {
+    blocks: Bitboard,
+    forbidden: Bitboard,
+    anchors: Bitboard,
+}
+
+/// The blocks covered by one placed piece. The move number of a placement is its position in
+/// `Board::placements`, starting at 1.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Placement {
+    player: usize,
+    piece_id: usize,
+    blocks: Bitboard,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum State {
+    Free,
+    /// A tile nobody may cover.
+    Blocked,
+    /// A tile covered by a block of `piece_id`, which `player` placed as the `move_number`th
+    /// piece on the board (starting at 1).
+    Occupied { player: usize, piece_id: usize, move_number: usize },
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Players {
+    players: Vec<Player>,
+    active_player_index: usize,
+}
+
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Player {
+    pub name: String,
+    pub color: Color,
+    pub secondary_color: Color,
+    pub available_pieces: Vec<Piece>,
+    pub first_move: bool,
+    pub status: PlayerStatus,
+    pub kind: PlayerKind,
+}
+
+/// A placement the active player may make, in the terms of `Game::place_piece`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegalMove {
+    pub piece_index: usize,
+    pub orientation: usize,
+    pub position: Position,
+    /// The cells the piece would cover.
+    pub blocks: Bitboard,
+}
+
+/// Who makes the moves of a player.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PlayerKind {
+    #[default]
+    Human,
+    /// A bot that plays a random legal move.
+    RandomBot,
+    /// A bot that plays the move with the best immediate gain, see `GreedyBot`. It plays a
+    /// random move instead in `blunder_percent` of its turns.
+    GreedyBot { blunder_percent: u8 },
+    /// A bot that searches with the given number of playouts per move, see `MctsBot`.
+    MctsBot { playouts: u32 },
+    /// A bot that searches ahead for the given time per move, every player maximizing their own
+    /// evaluation, see `SearchBot`.
+    MaxNBot { millis: u32 },
+    /// Like `MaxNBot`, but assuming that all other players play against the bot.
+    ParanoidBot { millis: u32 },
+}
+
+/// Whether a player still takes turns. Players in any other state are skipped.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PlayerStatus {
+    #[default]
+    Active,
+    /// None of the remaining pieces fits anywhere on the board.
+    Blocked,
+    /// The player gave up or ran out of time.
+    Resigned,
+    /// All pieces have been placed.
+    Finished,
+}
+
+/// A cell on the board or relative to a piece. Coordinates are signed so that transformations of
+/// pieces and cursor offsets may leave the board temporarily, positions are only checked against
+/// the board when they are converted into cell indices.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Game {
+    pub fn new(rules: GameRules, players: Players) -> Self {
+        Self::with_seed(rules, players, random())
+    }
+
+    /// Creates a game whose random decisions, like the choice of the starting player, are fully
+    /// determined by `seed`.
+    pub fn with_seed(rules: GameRules, players: Players, seed: u64) -> Self {
+        Self::starting_with(rules, players, seed, None)
+    }
+
+    /// The starting player is drawn unless it is given.
+    fn starting_with(rules: GameRules, players: Players, seed: u64, starting_player: Option<usize>) -> Self {
+        let mut game = Game {
+            board: Board::new(&rules, players.players.len()),
+            rules,
+            players,
+            history: vec![],
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            random_decisions: vec![],
+            starting_player: 0,
+            clock: None,
+            observers: vec![],
+            taken_pieces: vec![],
+            draft: None,
+            commitments: vec![],
+            revealed: vec![],
+        };
+        let num_players = game.players.players.len() as u64;
+        game.starting_player = match starting_player {
+            Some(starting_player) => starting_player,
+            None => game.random_below(STARTING_PLAYER_DRAW, num_players) as usize
+        };
+        game.players.active_player_index = game.starting_player;
+        if game.rules.draft {
+            // the racks the players were given make up the pool they draft from
+            let pool = game.players.players.iter_mut().flat_map(|player| std::mem::take(&mut player.available_pieces)).collect();
+            game.draft = Some(Draft::new(pool, game.players.active_player_index));
+        }
+        game
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    /// Draws a number in `0..bound` from the game's seeded random source. Every draw is recorded
+    /// together with its `purpose`, so that the decisions of a seeded game can be verified later.
+    pub fn random_below(&mut self, purpose: &str, bound: u64) -> u64 {
+        let value = self.rng.gen_range(0..bound);
+        self.random_decisions.push(RandomDecision {
+            purpose: purpose.to_string(),
+            bound,
+            value,
+            move_number: self.history.len(),
+        });
+        value
+    }
+
+    pub fn random_decisions(&self) -> &[RandomDecision] {
+        &self.random_decisions
+    }
+
+    pub fn starting_player(&self) -> usize {
+        self.starting_player
+    }
+
+    /// The draw that decided who starts, if the game drew one.
+    pub fn starting_player_draw(&self) -> Option<&RandomDecision> {
+        self.random_decisions.iter().find(|decision| decision.purpose == STARTING_PLAYER_DRAW)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.board.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.board.height
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players.players
+    }
+
+    pub fn active_player_pieces(&self) -> &[Piece] {
+        &self.active_player().available_pieces
+    }
+
+    /// The number of squares the player has covered so far.
+    pub fn squares_placed(&self, player_index: usize) -> usize {
+        self.board.player_boards[player_index].blocks.count() as usize
+    }
+
+    /// The number of squares of the pieces the player has not placed yet.
+    pub fn squares_remaining(&self, player_index: usize) -> usize {
+        self.players.players[player_index].available_pieces.iter().map(Piece::size).sum()
+    }
+
+    pub fn pieces_remaining(&self, player_index: usize) -> usize {
+        self.players.players[player_index].available_pieces.len()
+    }
+
+    /// Minus one point per square left in the rack, or the bonuses of the rules if every piece
+    /// has been placed. With `Scoring::SquaresPlaced` it is the number of squares covered.
+    pub fn score(&self, player_index: usize) -> i32 {
+        if self.rules.scoring == Scoring::SquaresPlaced {
+            return self.squares_placed(player_index) as i32;
+        }
+        if self.pieces_remaining(player_index) > 0 {
+            return -(self.squares_remaining(player_index) as i32);
+        }
+        let last_piece_size = self.board.placements.iter()
+            .rev()
+            .find(|placement| placement.player == player_index)
+            .map(|placement| placement.blocks.count());
+        match last_piece_size {
+            Some(1) => self.rules.all_pieces_bonus + self.rules.single_square_last_bonus,
+            _ => self.rules.all_pieces_bonus
+        }
+    }
+
+    /// The summed scores of the teams in the rules, in the same order.
+    pub fn team_scores(&self) -> Vec<i32> {
+        self.rules.teams.iter()
+            .map(|team| team.iter().map(|player_index| self.score(*player_index)).sum())
+            .collect()
+    }
+
+    /// Every placement the active player may make. Empty while drafting.
+    pub fn legal_moves(&self) -> Vec<LegalMove> {
+        if self.draft.is_some() {
+            return vec![];
+        }
+        self.board.legal_placements(self.active_player_pieces(), self.players.active_player_index, self.rules.allow_flips).collect()
+    }
+
+    /// Checks whether the active player may place the piece, without changing the game.
+    pub fn can_place(&self, piece_index: usize, orientation: usize, position: &Position) -> Result<(), GameError> {
+        self.placement_mask(piece_index, orientation, position).map(|_| ())
+    }
+
+    /// The placement with the cells it covers, if the active player may make it.
+    pub fn legal_move(&self, piece_index: usize, orientation: usize, position: Position) -> Option<LegalMove> {
+        let blocks = self.placement_mask(piece_index, orientation, &position).ok()?;
+        Some(LegalMove { piece_index, orientation, position, blocks })
+    }
+
+    /// Places the piece for the active player. Returns `Ok(false)` if the move is not legal in
+    /// the current position and an error if it could never be played.
+    pub fn place_piece(&mut self, piece_index: usize, orientation: usize, position: Position) -> Result<bool, String> {
+        let blocks = match self.placement_mask(piece_index, orientation, &position) {
+            Err(error) if error.is_invalid_input() => return Err(error.to_string()),
+            Err(_) => return Ok(false),
+            Ok(blocks) => blocks
+        };
+        if self.rules.simultaneous {
+            let commitment = self.commit(piece_index, orientation, position);
+            self.notify(|observer, game| observer.on_committed(game, &commitment));
+            self.switch_to_next_player();
+            return Ok(true);
+        }
+        let player_index = self.players.active_player_index;
+        let piece_id = self.active_player_pieces()[piece_index].id();
+        self.board.occupy(blocks, piece_id, player_index);
+
+        let piece = self.active_player_mut().take_piece(piece_index);
+        self.taken_pieces.push(TakenPiece { rack_index: piece_index, piece });
+        let played = Move { player: player_index, piece_id, orientation, position };
+        self.history.push(played.clone());
+        self.active_player_mut().first_move = false;
+        self.notify(|observer, game| observer.on_piece_placed(game, &played));
+        self.switch_to_next_player();
+        Ok(true)
+    }
+
+    /// Reverts the last placement. The piece goes back to its place in the rack and the player
+    /// who placed it is to move again. Returns the move that was taken back. The rounds of the
+    /// simultaneous variant cannot be taken back.
+    pub fn take_back(&mut self) -> Option<Move> {
+        if self.rules.simultaneous {
+            return None;
+        }
+        let played = self.history.pop()?;
+        let taken = self.taken_pieces.pop().expect("Every move takes a piece from a rack");
+        // the frontiers are only ever updated incrementally, so the board is rebuilt from the
+        // remaining placements
+        let mut placements = std::mem::take(&mut self.board.placements);
+        placements.pop();
+        self.board = Board::new(&self.rules, self.players.players.len());
+        for placement in placements {
+            self.board.occupy(placement.blocks, placement.piece_id, placement.player);
+        }
+
+        let has_placed = self.board.placements.iter().any(|placement| placement.player == played.player);
+        let player = &mut self.players.players[played.player];
+        player.available_pieces.insert(taken.rack_index, taken.piece);
+        player.first_move = !has_placed;
+        player.status = PlayerStatus::Active;
+        if self.rules.skip_blocked_players {
+            for (player_index, player) in self.players.players.iter_mut().enumerate() {
+                if player.status == PlayerStatus::Blocked && self.board.has_legal_move(&player.available_pieces, player_index, self.rules.allow_flips) {
+                    player.status = PlayerStatus::Active;
+                }
+            }
+        }
+
+        self.players.active_player_index = played.player;
+        if let Some(clock) = &mut self.clock {
+            clock.start(played.player, Instant::now());
+        }
+        self.notify(|observer, game| observer.on_turn_changed(game, played.player));
+        Some(played)
+    }
+
+    fn placement_mask(&self, piece_index: usize, orientation: usize, position: &Position) -> Result<Bitboard, GameError> {
+        if self.draft.is_some() {
+            return Err(GameError::DraftInProgress);
+        }
+        let piece = self.active_player_pieces().get(piece_index).ok_or(GameError::NoSuchPiece(piece_index))?;
+        let blocks = piece.orientation(orientation)
+            .ok_or_else(|| GameError::NoSuchOrientation { piece: piece.name().to_string(), orientation })?;
+        if blocks.is_mirrored() && !self.rules.allow_flips {
+            return Err(GameError::FlipsNotAllowed);
+        }
+        let mask = self.board.piece_mask(blocks, position)?;
+        self.board.check_placement(&mask, self.players.active_player_index)?;
+        Ok(mask)
+    }
+
+    /// Hands the turn to the next player. A player who passes without a legal move left is out
+    /// of the game, this is only needed if blocked players are not skipped automatically.
+    pub fn pass(&mut self) {
+        if self.draft.is_some() {
+            return;
+        }
+        let player_index = self.players.active_player_index;
+        let player = &self.players.players[player_index];
+        if !self.board.has_legal_move(&player.available_pieces, player_index, self.rules.allow_flips) {
+            self.active_player_mut().status = PlayerStatus::Blocked;
+            self.notify(|observer, game| observer.on_player_blocked(game, player_index));
+        }
+        self.notify(|observer, game| observer.on_passed(game, player_index));
+        self.switch_to_next_player();
+    }
+
+    /// Gives up the player, e.g. because they left a network game for good. The others play on,
+    /// the turn passes on if it was the player's.
+    pub fn resign(&mut self, player_index: usize) {
+        if self.players.players[player_index].status != PlayerStatus::Active {
+            return;
+        }
+        self.players.players[player_index].status = PlayerStatus::Resigned;
+        if player_index == self.players.active_player_index {
+            self.switch_to_next_player();
+        }
+    }
+
+    /// Changes the name the player is shown with, e.g. to the nickname of a client.
+    pub fn rename_player(&mut self, player_index: usize, name: String) {
+        self.players.players[player_index].name = name;
+    }
+
+    /// Takes over the state of `other`, e.g. the game of the host in a network game. The
+    /// observers of this game stay, they are not notified about the changes.
+    pub fn sync_with(&mut self, other: Game) {
+        let observers = std::mem::take(&mut self.observers);
+        *self = other;
+        self.observers = observers;
+    }
+
+    /// Starts over with the same rules and the racks the players started this game with, e.g. the
+    /// pool again in the draft variant. Nothing is drawn, the player after the one who started
+    /// this game starts. The clock starts over with the same time control, the observers stay.
+    pub fn rematch(&mut self) {
+        let mut players = self.players.players.clone();
+        for (taken, played) in self.taken_pieces.iter().zip(&self.history).rev() {
+            players[played.player].available_pieces.insert(taken.rack_index, taken.piece.clone());
+        }
+        for player in &mut players {
+            player.first_move = true;
+            player.status = PlayerStatus::Active;
+        }
+        let starting_player = (self.starting_player + 1) % players.len();
+        let mut rematch = Game::starting_with(self.rules.clone(), Players::new(players), self.seed.wrapping_add(1), Some(starting_player));
+        if let Some(clock) = &self.clock {
+            rematch.start_clock(clock.time_control().clone(), Instant::now());
+        }
+        self.sync_with(rematch);
+    }
+
+    pub fn add_observer(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Starts a chess clock for every player, the clock of the active player runs from `now`.
+    pub fn start_clock(&mut self, time_control: TimeControl, now: Instant) {
+        let mut clock = Clock::new(time_control, self.players.players.len());
+        clock.start(self.players.active_player_index, now);
+        self.clock = Some(clock);
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    pub fn remaining_time(&self, player_index: usize, now: Instant) -> Option<Duration> {
+        self.clock.as_ref().map(|clock| clock.remaining(player_index, now))
+    }
+
+    /// Advances the clock to `now`. If the active player ran out of time, the configured
+    /// timeout action is applied and the index of that player is returned.
+    pub fn tick(&mut self, now: Instant) -> Option<usize> {
+        // the clocks only start counting once the draft is over
+        let clock = self.clock.as_ref().filter(|_| self.draft.is_none())?;
+        let player_index = clock.expired_player(now)?;
+        if clock.time_control().timeout_action == TimeoutAction::Forfeit {
+            self.active_player_mut().status = PlayerStatus::Resigned;
+        }
+        self.switch_to_next_player_at(now);
+        Some(player_index)
+    }
+
+    /// The game is over once no player can take another turn.
+    pub fn is_over(&self) -> bool {
+        self.players.players.iter().all(|player| player.status != PlayerStatus::Active)
+    }
+
+    pub fn active_player(&self) -> &Player {
+        &self.players.players[self.players.active_player_index]
+    }
+
+    /// A Zobrist hash of the covered cells and the player to move. Equal positions reached
+    /// through different move orders share the same hash.
+    pub fn position_hash(&self) -> u64 {
+        self.board.hash ^ zobrist::turn_key(self.players.active_player_index)
+    }
+
+    /// Placed squares and reachable free area per player, see `OccupancyStats`.
+    pub fn occupancy_stats(&self) -> OccupancyStats {
+        self.board.occupancy_stats()
+    }
+
+    /// The anchors the active player would gain and the anchors of others the blocks would cover.
+    pub fn anchor_change(&self, blocks: &Bitboard) -> AnchorChange {
+        self.board.anchor_change(blocks, self.players.active_player_index)
+    }
+
+    /// Cells where the next piece of the given player may attach.
+    pub fn anchors(&self, player_index: usize) -> &Bitboard {
+        self.board.anchors(player_index)
+    }
+
+    pub fn active_player_index(&self) -> usize {
+        self.players.active_player_index
+    }
+
+    /// How many moves have been played, without copying them like `replay` does.
+    pub fn moves_played(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The move played last, `None` before the first move.
+    pub fn last_move(&self) -> Option<&Move> {
+        self.history.last()
+    }
+
+    /// Returns the moves played so far, which can be applied to a copy of the initial game.
+    pub fn replay(&self) -> Replay {
+        let metadata = ReplayMetadata { seed: self.seed, random_decisions: self.random_decisions.clone() };
+        Replay::new(metadata, self.history.clone())
+    }
+
+    pub fn get_color_map(&self) -> HashMap<usize, (Color, Color)> {
+        self.players()
+            .iter()
+            .enumerate()
+            .map(|(id, player)| (id, (player.color, player.secondary_color)))
+            .collect::<HashMap<usize, (Color, Color)>>()
+    }
+
+    fn switch_to_next_player(&mut self) {
+        self.switch_to_next_player_at(Instant::now())
+    }
+
+    fn switch_to_next_player_at(&mut self, now: Instant) {
+        if self.rules.simultaneous && self.pass_to_next_committer(now) {
+            return;
+        }
+        let was_over = self.is_over();
+        for player_index in self.update_player_statuses() {
+            self.notify(|observer, game| observer.on_player_blocked(game, player_index));
+        }
+        self.players.switch_to_next_player();
+        if let Some(clock) = &mut self.clock {
+            match self.players.players[self.players.active_player_index].status {
+                PlayerStatus::Active => clock.start(self.players.active_player_index, now),
+                _ => clock.stop(now),
+            }
+        }
+        match self.is_over() {
+            true if !was_over => self.notify(|observer, game| observer.on_game_over(game)),
+            true => (),
+            false => {
+                let player_index = self.players.active_player_index;
+                self.notify(|observer, game| observer.on_turn_changed(game, player_index))
+            }
+        }
+    }
+
+    /// Retires active players who placed all their pieces or have no legal move left, and
+    /// returns the players that became blocked.
+    fn update_player_statuses(&mut self) -> Vec<usize> {
+        let mut blocked = vec![];
+        for (player_index, player) in self.players.players.iter_mut().enumerate() {
+            if player.status != PlayerStatus::Active {
+                continue;
+            }
+            if player.available_pieces.is_empty() {
+                player.status = PlayerStatus::Finished;
+            } else if self.rules.skip_blocked_players && !self.board.has_legal_move(&player.available_pieces, player_index, self.rules.allow_flips) {
+                player.status = PlayerStatus::Blocked;
+                blocked.push(player_index);
+            }
+        }
+        blocked
+    }
+
+    /// Observers are taken out of the game while they are notified, so that they can look at the
+    /// whole game.
+    fn notify(&mut self, event: impl Fn(&mut dyn GameObserver, &Game)) {
+        let mut observers = std::mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    fn active_player_mut(&mut self) -> &mut Player {
+        &mut self.players.players[self.players.active_player_index]
+    }
+}
+
+/// Copies of a game, e.g. for replays or analysis, start without observers so that exploring
+/// moves on the copy does not notify anyone.
+impl Clone for Game {
+    fn clone(&self) -> Self {
+        Game {
+            rules: self.rules.clone(),
+            board: self.board.clone(),
+            players: self.players.clone(),
+            history: self.history.clone(),
+            seed: self.seed,
+            rng: self.rng.clone(),
+            random_decisions: self.random_decisions.clone(),
+            starting_player: self.starting_player,
+            clock: self.clock.clone(),
+            observers: vec![],
+            taken_pieces: self.taken_pieces.clone(),
+            draft: self.draft.clone(),
+            commitments: self.commitments.clone(),
+            revealed: self.revealed.clone(),
+        }
+    }
+}
+
+impl Board {
+    fn new(rules: &GameRules, num_players: usize) -> Self {
+        let GameRules { width, height, .. } = *rules;
+        assert!(width <= MAX_BOARD_WIDTH, "Boards can be at most {MAX_BOARD_WIDTH} columns wide");
+        let mut blocked = Bitboard::new(height);
+        rules.blocked_cells.iter().for_each(|cell| blocked.insert(cell));
+        let mut board = Board {
+            width,
+            height,
+            player_boards: vec![],
+            occupancy: blocked.clone(),
+            blocked,
+            free_placement: rules.free_placement,
+            placements: vec![],
+            hash: 0,
+        };
+        let mut anchors = match &rules.start {
+            Start::Corners => board.corners(),
+            Start::Squares(squares) => {
+                let mut anchors = Bitboard::new(height);
+                squares.iter().for_each(|square| anchors.insert(square));
+                anchors
+            }
+        };
+        anchors.subtract(&board.blocked);
+        let player_board = PlayerBoard {
+            blocks: Bitboard::new(height),
+            forbidden: Bitboard::new(height),
+            anchors,
+        };
+        board.player_boards = vec![player_board; num_players];
+        board
+    }
+
+    /// Checks and places a piece in one step, games go through `Game::place_piece` instead.
+    #[cfg(test)]
+    fn place_piece(&mut self, orientation: &Orientation, piece_id: usize, offset: Position, player_index: usize) -> Result<bool, String> {
+        let blocks = self.piece_mask(orientation, &offset).map_err(|error| error.to_string())?;
+        if self.check_placement(&blocks, player_index).is_err() {
+            return Ok(false);
+        }
+
+        self.occupy(blocks, piece_id, player_index);
+        Ok(true)
+    }
+
+    /// Records a placement that has already been checked.
+    fn occupy(&mut self, blocks: Bitboard, piece_id: usize, player_index: usize) {
+        self.occupy_blocks(&blocks, player_index);
+        self.placements.push(Placement { player: player_index, piece_id, blocks });
+    }
+
+    /// Reconstructs the state of a single tile from the bitboards. Only meant for rendering and
+    /// other code that looks at individual tiles, placement checks work on whole bitboards.
+    pub fn get_state_on_position(&self, position: &Position) -> Result<State, String> {
+        position.check_within_bounds(self.width, self.height)?;
+        if !self.occupancy.contains(position) {
+            return Ok(State::Free);
+        }
+        if self.blocked.contains(position) {
+            return Ok(State::Blocked);
+        }
+        let (index, placement) = self.placements
+            .iter()
+            .enumerate()
+            .find(|(_, placement)| placement.blocks.contains(position))
+            .expect("Occupied tile without placement");
+        Ok(State::Occupied { player: placement.player, piece_id: placement.piece_id, move_number: index + 1 })
+    }
+
+    /// The free cells a piece of the given player has to cover to be placed legally. Before the
+    /// first move these are the board corners.
+    pub fn anchors(&self, player_index: usize) -> &Bitboard {
+        &self.player_boards[player_index].anchors
+    }
+
+    /// Whether any orientation of any of the pieces can be placed somewhere by the player.
+    fn has_legal_move(&self, pieces: &[Piece], player_index: usize, allow_flips: bool) -> bool {
+        self.legal_placements(pieces, player_index, allow_flips).next().is_some()
+    }
+
+    /// Every placement of the pieces the player could make, lazily so that callers who only need
+    /// the first one do not pay for the rest.
+    fn legal_placements<'a>(&'a self, pieces: &'a [Piece], player_index: usize, allow_flips: bool) -> impl Iterator<Item=LegalMove> + 'a {
+        let anchors = &self.player_boards[player_index].anchors;
+        let playable = !anchors.is_empty() || self.free_placement;
+        pieces.iter()
+            .enumerate()
+            .filter(move |_| playable)
+            .flat_map(|(piece_index, piece)| piece.orientations().iter().enumerate().map(move |(orientation_index, orientation)| (piece_index, orientation_index, orientation)))
+            .filter(move |(_, _, orientation)| allow_flips || !orientation.is_mirrored())
+            .flat_map(move |(piece_index, orientation_index, orientation)| {
+                self.candidate_offsets(orientation, player_index)
+                    .into_iter()
+                    .filter_map(move |position| self.piece_mask(orientation, &position)
+                        .ok()
+                        .filter(|blocks| self.check_placement(blocks, player_index).is_ok())
+                        .map(|blocks| LegalMove { piece_index, orientation: orientation_index, position, blocks }))
+            })
+    }
+
+    /// The offsets worth checking for the orientation, in reading order. Unless pieces may go
+    /// anywhere, one of the blocks has to cover an anchor, which rules out most of the board.
+    fn candidate_offsets(&self, orientation: &Orientation, player_index: usize) -> Vec<Position> {
+        if self.free_placement {
+            return (0..=self.height as i32 - orientation.num_lines() as i32)
+                .flat_map(|y| (0..=self.width as i32 - orientation.num_columns() as i32).map(move |x| Position { x, y }))
+                .collect();
+        }
+        let mut offsets = self.player_boards[player_index].anchors.positions()
+            .flat_map(|anchor| orientation.blocks().map(move |block| &anchor - &block))
+            .collect::<Vec<_>>();
+        offsets.sort_by_key(|offset| (offset.y, offset.x));
+        offsets.dedup();
+        offsets
+    }
+
+    fn piece_mask(&self, orientation: &Orientation, offset: &Position) -> Result<Bitboard, GameError> {
+        let mut mask = Bitboard::new(self.height);
+        for block in orientation.blocks() {
+            let position = &block + offset;
+            if position.check_within_bounds(self.width, self.height).is_err() {
+                return Err(GameError::OutOfBounds(position));
+            }
+            mask.insert(&position);
+        }
+        Ok(mask)
+    }
+
+    /// Marks the blocks as occupied and updates the frontier of every player incrementally: the
+    /// new blocks are no longer available as anchors to anyone, and the placing player gains the
+    /// diagonal neighbours of the new blocks as anchors unless they share an edge with one of
+    /// their pieces.
+    fn occupy_blocks(&mut self, blocks: &Bitboard, player_index: usize) {
+        self.hash = blocks.positions().fold(self.hash, |hash, position| hash ^ zobrist::key(&position, player_index));
+        self.occupancy.union_with(blocks);
+        for player_board in self.player_boards.iter_mut() {
+            player_board.anchors.subtract(blocks);
+        }
+
+        let player_board = &mut self.player_boards[player_index];
+        if player_board.blocks.is_empty() {
+            // the corners only serve as anchors for the first piece
+            player_board.anchors = Bitboard::new(self.height);
+        }
+        player_board.blocks.union_with(blocks);
+        player_board.forbidden.union_with(&blocks.edge_neighbours(self.width));
+        player_board.anchors.union_with(&blocks.diagonal_neighbours(self.width));
+        player_board.anchors.subtract(&player_board.forbidden);
+        player_board.anchors.subtract(&self.occupancy);
+    }
+
+    fn check_placement(&self, blocks: &Bitboard, player_index: usize) -> Result<(), GameError> {
+        let player_board = &self.player_boards[player_index];
+        if blocks.intersects(&self.occupancy) {
+            Err(GameError::Overlap)
+        } else if self.free_placement {
+            Ok(())
+        } else if blocks.intersects(&player_board.forbidden) {
+            Err(GameError::EdgeContact)
+        } else if !blocks.intersects(&player_board.anchors) {
+            Err(GameError::NoCornerContact)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn corners(&self) -> Bitboard {
+        let mut corners = Bitboard::new(self.height);
+        for x in [0, self.width as i32 - 1] {
+            for y in [0, self.height as i32 - 1] {
+                corners.insert(&Position { x, y });
+            }
+        }
+        corners
+    }
+}
+
+impl Position {
+    pub fn check_within_bounds(&self, width: u16, height: u16) -> Result<(), String> {
+        match self.cell() {
+            Some((column, row)) if column < width as usize && row < height as usize => Ok(()),
+            _ => Err(format!("Out of bounds ({}, {})", self.x, self.y))
+        }
+    }
+
+    /// The column and row index of the position, or `None` if it lies left of or above the
+    /// board.
+    pub fn cell(&self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
+    }
+
+    /// Rotates the position clockwise inside a square box whose last row and column are `max`.
+    pub fn rotate_clockwise(&mut self, max: i32) {
+        let temp_x = self.x;
+        self.x = max - self.y;
+        self.y = temp_x;
+    }
+}
+
+impl std::ops::Add for Position {
+    type Output = Position;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Position {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl std::ops::Add for &Position {
+    type Output = Position;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Position {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl std::ops::Sub for &Position {
+    type Output = Position;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Position {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Players {
+    pub fn new(players: Vec<Player>) -> Self {
+        Players {
+            players,
+            active_player_index: 0,
+        }
+    }
+
+    /// Moves the turn to the next active player, if there is any.
+    pub fn switch_to_next_player(&mut self) {
+        let num_players = self.players.len();
+        if let Some(offset) = (1..=num_players).find(|offset| self.players[(self.active_player_index + offset) % num_players].status == PlayerStatus::Active) {
+            self.active_player_index = (self.active_player_index + offset) % num_players;
+        }
+    }
+}
+
+impl Player {
+    pub fn new(name: String, color: Color, secondary_color: Color, available_pieces: Vec<Piece>) -> Self {
+        Player {
+            name,
+            color,
+            secondary_color,
+            available_pieces,
+            first_move: true,
+            status: PlayerStatus::Active,
+            kind: PlayerKind::Human,
+        }
+    }
+
+    fn take_piece(&mut self, index: usize) -> Piece {
+        self.available_pieces.remove(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn piece_1x1() -> Piece {
+        Piece::new(vec![Position { x: 0, y: 0 }], 1)
+    }
+
+    fn domino() -> Piece {
+        Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2)
+    }
+
+    fn place(board: &mut Board, piece: &Piece, x: i32, y: i32, player_index: usize) -> Result<bool, String> {
+        board.place_piece(piece.orientation(0).unwrap(), piece.id(), Position { x, y }, player_index)
+    }
+
+    #[test]
+    fn should_place_block() {
+        let mut board = Board::new(&GameRules::with_board_size(1, 1), 1);
+        let was_placed = place(&mut board, &piece_1x1(), 0, 0, 0).unwrap();
+        assert!(was_placed);
+
+        assert_eq!(
+            board.get_state_on_position(&Position { x: 0, y: 0 }).unwrap(),
+            State::Occupied { player: 0, piece_id: 0, move_number: 1 }
+        );
+
+        let was_placed = place(&mut board, &piece_1x1(), 0, 0, 0).unwrap();
+        assert!(!was_placed)
+    }
+
+    #[test]
+    fn should_record_piece_and_move_number_on_tiles() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        let mut piece = piece_1x1();
+        piece.id = 7;
+        place(&mut board, &piece_1x1(), 0, 0, 0).unwrap();
+        place(&mut board, &piece, 2, 2, 1).unwrap();
+
+        assert_eq!(
+            board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(),
+            State::Occupied { player: 1, piece_id: 7, move_number: 2 }
+        );
+    }
+
+    #[test]
+    fn should_choose_same_starting_player_for_same_seed() {
+        let players = || Players::new((0..4).map(|index| Player::new(index.to_string(), Color::Green, Color::LightGreen, vec![])).collect());
+        let starting_players = (0..10)
+            .map(|seed| Game::with_seed(GameRules::with_board_size(20, 20), players(), seed).active_player_index())
+            .collect::<Vec<_>>();
+        let repeated_starting_players = (0..10)
+            .map(|seed| Game::with_seed(GameRules::with_board_size(20, 20), players(), seed).active_player_index())
+            .collect::<Vec<_>>();
+
+        assert_eq!(starting_players, repeated_starting_players);
+    }
+
+    #[test]
+    fn should_only_allow_corner_contact_with_own_pieces() {
+        let mut board = Board::new(&GameRules::with_board_size(5, 5), 2);
+        place(&mut board, &domino(), 0, 0, 0).unwrap();
+
+        let edge_contact = place(&mut board, &domino(), 2, 0, 0).unwrap();
+        let no_contact = place(&mut board, &domino(), 3, 3, 0).unwrap();
+        let corner_contact = place(&mut board, &domino(), 2, 1, 0).unwrap();
+        let other_player_edge_contact = place(&mut board, &domino(), 0, 1, 1).unwrap();
+
+        assert!(!edge_contact);
+        assert!(!no_contact);
+        assert!(corner_contact);
+        assert!(!other_player_edge_contact);
+    }
+
+    #[test]
+    fn should_track_anchors_incrementally() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        assert!(place(&mut board, &piece_1x1(), 0, 0, 0).unwrap());
+        assert!(place(&mut board, &piece_1x1(), 2, 2, 1).unwrap());
+        assert_eq!(board.anchors(0).positions().collect::<Vec<_>>(), vec![Position { x: 1, y: 1 }]);
+
+        assert!(place(&mut board, &piece_1x1(), 1, 1, 1).unwrap());
+        assert!(board.anchors(0).is_empty());
+        assert_eq!(board.anchors(1).positions().collect::<Vec<_>>(), vec![Position { x: 2, y: 0 }, Position { x: 0, y: 2 }]);
+    }
+
+    #[test]
+    fn should_hash_positions_independent_of_move_order() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        place(&mut board, &piece_1x1(), 0, 0, 0).unwrap();
+        place(&mut board, &piece_1x1(), 2, 2, 1).unwrap();
+
+        let mut transposed_board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        place(&mut transposed_board, &piece_1x1(), 2, 2, 1).unwrap();
+        place(&mut transposed_board, &piece_1x1(), 0, 0, 0).unwrap();
+
+        let mut other_board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        place(&mut other_board, &piece_1x1(), 0, 0, 1).unwrap();
+        place(&mut other_board, &piece_1x1(), 2, 2, 0).unwrap();
+
+        assert_eq!(board.hash, transposed_board.hash);
+        assert_ne!(board.hash, other_board.hash);
+    }
+
+    #[test]
+    fn should_skip_player_who_forfeited_on_time() {
+        let players = Players::new((0..3).map(|index| Player::new(index.to_string(), Color::Green, Color::LightGreen, vec![piece_1x1()])).collect());
+        let mut game = Game::new(GameRules::with_board_size(5, 5), players);
+        let start = Instant::now();
+        let time_control = TimeControl { budget: Duration::from_secs(10), seat_budgets: vec![], increment: Duration::ZERO, timeout_action: TimeoutAction::Forfeit };
+        game.start_clock(time_control, start);
+        let first_player = game.active_player_index();
+
+        assert_eq!(game.tick(start + Duration::from_secs(5)), None);
+        assert_eq!(game.tick(start + Duration::from_secs(10)), Some(first_player));
+        assert_eq!(game.players()[first_player].status, PlayerStatus::Resigned);
+
+        game.switch_to_next_player_at(start + Duration::from_secs(11));
+        game.switch_to_next_player_at(start + Duration::from_secs(12));
+        assert_eq!(game.active_player_index(), (first_player + 1) % 3);
+    }
+
+    #[test]
+    fn should_pass_the_turn_of_a_resigned_player() {
+        let players = Players::new((0..2).map(|index| Player::new(index.to_string(), Color::Green, Color::LightGreen, vec![piece_1x1()])).collect());
+        let mut game = Game::new(GameRules::with_board_size(5, 5), players);
+        game.players.active_player_index = 0;
+
+        game.resign(1);
+        assert_eq!(game.active_player_index(), 0);
+        game.resign(0);
+        assert_eq!(game.players()[0].status, PlayerStatus::Resigned);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn should_skip_players_without_pieces_or_moves() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino(), domino()]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(2, 2), players);
+        game.players.active_player_index = 0;
+
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert_eq!(game.players()[0].status, PlayerStatus::Finished);
+        assert_eq!(game.active_player_index(), 1);
+
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 1 }).unwrap());
+        assert_eq!(game.players()[1].status, PlayerStatus::Blocked);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn should_count_placed_and_remaining_squares() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino()]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+        game.players.active_player_index = 0;
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+
+        assert_eq!(game.squares_placed(0), 2);
+        assert_eq!(game.squares_remaining(0), 1);
+        assert_eq!(game.pieces_remaining(0), 1);
+        assert_eq!(game.squares_placed(1), 0);
+        assert_eq!(game.squares_remaining(1), 2);
+    }
+
+    #[test]
+    fn should_notify_observers() {
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl GameObserver for Recorder {
+            fn on_piece_placed(&mut self, _game: &Game, played: &Move) {
+                self.0.lock().unwrap().push(format!("placed {}", played.player));
+            }
+
+            fn on_turn_changed(&mut self, _game: &Game, player_index: usize) {
+                self.0.lock().unwrap().push(format!("turn {player_index}"));
+            }
+
+            fn on_player_blocked(&mut self, _game: &Game, player_index: usize) {
+                self.0.lock().unwrap().push(format!("blocked {player_index}"));
+            }
+
+            fn on_game_over(&mut self, game: &Game) {
+                assert!(game.is_over());
+                self.0.lock().unwrap().push("game over".to_string());
+            }
+        }
+
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece_1x1(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino(), domino()]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+        game.players.active_player_index = 0;
+        let events = Arc::new(Mutex::new(vec![]));
+        game.add_observer(Recorder(events.clone()));
+
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 2 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 1 }).unwrap());
+
+        assert_eq!(*events.lock().unwrap(), vec![
+            "placed 0", "turn 1",
+            "placed 1", "blocked 1", "turn 0",
+            "placed 0", "game over",
+        ]);
+    }
+
+    #[test]
+    fn should_check_placement_without_changing_the_game() {
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino()])]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+
+        assert_eq!(game.can_place(0, 0, &Position { x: 1, y: 1 }), Err(GameError::NoCornerContact));
+        assert_eq!(game.can_place(0, 0, &Position { x: 2, y: 0 }), Err(GameError::OutOfBounds(Position { x: 3, y: 0 })));
+        assert_eq!(game.can_place(1, 0, &Position { x: 0, y: 0 }), Err(GameError::NoSuchPiece(1)));
+        assert_eq!(game.can_place(0, 0, &Position { x: 0, y: 0 }), Ok(()));
+        assert_eq!(game.pieces_remaining(0), 1);
+        assert_eq!(game.squares_placed(0), 0);
+
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+    }
+
+    #[test]
+    fn should_start_from_the_squares_of_the_rules() {
+        let rules = GameRules { start: Start::Squares(vec![Position { x: 1, y: 1 }]), ..GameRules::with_board_size(3, 3) };
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece_1x1(), piece_1x1()])]);
+        let game = Game::new(rules, players);
+
+        assert_eq!(game.can_place(0, 0, &Position { x: 0, y: 0 }), Err(GameError::NoCornerContact));
+        assert_eq!(game.can_place(0, 0, &Position { x: 1, y: 1 }), Ok(()));
+    }
+
+    #[test]
+    fn should_only_allow_mirrored_pieces_if_the_rules_do() {
+        let l_piece = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 0, y: 1 }, Position { x: 0, y: 2 }, Position { x: 1, y: 2 }], 3);
+        let mirrored = l_piece.orientations().iter().position(Orientation::is_mirrored).unwrap();
+        let players = || Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![l_piece.clone()])]);
+
+        let game = Game::new(GameRules::with_board_size(5, 5), players());
+        let position = [Position { x: 0, y: 0 }, Position { x: 0, y: 2 }, Position { x: 3, y: 0 }, Position { x: 3, y: 2 }, Position { x: 2, y: 3 }]
+            .into_iter()
+            .find(|position| game.can_place(0, mirrored, position).is_ok())
+            .unwrap();
+        let rules = GameRules { allow_flips: false, ..GameRules::with_board_size(5, 5) };
+        assert_eq!(Game::new(rules, players()).can_place(0, mirrored, &position), Err(GameError::FlipsNotAllowed));
+    }
+
+    #[test]
+    fn should_score_remaining_squares_and_bonuses() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino(), domino()]),
+        ]);
+        let rules = GameRules { teams: vec![vec![0, 1]], ..GameRules::with_board_size(4, 4) };
+        let mut game = Game::new(rules, players);
+        game.players.active_player_index = 0;
+        assert_eq!(game.score(0), -3);
+
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 2, y: 3 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 2, y: 1 }).unwrap());
+
+        assert_eq!(game.score(0), 20);
+        assert_eq!(game.score(1), -2);
+        assert_eq!(game.team_scores(), vec![18]);
+    }
+
+    #[test]
+    fn should_end_solo_game_when_no_piece_fits() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece_1x1(), piece_1x1(), domino()]),
+        ]);
+        let mut game = Game::new(GameRules { width: 2, height: 2, ..GameRules::solo() }, players);
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(!game.is_over());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 1 }).unwrap());
+
+        assert!(game.is_over());
+        assert_eq!(game.players()[0].status, PlayerStatus::Blocked);
+        assert_eq!(game.score(0), 2);
+    }
+
+    #[test]
+    fn should_take_back_last_move() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![piece_1x1()]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+        game.players.active_player_index = 0;
+        assert!(game.place_piece(1, 0, Position { x: 0, y: 0 }).unwrap());
+        let hash = game.position_hash();
+        assert!(game.place_piece(0, 0, Position { x: 2, y: 2 }).unwrap());
+        assert_eq!(game.players()[1].status, PlayerStatus::Finished);
+
+        let undone = game.take_back().unwrap();
+        assert_eq!((undone.player, undone.position), (1, Position { x: 2, y: 2 }));
+        assert_eq!(game.active_player_index(), 1);
+        assert_eq!(game.position_hash(), hash);
+        assert!(game.players()[1].first_move);
+        assert_eq!(game.players()[1].status, PlayerStatus::Active);
+        assert_eq!(game.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(), State::Free);
+
+        game.take_back().unwrap();
+        assert_eq!(game.players()[0].available_pieces, vec![domino(), piece_1x1()]);
+        assert_eq!(game.anchors(0), &game.board.corners());
+        assert!(game.take_back().is_none());
+    }
+
+    #[test]
+    fn should_start_a_rematch_with_the_full_racks_and_the_next_starting_player() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![piece_1x1()]),
+        ]);
+        let mut game = Game::with_seed(GameRules::with_board_size(3, 3), players, 3);
+        let starting_player = game.starting_player();
+        let position = if starting_player == 0 { Position { x: 0, y: 0 } } else { Position { x: 2, y: 2 } };
+        assert!(game.place_piece(game.active_player_pieces().len() - 1, 0, position).unwrap());
+
+        game.rematch();
+        assert!(game.replay().is_empty());
+        assert_eq!(game.starting_player(), 1 - starting_player);
+        assert_eq!(game.active_player_index(), 1 - starting_player);
+        assert!(game.starting_player_draw().is_none());
+        assert_eq!(game.players()[0].available_pieces, vec![domino(), piece_1x1()]);
+        assert_eq!(game.players()[1].available_pieces, vec![piece_1x1()]);
+        assert!(game.players().iter().all(|player| player.first_move && player.status == PlayerStatus::Active));
+    }
+
+    #[test]
+    fn should_keep_blocked_players_until_they_pass() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece_1x1(), piece_1x1()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino(), domino()]),
+        ]);
+        let rules = GameRules { skip_blocked_players: false, ..GameRules::with_board_size(3, 3) };
+        let mut game = Game::new(rules, players);
+        game.players.active_player_index = 0;
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 2 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 1 }).unwrap());
+
+        assert_eq!(game.players()[1].status, PlayerStatus::Active);
+        assert_eq!(game.active_player_index(), 1);
+        game.pass();
+        assert_eq!(game.players()[1].status, PlayerStatus::Blocked);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn should_not_place_pieces_on_blocked_cells() {
+        let rules = GameRules { blocked_cells: vec![Position { x: 0, y: 0 }, Position { x: 1, y: 1 }], ..GameRules::with_board_size(3, 3) };
+        let mut board = Board::new(&rules, 1);
+
+        assert_eq!(board.get_state_on_position(&Position { x: 1, y: 1 }).unwrap(), State::Blocked);
+        assert!(!place(&mut board, &piece_1x1(), 0, 0, 0).unwrap());
+        assert!(place(&mut board, &piece_1x1(), 2, 0, 0).unwrap());
+        assert!(!place(&mut board, &piece_1x1(), 1, 1, 0).unwrap());
+    }
+
+    #[test]
+    fn should_reject_pieces_out_of_bounds() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        assert!(place(&mut board, &domino(), 2, 2, 0).is_err());
+    }
+
+    #[test]
+    fn should_reject_positions_left_of_or_above_the_board() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 1);
+        assert!(Position { x: -1, y: 0 }.check_within_bounds(3, 3).is_err());
+        assert!(place(&mut board, &piece_1x1(), 0, -1, 0).is_err());
+        assert!(!board.occupancy.contains(&Position { x: -1, y: -1 }));
+    }
+
+    #[test]
+    fn should_place_piece_in_rotated_orientation() {
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino()])]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+        let rotated = game.active_player_pieces()[0].orientation(0).unwrap().rotated();
+
+        assert!(game.place_piece(0, rotated, Position { x: 2, y: 1 }).unwrap());
+        assert_eq!(game.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(), State::Occupied { player: 0, piece_id: 0, move_number: 1 });
+    }
+}
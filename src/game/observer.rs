@@ -0,0 +1,27 @@
+use crate::game::Game;
+use crate::game::replay::Move;
+use crate::game::simultaneous::Commitment;
+
+/// Receives notifications about what happens in a game. Observers are registered with
+/// `Game::add_observer` and called after the game has been updated, so they see the new state.
+/// All methods do nothing by default.
+pub trait GameObserver: Send {
+    fn on_piece_placed(&mut self, _game: &Game, _played: &Move) {}
+
+    fn on_turn_changed(&mut self, _game: &Game, _player_index: usize) {}
+
+    /// Called when a player committed a move in the simultaneous variant, before the turn
+    /// changes. The move is placed once the round is revealed.
+    fn on_committed(&mut self, _game: &Game, _commitment: &Commitment) {}
+
+    /// Called when a player passes, before the turn changes.
+    fn on_passed(&mut self, _game: &Game, _player_index: usize) {}
+
+    /// Called when a player took a piece from the draft pool, before the turn changes.
+    fn on_draft_pick(&mut self, _game: &Game, _player_index: usize, _pool_index: usize) {}
+
+    /// Called once for every player who has no legal move left.
+    fn on_player_blocked(&mut self, _game: &Game, _player_index: usize) {}
+
+    fn on_game_over(&mut self, _game: &Game) {}
+}
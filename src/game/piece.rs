@@ -0,0 +1,313 @@
+use std::hash::{Hash, Hasher};
+
+use crate::game::Position;
+
+/// Pieces compare equal if they have the same shape, regardless of their name, id, or the
+/// orientation they were defined in.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Piece {
+    pub(crate) id: usize,
+    pub(crate) name: String,
+    orientations: Vec<Orientation>,
+}
+
+/// One of the up to eight distinct ways a piece can lie on the board. Orientations are computed
+/// once when the piece is created, rotating or flipping a piece only moves to another index of
+/// the orientation table.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+    blocks: Vec<Position>,
+    num_lines: u16,
+    num_columns: u16,
+    pub(crate) bounding_box_offset: Position,
+    rotated: usize,
+    flipped: usize,
+    mirrored: bool,
+}
+
+/// A transformation of the piece as defined in the resource file: optionally mirrored along the
+/// vertical axis, then rotated clockwise `rotations` times.
+#[derive(Clone, Copy)]
+struct Transformation {
+    flipped: bool,
+    rotations: u16,
+}
+
+impl Piece {
+    /// Creates a piece from blocks given in a square box of `box_size` rows and columns. The
+    /// piece is rotated and mirrored inside that box, which keeps rotations centered on the box.
+    pub fn new(blocks: Vec<Position>, box_size: i32) -> Self {
+        let transformations = Transformation::all().collect::<Vec<_>>();
+        let transformed_blocks = transformations.iter()
+            .map(|transformation| transformation.apply(&blocks, box_size))
+            .collect::<Vec<_>>();
+
+        // maps every transformation to the first transformation producing the same shape
+        let representatives = transformed_blocks.iter()
+            .map(|blocks| transformed_blocks.iter().position(|other| Self::normalize(other) == Self::normalize(blocks)).unwrap())
+            .collect::<Vec<_>>();
+        let mut unique = representatives.clone();
+        unique.sort();
+        unique.dedup();
+        let orientation_index = |transformation: Transformation| {
+            let representative = representatives[transformation.index()];
+            unique.iter().position(|index| *index == representative).unwrap()
+        };
+
+        let orientations = unique.iter()
+            .map(|index| {
+                let transformation = transformations[*index];
+                Orientation::new(
+                    &transformed_blocks[*index],
+                    transformation.flipped,
+                    orientation_index(transformation.rotate()),
+                    orientation_index(transformation.flip()),
+                )
+            })
+            .collect();
+        Piece { id: 0, name: String::new(), orientations }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn orientations(&self) -> &[Orientation] {
+        &self.orientations
+    }
+
+    pub fn orientation(&self, index: usize) -> Option<&Orientation> {
+        self.orientations.get(index)
+    }
+
+    /// The blocks of the piece as it is defined in the resource file.
+    pub fn blocks(&self) -> impl Iterator<Item=Position> + '_ {
+        self.orientations[0].blocks()
+    }
+
+    /// The blocks of the piece in a normal form shared by all its rotations and reflections:
+    /// the smallest of its orientations when comparing blocks row by row.
+    pub fn canonical(&self) -> Vec<Position> {
+        self.orientations.iter()
+            .map(|orientation| orientation.blocks.clone())
+            .min_by_key(|blocks| blocks.iter().map(|block| (block.y, block.x)).collect::<Vec<_>>())
+            .expect("Pieces have at least one orientation")
+    }
+
+    /// The number of squares the piece covers.
+    pub fn size(&self) -> usize {
+        self.orientations[0].blocks.len()
+    }
+
+    pub fn num_lines(&self) -> u16 {
+        self.orientations[0].num_lines
+    }
+
+    pub fn num_columns(&self) -> u16 {
+        self.orientations[0].num_columns
+    }
+
+    fn normalize(blocks: &[Position]) -> Vec<Position> {
+        let offset = Position { x: Self::min_x(blocks), y: Self::min_y(blocks) };
+        let mut normalized = blocks.iter().map(|block| block - &offset).collect::<Vec<_>>();
+        normalized.sort_by_key(|block| (block.y, block.x));
+        normalized
+    }
+
+    fn min_x(blocks: &[Position]) -> i32 {
+        blocks.iter().map(|block| block.x).min().unwrap()
+    }
+
+    fn min_y(blocks: &[Position]) -> i32 {
+        blocks.iter().map(|block| block.y).min().unwrap()
+    }
+}
+
+impl PartialEq for Piece {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for Piece {}
+
+impl Hash for Piece {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+}
+
+impl Orientation {
+    fn new(blocks: &[Position], mirrored: bool, rotated: usize, flipped: usize) -> Self {
+        let bounding_box_offset = Position { x: Piece::min_x(blocks), y: Piece::min_y(blocks) };
+        let blocks = Piece::normalize(blocks);
+        let num_columns = blocks.iter().map(|block| block.x).max().unwrap() as u16 + 1;
+        let num_lines = blocks.iter().map(|block| block.y).max().unwrap() as u16 + 1;
+        Orientation { blocks, num_lines, num_columns, bounding_box_offset, rotated, flipped, mirrored }
+    }
+
+    /// The blocks of this orientation, moved so that the bounding box starts at the origin.
+    pub fn blocks(&self) -> impl Iterator<Item=Position> + '_ {
+        self.blocks.iter().cloned()
+    }
+
+    pub fn num_lines(&self) -> u16 {
+        self.num_lines
+    }
+
+    pub fn num_columns(&self) -> u16 {
+        self.num_columns
+    }
+
+    /// Index of the orientation reached by rotating this one clockwise.
+    pub fn rotated(&self) -> usize {
+        self.rotated
+    }
+
+    /// Whether this orientation can only be reached by mirroring the piece, not by rotating it.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirrored
+    }
+
+    /// Index of the orientation reached by mirroring this one along the vertical axis.
+    pub fn flipped(&self) -> usize {
+        self.flipped
+    }
+}
+
+impl Transformation {
+    fn all() -> impl Iterator<Item=Transformation> {
+        [false, true].into_iter().flat_map(|flipped| (0..4).map(move |rotations| Transformation { flipped, rotations }))
+    }
+
+    fn index(&self) -> usize {
+        self.flipped as usize * 4 + self.rotations as usize
+    }
+
+    fn rotate(&self) -> Transformation {
+        Transformation { flipped: self.flipped, rotations: (self.rotations + 1) % 4 }
+    }
+
+    /// Mirroring a rotated piece equals rotating the mirrored piece in the opposite direction.
+    fn flip(&self) -> Transformation {
+        Transformation { flipped: !self.flipped, rotations: (4 - self.rotations) % 4 }
+    }
+
+    fn apply(&self, blocks: &[Position], box_size: i32) -> Vec<Position> {
+        let max = box_size - 1;
+        let mut blocks = blocks.to_vec();
+        for block in blocks.iter_mut() {
+            if self.flipped {
+                block.x = max - block.x;
+            }
+            (0..self.rotations).for_each(|_| block.rotate_clockwise(max));
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    use crate::game::piece_set::read_standard_piece_set;
+
+    use super::*;
+
+    fn positions(blocks: &[(i32, i32)]) -> Vec<Position> {
+        blocks.iter().map(|(x, y)| Position { x: *x, y: *y }).collect()
+    }
+
+    #[test]
+    fn should_rotate_block() {
+        let piece = Piece::new(positions(&[(0, 1), (1, 1), (2, 1)]), 3);
+        assert_eq!(piece.orientations().len(), 2);
+
+        let rotated = piece.orientation(piece.orientation(0).unwrap().rotated()).unwrap();
+        assert_eq!(rotated.blocks, positions(&[(0, 0), (0, 1), (0, 2)]));
+        assert_eq!(rotated.bounding_box_offset, Position { x: 1, y: 0 });
+
+        assert_eq!(rotated.rotated(), 0);
+    }
+
+    #[test]
+    fn should_rotate_box_block() {
+        let piece = Piece::new(positions(&[(0, 0), (1, 0), (0, 1), (1, 1)]), 2);
+        assert_eq!(piece.orientations().len(), 1);
+        assert_eq!(piece.orientation(0).unwrap().rotated(), 0);
+        assert_eq!(piece.orientation(0).unwrap().flipped(), 0);
+    }
+
+    #[test]
+    fn should_compute_all_orientations_of_asymmetric_piece() {
+        let f_piece = Piece::new(positions(&[(0, 0), (1, 0), (1, 1), (2, 1), (1, 2)]), 3);
+        assert_eq!(f_piece.orientations().len(), 8);
+
+        for (index, orientation) in f_piece.orientations().iter().enumerate() {
+            let flipped = f_piece.orientation(orientation.flipped()).unwrap();
+            assert_eq!(flipped.flipped(), index);
+
+            let mut rotated = index;
+            (0..4).for_each(|_| rotated = f_piece.orientation(rotated).unwrap().rotated());
+            assert_eq!(rotated, index);
+        }
+    }
+
+    #[test]
+    fn should_flip_piece() {
+        let l_piece = Piece::new(positions(&[(0, 0), (0, 1), (0, 2), (1, 2)]), 3);
+        let flipped = l_piece.orientation(l_piece.orientation(0).unwrap().flipped()).unwrap();
+        assert_eq!(flipped.blocks, positions(&[(1, 0), (1, 1), (0, 2), (1, 2)]));
+    }
+
+    #[test]
+    fn should_compare_pieces_by_shape() {
+        let l_piece = Piece::new(positions(&[(0, 0), (0, 1), (0, 2), (1, 2)]), 3);
+        let mut j_piece = Piece::new(positions(&[(1, 0), (1, 1), (0, 2), (1, 2)]), 3);
+        j_piece.name = "J4".to_string();
+        let t_piece = Piece::new(positions(&[(0, 0), (1, 0), (2, 0), (1, 1)]), 3);
+
+        assert_eq!(l_piece.canonical(), j_piece.canonical());
+        assert_eq!(l_piece, j_piece);
+        assert_ne!(l_piece, t_piece);
+        assert_eq!(HashSet::from([l_piece, j_piece, t_piece]).len(), 2);
+    }
+
+    #[test]
+    fn should_return_to_start_after_four_rotations() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let max = rng.gen_range(0..8);
+            let start = Position { x: rng.gen_range(0..=max), y: rng.gen_range(0..=max) };
+            let mut position = start.clone();
+            for rotation in 1..=4 {
+                position.rotate_clockwise(max);
+                assert!(position.cell().is_some_and(|(x, y)| x <= max as usize && y <= max as usize));
+                assert_eq!(position == start, rotation == 4 || (start.x == start.y && start.x * 2 == max));
+            }
+        }
+    }
+
+    #[test]
+    fn should_cycle_through_orientations_of_standard_pieces() {
+        for piece in read_standard_piece_set().unwrap() {
+            for (index, orientation) in piece.orientations().iter().enumerate() {
+                let mut rotated = index;
+                (0..4).for_each(|_| rotated = piece.orientation(rotated).unwrap().rotated());
+                assert_eq!(rotated, index, "four rotations of {} are not the identity", piece.name());
+
+                let flipped = piece.orientation(orientation.flipped()).unwrap();
+                assert_eq!(flipped.flipped(), index, "flipping {} twice is not the identity", piece.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::game::{Piece, Position};
+
+const MAX_PIECE_DIMENSION: u16 = 5;
+
+pub fn read_standard_piece_set() -> Result<Vec<Piece>, String> {
+    parse_piece_set(std::str::from_utf8(include_bytes!("../res/standard_pieces")).unwrap())
+}
+
+/// Parses a set of pieces separated by blank lines. Each piece starts with a header line holding
+/// its name, followed by the rows of its bounding box. Every piece is validated on its own and
+/// against the pieces before it, errors point to the line in `source` where the problem starts.
+pub fn parse_piece_set(source: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces: Vec<(usize, Piece)> = vec![];
+    let mut first_line = 1;
+    for (id, chunk) in source.split("\n\n").enumerate() {
+        let mut piece = parse_piece(chunk, first_line)?;
+        piece.id = id;
+        if let Some((line, _)) = pieces.iter().find(|(_, other)| other.name == piece.name) {
+            return Err(format!("line {first_line}: piece name '{}' is already used on line {line}", piece.name));
+        }
+        if let Some((line, _)) = pieces.iter().find(|(_, other)| *other == piece) {
+            return Err(format!("line {first_line}: piece is a duplicate of the piece on line {line}"));
+        }
+        pieces.push((first_line, piece));
+        first_line += chunk.lines().count() + 1;
+    }
+    Ok(pieces.into_iter().map(|(_, piece)| piece).collect())
+}
+
+fn parse_piece(string: &str, first_line: usize) -> Result<Piece, String> {
+    let mut lines = string.lines();
+    let name = lines.next().unwrap_or_default().trim();
+    if name.is_empty() || name.contains(['.', 'x']) {
+        return Err(format!("line {first_line}: piece must start with a name, e.g. 'I5'"));
+    }
+    let rows = lines.collect::<Vec<_>>();
+    let first_row = first_line + 1;
+
+    let blocks = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().filter_map(move |(x, c)| match c {
+            'x' => Some(Position { x: x as i32, y: y as i32 }),
+            _ => None
+        }))
+        .collect::<Vec<_>>();
+
+    if blocks.is_empty() {
+        return Err(format!("line {first_line}: piece '{name}' does not contain any blocks"));
+    }
+    if let Some(block) = find_disconnected_block(&blocks) {
+        return Err(format!("line {}: block at column {} is not connected to the rest of the piece", first_row + block.y as usize, block.x + 1));
+    }
+
+    let mut piece = Piece::new(blocks, rows.len() as i32);
+    piece.name = name.to_string();
+    if piece.num_lines() > MAX_PIECE_DIMENSION || piece.num_columns() > MAX_PIECE_DIMENSION {
+        return Err(format!(
+            "line {first_line}: piece spans {}x{} blocks, the maximum is {MAX_PIECE_DIMENSION}x{MAX_PIECE_DIMENSION}",
+            piece.num_columns(),
+            piece.num_lines()
+        ));
+    }
+    Ok(piece)
+}
+
+/// Flood fills the piece starting at its first block and returns a block that could not be
+/// reached through edge-adjacent neighbours, if any.
+fn find_disconnected_block(blocks: &[Position]) -> Option<&Position> {
+    let mut reached = vec![false; blocks.len()];
+    let mut queue = VecDeque::from([0]);
+    reached[0] = true;
+    while let Some(index) = queue.pop_front() {
+        let block = &blocks[index];
+        for (neighbour_index, neighbour) in blocks.iter().enumerate() {
+            if !reached[neighbour_index] && block.x.abs_diff(neighbour.x) + block.y.abs_diff(neighbour.y) == 1 {
+                reached[neighbour_index] = true;
+                queue.push_back(neighbour_index);
+            }
+        }
+    }
+    blocks.iter().zip(reached).find(|(_, reached)| !reached).map(|(block, _)| block)
+}
+
+impl FromStr for Piece {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        parse_piece(string, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_standard_pieces() {
+        let pieces = read_standard_piece_set().unwrap();
+        assert_eq!(pieces.len(), 21);
+        assert_eq!(pieces[15].name(), "W5");
+        assert_eq!(pieces[15].id(), 15);
+    }
+
+    #[test]
+    fn should_reject_empty_piece() {
+        assert_eq!(parse_piece_set("I2\nxx\n..\n\nE\n..\n.."), Err("line 5: piece 'E' does not contain any blocks".to_string()));
+    }
+
+    #[test]
+    fn should_reject_disconnected_piece() {
+        assert_eq!(
+            parse_piece_set("I1\nx\n\nD\nx..\n...\n..x"),
+            Err("line 7: block at column 3 is not connected to the rest of the piece".to_string())
+        );
+    }
+
+    #[test]
+    fn should_reject_duplicate_piece() {
+        assert_eq!(
+            parse_piece_set("I3\n...\nxxx\n...\n\nI3'\n.x.\n.x.\n.x."),
+            Err("line 6: piece is a duplicate of the piece on line 1".to_string())
+        );
+    }
+
+    #[test]
+    fn should_reject_missing_or_duplicate_name() {
+        assert_eq!(parse_piece_set("xx\n.."), Err("line 1: piece must start with a name, e.g. 'I5'".to_string()));
+        assert_eq!(
+            parse_piece_set("I\nx\n\nI\nxx\n.."),
+            Err("line 4: piece name 'I' is already used on line 1".to_string())
+        );
+    }
+
+    #[test]
+    fn should_reject_oversized_piece() {
+        assert_eq!(
+            parse_piece_set("I6\nxxxxxx\n......\n......\n......\n......\n......"),
+            Err("line 1: piece spans 6x1 blocks, the maximum is 5x5".to_string())
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use rand::Rng;
+
+use crate::game::{Game, Piece, Position};
+use crate::game::bitboard::{Bitboard, MAX_BOARD_WIDTH};
+use crate::game::rules::{GameRules, Scoring};
+
+/// How often the generator draws a new set of pieces before it gives up.
+const GENERATOR_ATTEMPTS: usize = 200;
+
+/// A region to tile exactly with a given set of pieces.
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    pub width: u16,
+    pub height: u16,
+    pub pieces: Vec<Piece>,
+}
+
+/// One piece of a solution, in the same terms as `Game::place_piece`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolutionStep {
+    pub piece_index: usize,
+    pub orientation: usize,
+    pub position: Position,
+}
+
+impl Puzzle {
+    /// A single player fills the board with the pieces of the puzzle, the pieces may touch.
+    pub fn rules(&self) -> GameRules {
+        GameRules {
+            num_players: 1,
+            scoring: Scoring::SquaresPlaced,
+            free_placement: true,
+            ..GameRules::with_board_size(self.width, self.height)
+        }
+    }
+}
+
+/// Draws `num_pieces` distinct pieces from `piece_set` until they tile a rectangle, which the
+/// solver has to confirm. Returns `None` if no solvable set was found.
+pub fn generate_puzzle(piece_set: &[Piece], num_pieces: usize, rng: &mut impl Rng) -> Option<Puzzle> {
+    if num_pieces == 0 || num_pieces > piece_set.len() {
+        return None;
+    }
+    (0..GENERATOR_ATTEMPTS).find_map(|_| {
+        let mut candidates = piece_set.to_vec();
+        let pieces = (0..num_pieces)
+            .map(|_| candidates.remove(rng.gen_range(0..candidates.len())))
+            .collect::<Vec<_>>();
+        let area = pieces.iter().map(Piece::size).sum::<usize>();
+        // the most square rectangle with the area of the pieces, at least as wide as it is high
+        let height = (1..=area).rev().find(|height| height * height <= area && area % height == 0)?;
+        let width = area / height;
+        if width > MAX_BOARD_WIDTH as usize {
+            return None;
+        }
+        let mut region = Bitboard::new(height as u16);
+        (0..height).flat_map(|y| (0..width).map(move |x| Position { x: x as i32, y: y as i32 }))
+            .for_each(|position| region.insert(&position));
+        solve(&region, &pieces, true)?;
+        Some(Puzzle { width: width as u16, height: height as u16, pieces })
+    })
+}
+
+/// Finds placements of all `pieces` that cover `region` exactly, if there are any.
+pub fn solve(region: &Bitboard, pieces: &[Piece], allow_flips: bool) -> Option<Vec<SolutionStep>> {
+    if region.count() as usize != pieces.iter().map(Piece::size).sum::<usize>() {
+        return None;
+    }
+    let mut used = vec![false; pieces.len()];
+    let mut steps = vec![];
+    search(region, pieces, allow_flips, &mut used, &mut steps).then_some(steps)
+}
+
+/// The first uncovered cell in reading order has to be covered by the first block in reading
+/// order of some piece, so every orientation only has to be tried at a single position.
+fn search(remaining: &Bitboard, pieces: &[Piece], allow_flips: bool, used: &mut [bool], steps: &mut Vec<SolutionStep>) -> bool {
+    let Some(target) = remaining.positions().next() else {
+        return true;
+    };
+    for (piece_index, piece) in pieces.iter().enumerate() {
+        if used[piece_index] {
+            continue;
+        }
+        for (orientation_index, orientation) in piece.orientations().iter().enumerate() {
+            if orientation.is_mirrored() && !allow_flips {
+                continue;
+            }
+            let first_block = orientation.blocks().min_by_key(|block| (block.y, block.x)).unwrap();
+            let position = Position { x: target.x - first_block.x, y: target.y - first_block.y };
+            let cells = orientation.blocks().map(|block| &block + &position).collect::<Vec<_>>();
+            if !cells.iter().all(|cell| remaining.contains(cell)) {
+                continue;
+            }
+
+            let mut mask = Bitboard::new(remaining.height());
+            cells.iter().for_each(|cell| mask.insert(cell));
+            let mut rest = remaining.clone();
+            rest.subtract(&mask);
+            used[piece_index] = true;
+            steps.push(SolutionStep { piece_index, orientation: orientation_index, position });
+            if search(&rest, pieces, allow_flips, used, steps) {
+                return true;
+            }
+            steps.pop();
+            used[piece_index] = false;
+        }
+    }
+    false
+}
+
+impl Game {
+    /// A way to cover every free cell with the remaining pieces of the active player.
+    pub fn solve_remaining(&self) -> Option<Vec<SolutionStep>> {
+        let mut region = Bitboard::new(self.board.height);
+        (0..self.board.height as i32)
+            .flat_map(|y| (0..self.board.width as i32).map(move |x| Position { x, y }))
+            .for_each(|position| region.insert(&position));
+        region.subtract(&self.board.occupancy);
+        solve(&region, self.active_player_pieces(), self.rules.allow_flips)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+
+    use super::*;
+
+    #[test]
+    fn should_solve_generated_puzzles_by_playing_the_solution() {
+        let piece_set = read_standard_piece_set().unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        for num_pieces in 2..=4 {
+            let puzzle = generate_puzzle(&piece_set, num_pieces, &mut rng).unwrap();
+            let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, puzzle.pieces.clone())]);
+            let mut game = Game::new(puzzle.rules(), players);
+
+            // placing a piece removes it from the rack, which shifts the indices of later pieces
+            let mut solution = game.solve_remaining().unwrap();
+            while let Some(step) = solution.first().cloned() {
+                assert!(game.place_piece(step.piece_index, step.orientation, step.position).unwrap());
+                solution.remove(0);
+                solution.iter_mut().filter(|later| later.piece_index > step.piece_index).for_each(|later| later.piece_index -= 1);
+            }
+            assert!(game.is_over());
+            assert_eq!(game.score(0), (puzzle.width * puzzle.height) as i32);
+        }
+    }
+
+    #[test]
+    fn should_not_solve_regions_of_the_wrong_shape() {
+        let domino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2);
+        let mut region = Bitboard::new(2);
+        [Position { x: 0, y: 0 }, Position { x: 1, y: 1 }].iter().for_each(|position| region.insert(position));
+        assert_eq!(solve(&region, std::slice::from_ref(&domino), true), None);
+
+        region.insert(&Position { x: 1, y: 0 });
+        region.insert(&Position { x: 0, y: 1 });
+        assert_eq!(solve(&region, &[domino.clone(), domino], true).map(|steps| steps.len()), Some(2));
+    }
+}
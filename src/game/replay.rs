@@ -0,0 +1,205 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::game::{Game, Position};
+
+/// A single successful placement: which player put which piece where, and in which of the
+/// piece's orientations.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub player: usize,
+    pub piece_id: usize,
+    pub orientation: usize,
+    pub position: Position,
+}
+
+/// A value drawn from the game's random source, `move_number` being the number of moves played
+/// before the draw.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomDecision {
+    pub purpose: String,
+    pub bound: u64,
+    pub value: u64,
+    pub move_number: usize,
+}
+
+/// Everything needed to reproduce the random decisions of a game.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayMetadata {
+    pub seed: u64,
+    pub random_decisions: Vec<RandomDecision>,
+}
+
+/// The list of moves played in a game, in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    metadata: ReplayMetadata,
+    moves: Vec<Move>,
+}
+
+/// Walks through a replay one move at a time, starting from a copy of the initial game.
+pub struct ReplayStepper<'a> {
+    replay: &'a Replay,
+    initial: Game,
+    game: Game,
+    position: usize,
+}
+
+impl Replay {
+    pub fn new(metadata: ReplayMetadata, moves: Vec<Move>) -> Self {
+        Replay { metadata, moves }
+    }
+
+    pub fn metadata(&self) -> &ReplayMetadata {
+        &self.metadata
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Plays all recorded moves on `game`, which is expected to be in the state the recorded
+    /// game started from.
+    pub fn apply_to(&self, game: &mut Game) -> Result<(), String> {
+        self.moves.iter().try_for_each(|played| Self::apply_move(played, game))
+    }
+
+    /// Redraws every recorded random decision from the seed and checks that it matches the
+    /// recorded value.
+    pub fn verify_random_decisions(&self) -> Result<(), String> {
+        let mut rng = StdRng::seed_from_u64(self.metadata.seed);
+        for decision in &self.metadata.random_decisions {
+            let value = rng.gen_range(0..decision.bound);
+            if value != decision.value {
+                return Err(format!("Recorded {} {} does not match {value} drawn from seed {}", decision.purpose, decision.value, self.metadata.seed));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stepper(&self, initial: Game) -> ReplayStepper<'_> {
+        ReplayStepper { replay: self, game: initial.clone(), initial, position: 0 }
+    }
+
+    fn apply_move(played: &Move, game: &mut Game) -> Result<(), String> {
+        if played.player != game.active_player_index() {
+            return Err(format!("Move of player {} played out of turn", played.player));
+        }
+        let piece_index = game.active_player_pieces()
+            .iter()
+            .position(|piece| piece.id() == played.piece_id)
+            .ok_or_else(|| format!("Player {} does not have piece {}", played.player, played.piece_id))?;
+        match game.place_piece(piece_index, played.orientation, played.position.clone())? {
+            true => Ok(()),
+            false => Err(format!("Piece {} cannot be placed at ({}, {})", played.piece_id, played.position.x, played.position.y))
+        }
+    }
+}
+
+impl<'a> ReplayStepper<'a> {
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Number of moves applied to the current game.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn step_forward(&mut self) -> Result<Option<&'a Move>, String> {
+        let Some(played) = self.replay.moves.get(self.position) else {
+            return Ok(None);
+        };
+        Replay::apply_move(played, &mut self.game)?;
+        self.position += 1;
+        Ok(Some(played))
+    }
+
+    /// Moves one step back by replaying all but the last applied move on the initial game.
+    pub fn step_back(&mut self) -> Result<Option<&'a Move>, String> {
+        if self.position == 0 {
+            return Ok(None);
+        }
+        self.position -= 1;
+        self.game = self.initial.clone();
+        self.replay.moves[..self.position].iter().try_for_each(|played| Replay::apply_move(played, &mut self.game))?;
+        Ok(Some(&self.replay.moves[self.position]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players, State};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    fn two_player_game() -> Game {
+        let piece = crate::game::Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece.clone()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![piece]),
+        ]);
+        Game::with_seed(GameRules::with_board_size(3, 3), players, 42)
+    }
+
+    #[test]
+    fn should_record_and_apply_replay() {
+        let initial = two_player_game();
+        let mut game = initial.clone();
+        game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap();
+        game.place_piece(0, 0, Position { x: 2, y: 2 }).unwrap();
+
+        let replay = game.replay();
+        let mut replayed = initial.clone();
+        replay.apply_to(&mut replayed).unwrap();
+
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replayed.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(), game.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap());
+    }
+
+    #[test]
+    fn should_verify_recorded_random_decisions() {
+        let mut game = two_player_game();
+        game.random_below("test", 1000);
+        let mut replay = game.replay();
+        assert_eq!(replay.metadata().random_decisions.len(), 2);
+        assert!(replay.verify_random_decisions().is_ok());
+
+        replay.metadata.random_decisions[1].value += 1;
+        assert!(replay.verify_random_decisions().is_err());
+    }
+
+    #[test]
+    fn should_step_forward_and_back() {
+        let initial = two_player_game();
+        let mut game = initial.clone();
+        game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap();
+        game.place_piece(0, 0, Position { x: 2, y: 2 }).unwrap();
+        let replay = game.replay();
+        let mut stepper = replay.stepper(initial);
+
+        stepper.step_forward().unwrap();
+        stepper.step_forward().unwrap();
+        assert!(stepper.step_forward().unwrap().is_none());
+        assert_eq!(stepper.position(), 2);
+
+        let undone = stepper.step_back().unwrap().unwrap();
+        assert_eq!(undone.position, Position { x: 2, y: 2 });
+        assert_eq!(stepper.game().board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(), State::Free);
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+
+use crate::game::Position;
+use crate::game::bitboard::MAX_BOARD_WIDTH;
+use crate::game::clock::TimeControl;
+
+/// Every corner of the board is the start of a player.
+pub const MAX_PLAYERS: usize = 4;
+
+/// The rules a game is played with. Variants like Blokus Duo and house rules are expressed as a
+/// different configuration of the same game.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct GameRules {
+    pub num_players: usize,
+    pub width: u16,
+    pub height: u16,
+    /// Cells nobody may occupy, e.g. from a board mask.
+    pub blocked_cells: Vec<Position>,
+    pub start: Start,
+    /// Whether pieces may be mirrored, otherwise they can only be rotated.
+    pub allow_flips: bool,
+    /// Points for placing every piece.
+    pub all_pieces_bonus: i32,
+    /// Additional points if the last piece placed was the single square.
+    pub single_square_last_bonus: i32,
+    /// Whether players without a legal move are skipped automatically. Otherwise they keep
+    /// their turn until they pass.
+    pub skip_blocked_players: bool,
+    /// Players whose scores are added up. Empty if everybody plays on their own.
+    pub teams: Vec<Vec<usize>>,
+    pub scoring: Scoring,
+    /// Pieces may cover any free cells and touch each other along their edges, as in puzzles.
+    pub free_placement: bool,
+    /// Players pick their pieces one at a time from a shared pool before the game starts.
+    pub draft: bool,
+    /// Every player secretly commits a move each round, then all moves are revealed at once.
+    /// Overlapping pieces are resolved by size: the larger piece is placed, equal sizes cancel.
+    pub simultaneous: bool,
+}
+
+/// How the score of a player is counted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Scoring {
+    /// Minus the squares left in the rack, or the bonuses once every piece is placed.
+    RemainingSquares,
+    /// The number of squares placed, for the solo challenge.
+    SquaresPlaced,
+}
+
+/// Everything the players of a game have to agree on before it starts. Settings can be exported
+/// to a file and imported on another machine so that both sides play with the same configuration.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSettings {
+    pub rules: GameRules,
+    pub time_control: Option<TimeControl>,
+}
+
+/// Where the first piece of every player has to be placed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Start {
+    /// The first piece covers a corner of the board.
+    Corners,
+    /// The first piece covers one of these squares.
+    Squares(Vec<Position>),
+}
+
+impl GameRules {
+    /// The standard rules on a board of the given size.
+    pub fn with_board_size(width: u16, height: u16) -> Self {
+        GameRules {
+            num_players: 4,
+            width,
+            height,
+            blocked_cells: vec![],
+            start: Start::Corners,
+            allow_flips: true,
+            all_pieces_bonus: 15,
+            single_square_last_bonus: 5,
+            skip_blocked_players: true,
+            teams: vec![],
+            scoring: Scoring::RemainingSquares,
+            free_placement: false,
+            draft: false,
+            simultaneous: false,
+        }
+    }
+
+    /// Blokus Duo: two players on a 14x14 board, starting from two squares near the center.
+    pub fn duo() -> Self {
+        GameRules {
+            num_players: 2,
+            start: Start::Squares(vec![Position { x: 4, y: 4 }, Position { x: 9, y: 9 }]),
+            ..GameRules::with_board_size(14, 14)
+        }
+    }
+
+    /// The solo challenge: a single player places as much of their rack as possible on a small
+    /// board. The game ends as soon as no piece fits anymore.
+    pub fn solo() -> Self {
+        GameRules {
+            num_players: 1,
+            scoring: Scoring::SquaresPlaced,
+            ..GameRules::with_board_size(10, 10)
+        }
+    }
+
+    /// Checks that a game can be set up with the rules, e.g. with rules from a file that somebody
+    /// else wrote.
+    pub fn validate(&self) -> Result<(), String> {
+        let sizes = 1..=MAX_BOARD_WIDTH;
+        if !sizes.contains(&self.width) || !sizes.contains(&self.height) {
+            return Err(format!("the board is {}x{}, it has to be between 1x1 and {MAX_BOARD_WIDTH}x{MAX_BOARD_WIDTH}", self.width, self.height));
+        }
+        if !(1..=MAX_PLAYERS).contains(&self.num_players) {
+            return Err(format!("there are {} players, there have to be between 1 and {MAX_PLAYERS}", self.num_players));
+        }
+        let on_board = |cell: &&Position| (0..self.width as i32).contains(&cell.x) && (0..self.height as i32).contains(&cell.y);
+        if let Some(cell) = self.blocked_cells.iter().find(|cell| !on_board(cell)) {
+            return Err(format!("the blocked cell ({}, {}) is not on the board", cell.x, cell.y));
+        }
+        if let Start::Squares(squares) = &self.start {
+            if let Some(square) = squares.iter().find(|square| !on_board(square)) {
+                return Err(format!("the start square ({}, {}) is not on the board", square.x, square.y));
+            }
+        }
+        let mut in_team = HashSet::new();
+        for player_index in self.teams.iter().flatten() {
+            if *player_index >= self.num_players {
+                return Err(format!("player {} of a team does not take part", player_index + 1));
+            }
+            if !in_team.insert(player_index) {
+                return Err(format!("player {} is in more than one team", player_index + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// The rules of a preset by name, see `PRESETS`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(GameRules::default()),
+            "duo" => Some(GameRules::duo()),
+            "solo" => Some(GameRules::solo()),
+            _ => None
+        }
+    }
+}
+
+pub const PRESETS: [&str; 3] = ["standard", "duo", "solo"];
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::with_board_size(20, 20)
+    }
+}
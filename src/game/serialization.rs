@@ -0,0 +1,353 @@
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+
+use crate::game::{Board, Game, Players, TakenPiece};
+use crate::game::clock::{Clock, TimeControl};
+use crate::game::draft::Draft;
+use crate::game::replay::{Move, RandomDecision, Replay};
+use crate::game::rules::{GameRules, GameSettings};
+use crate::game::simultaneous::Commitment;
+use crate::persistence;
+
+/// Version of the serialized game representation. Bump it whenever the layout of any serialized
+/// engine type changes, so that older snapshots are rejected instead of silently misread.
+pub const FORMAT_VERSION: u32 = 17;
+
+impl Game {
+    /// Writes a snapshot of the whole game to `path` as JSON: the board, the racks, the turn order,
+    /// the history, the random decisions, the clock, the draft and the simultaneous commitments.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Game> {
+        read_json(path)
+    }
+}
+
+impl Replay {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Replay> {
+        read_json(path)
+    }
+}
+
+impl GameSettings {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_json(path, &SettingsRepr { version: FORMAT_VERSION, settings: self.clone() })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<GameSettings> {
+        let repr: SettingsRepr = read_json(path)?;
+        if repr.version != FORMAT_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                "unsupported settings format version {}, expected {FORMAT_VERSION}",
+                repr.version
+            )));
+        }
+        // the file may come from another player
+        repr.settings.rules.validate().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(repr.settings)
+    }
+}
+
+fn write_json(path: impl AsRef<Path>, value: &impl Serialize) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+    persistence::write_atomic(path, &json)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: impl AsRef<Path>) -> io::Result<T> {
+    let json = persistence::read_atomic(path)?;
+    serde_json::from_slice(&json).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[derive(Deserialize, Serialize)]
+struct SettingsRepr {
+    version: u32,
+    #[serde(flatten)]
+    settings: GameSettings,
+}
+
+#[derive(Deserialize)]
+struct GameRepr {
+    version: u32,
+    rules: GameRules,
+    board: Board,
+    players: Players,
+    history: Vec<Move>,
+    seed: u64,
+    random_decisions: Vec<RandomDecision>,
+    starting_player: usize,
+    clock: Option<Clock>,
+    taken_pieces: Vec<TakenPiece>,
+    draft: Option<Draft>,
+    commitments: Vec<Commitment>,
+    revealed: Vec<Commitment>,
+}
+
+/// Instants cannot be stored, so a clock is saved with the remaining time of every player at the
+/// moment of saving. The running clock continues when the game is loaded, the time in between is
+/// not charged to anybody.
+#[derive(Deserialize, Serialize)]
+struct ClockRepr {
+    time_control: TimeControl,
+    remaining: Vec<Duration>,
+    running: Option<usize>,
+}
+
+impl Serialize for Clock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (remaining, running) = self.snapshot(Instant::now());
+        ClockRepr { time_control: self.time_control().clone(), remaining, running }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Clock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ClockRepr { time_control, remaining, running } = ClockRepr::deserialize(deserializer)?;
+        Ok(Clock::resume(time_control, remaining, running, Instant::now()))
+    }
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Game", 13)?;
+        state.serialize_field("version", &FORMAT_VERSION)?;
+        state.serialize_field("rules", &self.rules)?;
+        state.serialize_field("board", &self.board)?;
+        state.serialize_field("players", &self.players)?;
+        state.serialize_field("history", &self.history)?;
+        state.serialize_field("seed", &self.seed)?;
+        state.serialize_field("random_decisions", &self.random_decisions)?;
+        state.serialize_field("starting_player", &self.starting_player)?;
+        state.serialize_field("clock", &self.clock)?;
+        state.serialize_field("taken_pieces", &self.taken_pieces)?;
+        state.serialize_field("draft", &self.draft)?;
+        state.serialize_field("commitments", &self.commitments)?;
+        state.serialize_field("revealed", &self.revealed)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GameRepr::deserialize(deserializer)?;
+        if repr.version != FORMAT_VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported game format version {}, expected {FORMAT_VERSION}",
+                repr.version
+            )));
+        }
+        // saves are read from disk and games from the host, neither may crash the game later
+        validate(&repr).map_err(|error| D::Error::custom(format!("invalid game: {error}")))?;
+        // the generator state itself is not stored, it is restored by redrawing the recorded
+        // decisions from the original seed
+        let mut rng = StdRng::seed_from_u64(repr.seed);
+        for decision in &repr.random_decisions {
+            rng.gen_range(0..decision.bound);
+        }
+        Ok(Game {
+            rules: repr.rules,
+            board: repr.board,
+            players: repr.players,
+            history: repr.history,
+            seed: repr.seed,
+            rng,
+            random_decisions: repr.random_decisions,
+            starting_player: repr.starting_player,
+            clock: repr.clock,
+            observers: vec![],
+            taken_pieces: repr.taken_pieces,
+            draft: repr.draft,
+            commitments: repr.commitments,
+            revealed: repr.revealed,
+        })
+    }
+}
+
+/// Checks what the engine takes for granted once a game is set up.
+fn validate(repr: &GameRepr) -> Result<(), String> {
+    repr.rules.validate()?;
+    let num_players = repr.players.players.len();
+    if num_players == 0 || num_players > repr.rules.num_players {
+        return Err(format!("there are {num_players} players, the rules are for {}", repr.rules.num_players));
+    }
+    if let Some(player_index) = [repr.players.active_player_index, repr.starting_player].into_iter().find(|player_index| *player_index >= num_players) {
+        return Err(format!("player {} does not take part", player_index + 1));
+    }
+    let board = &repr.board;
+    let mut bitboards = [&board.occupancy, &board.blocked].into_iter()
+        .chain(board.player_boards.iter().flat_map(|player_board| [&player_board.blocks, &player_board.forbidden, &player_board.anchors]));
+    if (board.width, board.height) != (repr.rules.width, repr.rules.height)
+        || board.player_boards.len() != num_players
+        || bitboards.any(|bitboard| bitboard.height() != board.height) {
+        return Err("the board does not match the rules".to_string());
+    }
+    if repr.history.iter().map(|played| played.player).chain(board.placements.iter().map(|placement| placement.player)).any(|player| player >= num_players)
+        || repr.taken_pieces.len() != repr.history.len() {
+        return Err("the moves do not match the players".to_string());
+    }
+    if repr.random_decisions.iter().any(|decision| decision.bound == 0) {
+        return Err("a random decision has no possible outcome".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Piece, Player, Position};
+    use crate::game::clock::{TimeControl, TimeoutAction};
+    use crate::game::rules::Start;
+
+    use super::*;
+
+    #[test]
+    fn should_round_trip_game() {
+        let piece = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece.clone()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![piece]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(2, 2), players);
+        let active_player = game.active_player_index();
+        game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let restored: Game = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.active_player_index(), (active_player + 1) % 2);
+        assert!(restored.players()[active_player].available_pieces.is_empty());
+        assert_eq!(restored.board.get_state_on_position(&Position { x: 0, y: 0 }).unwrap(), game.board.get_state_on_position(&Position { x: 0, y: 0 }).unwrap());
+    }
+
+    #[test]
+    fn should_save_and_load_game() {
+        let piece = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![piece.clone(), piece])]);
+        let mut game = Game::new(GameRules::with_board_size(3, 3), players);
+        game.place_piece(0, 0, Position { x: 2, y: 2 }).unwrap();
+        let path = std::env::temp_dir().join(format!("blokus-save-test-{}.json", std::process::id()));
+
+        game.save(&path).unwrap();
+        let restored = Game::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.players()[0].available_pieces.len(), 1);
+        assert!(!restored.players()[0].first_move);
+        assert_eq!(restored.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap(), game.board.get_state_on_position(&Position { x: 2, y: 2 }).unwrap());
+    }
+
+    #[test]
+    fn should_keep_clock_when_saving() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![]),
+        ]);
+        let mut game = Game::new(GameRules::with_board_size(2, 2), players);
+        let time_control = TimeControl {
+            budget: Duration::from_secs(60),
+            seat_budgets: vec![],
+            increment: Duration::ZERO,
+            timeout_action: TimeoutAction::Pass,
+        };
+        // the clock has been running for ten seconds already
+        game.start_clock(time_control, Instant::now() - Duration::from_secs(10));
+        let active_player = game.active_player_index();
+
+        let restored: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        let now = Instant::now();
+        let clock = restored.clock().unwrap();
+        assert_eq!(clock.remaining(1 - active_player, now), Duration::from_secs(60));
+        assert!(clock.remaining(active_player, now) <= Duration::from_secs(50));
+        assert!(clock.remaining(active_player, now) > Duration::from_secs(49));
+    }
+
+    #[test]
+    fn should_export_and_import_settings() {
+        let settings = GameSettings {
+            rules: GameRules { blocked_cells: vec![Position { x: 6, y: 7 }], ..GameRules::duo() },
+            time_control: Some(TimeControl {
+                budget: Duration::from_secs(300),
+                seat_budgets: vec![Duration::from_secs(600)],
+                increment: Duration::from_secs(2),
+                timeout_action: TimeoutAction::Forfeit,
+            }),
+        };
+        let path = std::env::temp_dir().join(format!("blokus-settings-test-{}.json", std::process::id()));
+
+        settings.save(&path).unwrap();
+        let imported = GameSettings::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(imported, settings);
+    }
+
+    #[test]
+    fn should_refuse_to_import_rules_that_no_game_can_be_set_up_with() {
+        let invalid_rules = [
+            GameRules::with_board_size(100, 20),
+            GameRules::with_board_size(20, 0),
+            GameRules { num_players: 0, ..GameRules::default() },
+            GameRules { blocked_cells: vec![Position { x: 3, y: 500 }], ..GameRules::default() },
+            GameRules { start: Start::Squares(vec![Position { x: -1, y: 4 }]), ..GameRules::duo() },
+            GameRules { teams: vec![vec![0, 4]], ..GameRules::default() },
+            GameRules { teams: vec![vec![0, 1], vec![1, 2]], ..GameRules::default() },
+        ];
+        let path = std::env::temp_dir().join(format!("blokus-invalid-settings-test-{}.json", std::process::id()));
+
+        for rules in invalid_rules {
+            assert!(rules.validate().is_err(), "{rules:?}");
+            GameSettings { rules, time_control: None }.save(&path).unwrap();
+            assert_eq!(GameSettings::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(GameRules::duo().validate(), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_games_that_cannot_be_played() {
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![]),
+        ]);
+        let game = serde_json::to_value(Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(5, 5) }, players, 1)).unwrap();
+        let broken: [fn(&mut serde_json::Value); 6] = [
+            |game| game["random_decisions"][0]["bound"] = 0.into(),
+            |game| game["rules"]["width"] = 100.into(),
+            |game| game["rules"]["num_players"] = 1.into(),
+            |game| game["players"]["active_player_index"] = 2.into(),
+            |game| game["starting_player"] = 5.into(),
+            |game| game["board"]["player_boards"].as_array_mut().unwrap().truncate(1),
+        ];
+
+        assert!(serde_json::from_value::<Game>(game.clone()).is_ok());
+        for breaking in broken {
+            let mut game = game.clone();
+            breaking(&mut game);
+            let error = serde_json::from_value::<Game>(game).err().unwrap();
+            assert!(error.to_string().starts_with("invalid game"), "{error}");
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_version() {
+        let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![])]);
+        let json = serde_json::to_string(&Game::new(GameRules::with_board_size(1, 1), players)).unwrap()
+            .replace(&format!("\"version\":{FORMAT_VERSION}"), "\"version\":0");
+
+        let error = serde_json::from_str::<Game>(&json).err().unwrap();
+        assert!(error.to_string().starts_with("unsupported game format version 0"));
+    }
+}
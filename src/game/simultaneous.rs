@@ -0,0 +1,170 @@
+use std::time::Instant;
+
+use crate::game::{Game, PlayerStatus, Position, TakenPiece};
+use crate::game::bitboard::Bitboard;
+use crate::game::replay::Move;
+
+/// The move a player settled on in a round of the simultaneous variant. It stays hidden until
+/// every player has committed, then all moves of the round are revealed at once.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Commitment {
+    pub player: usize,
+    /// `None` if the player passed or ran out of time.
+    pub played: Option<Move>,
+    pub piece_name: String,
+    /// Larger pieces win conflicts, pieces of the same size cancel each other.
+    pub size: usize,
+    /// Whether the piece made it onto the board, only meaningful once the round is revealed.
+    pub placed: bool,
+}
+
+impl Game {
+    /// The moves of the last revealed round of the simultaneous variant, in the order they were
+    /// committed.
+    pub fn revealed(&self) -> &[Commitment] {
+        &self.revealed
+    }
+
+    /// A copy of the game without the moves that the other players committed to in the current
+    /// round, as the player may know it.
+    pub fn as_seen_by(&self, player_index: usize) -> Game {
+        let mut game = self.clone();
+        game.commitments.retain(|commitment| commitment.player == player_index);
+        game
+    }
+
+    /// Whether the player has already committed a move in the current round.
+    pub fn has_committed(&self, player_index: usize) -> bool {
+        self.commitments.iter().any(|commitment| commitment.player == player_index)
+    }
+
+    /// Records the move of the active player for the current round instead of placing it. The
+    /// piece stays in the rack until the round is resolved. Returns the commitment.
+    pub(super) fn commit(&mut self, piece_index: usize, orientation: usize, position: Position) -> Commitment {
+        let player = self.players.active_player_index;
+        let piece = &self.active_player_pieces()[piece_index];
+        let commitment = Commitment {
+            player,
+            played: Some(Move { player, piece_id: piece.id(), orientation, position }),
+            piece_name: piece.name().to_string(),
+            size: piece.size(),
+            placed: false,
+        };
+        self.commitments.push(commitment.clone());
+        commitment
+    }
+
+    /// Hands the turn to the next player who has not committed in this round yet. Returns
+    /// `false` once everybody has committed, after the round has been resolved.
+    pub(super) fn pass_to_next_committer(&mut self, now: Instant) -> bool {
+        let player_index = self.players.active_player_index;
+        if !self.has_committed(player_index) {
+            self.commitments.push(Commitment { player: player_index, played: None, piece_name: String::new(), size: 0, placed: false });
+        }
+        let num_players = self.players.players.len();
+        let next_player = (1..num_players)
+            .map(|offset| (player_index + offset) % num_players)
+            .find(|next_player| self.players.players[*next_player].status == PlayerStatus::Active && !self.has_committed(*next_player));
+        let Some(next_player) = next_player else {
+            self.resolve_round();
+            return false;
+        };
+        self.players.active_player_index = next_player;
+        if let Some(clock) = &mut self.clock {
+            clock.start(next_player, now);
+        }
+        self.notify(|observer, game| observer.on_turn_changed(game, next_player));
+        true
+    }
+
+    /// Places every committed piece that does not overlap a piece of at least its size. Every
+    /// move was legal on the board the round started from, and a player places at most one
+    /// piece per round, so the pieces that remain cannot make each other illegal.
+    fn resolve_round(&mut self) {
+        let mut commitments = std::mem::take(&mut self.commitments);
+        let masks = commitments.iter()
+            .map(|commitment| commitment.played.as_ref().map(|played| self.committed_mask(played)))
+            .collect::<Vec<_>>();
+        let sizes = commitments.iter().map(|commitment| commitment.size).collect::<Vec<_>>();
+        for (index, commitment) in commitments.iter_mut().enumerate() {
+            let Some(mask) = &masks[index] else {
+                continue;
+            };
+            commitment.placed = !masks.iter().enumerate().any(|(other, other_mask)| {
+                other != index && sizes[other] >= commitment.size && other_mask.as_ref().is_some_and(|other_mask| other_mask.intersects(mask))
+            });
+        }
+
+        for (commitment, mask) in commitments.iter().zip(masks) {
+            let (Some(played), Some(mask), true) = (&commitment.played, mask, commitment.placed) else {
+                continue;
+            };
+            let player = &mut self.players.players[played.player];
+            let rack_index = player.available_pieces.iter()
+                .position(|piece| piece.id() == played.piece_id)
+                .expect("Committed pieces stay in the rack until the round is resolved");
+            let piece = player.take_piece(rack_index);
+            player.first_move = false;
+            self.board.occupy(mask, played.piece_id, played.player);
+            self.taken_pieces.push(TakenPiece { rack_index, piece });
+            self.history.push(played.clone());
+            self.notify(|observer, game| observer.on_piece_placed(game, played));
+        }
+        self.revealed = commitments;
+    }
+
+    fn committed_mask(&self, played: &Move) -> Bitboard {
+        let piece = self.players.players[played.player].available_pieces.iter()
+            .find(|piece| piece.id() == played.piece_id)
+            .expect("Committed pieces stay in the rack until the round is resolved");
+        let orientation = piece.orientation(played.orientation).expect("Commitments are checked");
+        self.board.piece_mask(orientation, &played.position).expect("Commitments are checked")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Piece, Player, Players, State};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    #[test]
+    fn should_resolve_conflicting_commitments_by_piece_size() {
+        let domino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2);
+        let mut tromino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }, Position { x: 2, y: 0 }], 3);
+        tromino.id = 1;
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![domino.clone(), tromino.clone()]),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, vec![domino, tromino]),
+        ]);
+        let rules = GameRules { num_players: 2, simultaneous: true, ..GameRules::with_board_size(3, 1) };
+        let mut game = Game::new(rules, players);
+        let first_player = game.active_player_index();
+
+        // the commitment stays hidden until the other player has committed as well
+        assert!(game.place_piece(0, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(game.has_committed(first_player));
+        assert!(!game.as_seen_by(1 - first_player).has_committed(first_player));
+        assert_eq!(game.active_player_index(), 1 - first_player);
+        assert_eq!(game.board.get_state_on_position(&Position { x: 0, y: 0 }), Ok(State::Free));
+
+        // two dominoes overlapping in the middle cancel each other
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 0 }).unwrap());
+        assert_eq!(game.revealed().len(), 2);
+        assert!(game.revealed().iter().all(|commitment| !commitment.placed));
+        assert!(game.players().iter().all(|player| player.available_pieces.len() == 2));
+        assert_eq!(game.active_player_index(), first_player);
+
+        // the tromino is larger than the domino it overlaps
+        assert!(game.place_piece(1, 0, Position { x: 0, y: 0 }).unwrap());
+        assert!(game.place_piece(0, 0, Position { x: 1, y: 0 }).unwrap());
+        assert_eq!(game.revealed().iter().map(|commitment| commitment.placed).collect::<Vec<_>>(), vec![true, false]);
+        assert_eq!(game.replay().len(), 1);
+        assert!(matches!(game.board.get_state_on_position(&Position { x: 2, y: 0 }), Ok(State::Occupied { player, .. }) if player == first_player));
+        assert!(game.is_over());
+    }
+}
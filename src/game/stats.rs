@@ -0,0 +1,187 @@
+use crate::game::{Board, Game, LegalMove};
+use crate::game::bitboard::Bitboard;
+
+/// A summary of how the board is divided between the players.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OccupancyStats {
+    pub players: Vec<PlayerOccupancy>,
+    /// Free cells that more than one player can still reach.
+    pub contested: Bitboard,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerOccupancy {
+    pub placed_squares: u32,
+    /// Free cells connected to the player's anchors without crossing occupied cells or cells
+    /// next to the player's own pieces. This is an upper bound of the area the player can still
+    /// cover.
+    pub reachable: Bitboard,
+}
+
+/// How a placement would change the anchors on the board, see `Game::anchor_change`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AnchorChange {
+    /// Anchors the placing player would gain.
+    pub gained: u32,
+    /// Anchors of the other players the piece would cover, summed over the players.
+    pub blocked: u32,
+}
+
+/// Why a placement is likely a mistake, see `Game::blunder`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Blunder {
+    /// None of the pieces left in the rack could be placed afterwards.
+    NoMovesLeft,
+    /// Less than half of the player's anchors would be left.
+    AnchorsLost { before: u32, after: u32 },
+}
+
+impl Game {
+    /// Checks a legal move of the active player for obvious mistakes on a copy of the board. The
+    /// moves of the other players until the next turn are not taken into account, and the first
+    /// piece is never a blunder by its anchors, since it is compared with the corners.
+    pub fn blunder(&self, candidate: &LegalMove) -> Option<Blunder> {
+        let player_index = self.players.active_player_index;
+        let mut board = self.board.clone();
+        board.occupy_blocks(&candidate.blocks, player_index);
+        let mut rack = self.active_player_pieces().to_vec();
+        rack.remove(candidate.piece_index);
+        if !rack.is_empty() && !board.has_legal_move(&rack, player_index, self.rules.allow_flips) {
+            return Some(Blunder::NoMovesLeft);
+        }
+        if self.board.player_boards[player_index].blocks.is_empty() {
+            return None;
+        }
+        let before = self.board.anchors(player_index).count();
+        let after = board.anchors(player_index).count();
+        (after * 2 < before).then_some(Blunder::AnchorsLost { before, after })
+    }
+}
+
+impl Board {
+    /// Computes the anchors the same way `occupy_blocks` updates them, without placing anything.
+    pub fn anchor_change(&self, blocks: &Bitboard, player_index: usize) -> AnchorChange {
+        let player_board = &self.player_boards[player_index];
+        let mut anchors = blocks.diagonal_neighbours(self.width);
+        anchors.subtract(&player_board.forbidden);
+        anchors.subtract(&blocks.edge_neighbours(self.width));
+        anchors.subtract(&self.occupancy);
+        anchors.subtract(blocks);
+        // the corners only serve as anchors for the first piece, so they are never kept
+        if !player_board.blocks.is_empty() {
+            anchors.subtract(&player_board.anchors);
+        }
+        let blocked = self.player_boards.iter()
+            .enumerate()
+            .filter(|(other_index, _)| *other_index != player_index)
+            .map(|(_, other)| {
+                let mut covered = other.anchors.clone();
+                covered.intersect_with(blocks);
+                covered.count()
+            })
+            .sum();
+        AnchorChange { gained: anchors.count(), blocked }
+    }
+
+    pub fn occupancy_stats(&self) -> OccupancyStats {
+        let players = (0..self.player_boards.len())
+            .map(|player_index| PlayerOccupancy {
+                placed_squares: self.player_boards[player_index].blocks.count(),
+                reachable: self.reachable_area(player_index),
+            })
+            .collect::<Vec<_>>();
+
+        let mut contested = Bitboard::new(self.height);
+        for (index, player) in players.iter().enumerate() {
+            for other in &players[index + 1..] {
+                let mut shared = player.reachable.clone();
+                shared.intersect_with(&other.reachable);
+                contested.union_with(&shared);
+            }
+        }
+        OccupancyStats { players, contested }
+    }
+
+    fn reachable_area(&self, player_index: usize) -> Bitboard {
+        let player_board = &self.player_boards[player_index];
+        let mut reachable = player_board.anchors.clone();
+        loop {
+            let mut grown = reachable.edge_neighbours(self.width);
+            grown.union_with(&reachable);
+            grown.subtract(&self.occupancy);
+            grown.subtract(&player_board.forbidden);
+            if grown == reachable {
+                return reachable;
+            }
+            reachable = grown;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::game::{Piece, Player, Players, Position};
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    fn place(board: &mut Board, x: i32, y: i32, player_index: usize) {
+        let piece = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        assert!(board.place_piece(piece.orientation(0).unwrap(), 0, Position { x, y }, player_index).unwrap());
+    }
+
+    #[test]
+    fn should_compute_reachable_and_contested_area() {
+        let mut board = Board::new(&GameRules::with_board_size(3, 3), 2);
+        place(&mut board, 0, 0, 0);
+        place(&mut board, 2, 2, 1);
+
+        let stats = board.occupancy_stats();
+        assert_eq!(stats.players[0].placed_squares, 1);
+        // cells next to the own piece are never reachable, all other free cells are
+        assert_eq!(stats.players[0].reachable.count(), 5);
+        assert!(!stats.players[0].reachable.contains(&Position { x: 1, y: 0 }));
+        assert_eq!(stats.players[1].reachable.count(), 5);
+        assert_eq!(stats.contested.positions().collect::<Vec<_>>(), vec![
+            Position { x: 2, y: 0 }, Position { x: 1, y: 1 }, Position { x: 0, y: 2 }
+        ]);
+    }
+
+    #[test]
+    fn should_count_gained_and_blocked_anchors() {
+        let mut board = Board::new(&GameRules::with_board_size(5, 5), 2);
+        place(&mut board, 0, 0, 0);
+        place(&mut board, 4, 4, 1);
+
+        let mut blocks = Bitboard::new(5);
+        [Position { x: 1, y: 1 }, Position { x: 2, y: 1 }].iter().for_each(|position| blocks.insert(position));
+        // (2, 0) and (2, 2) share an edge with the new piece, which leaves (0, 2), (3, 0) and (3, 2)
+        assert_eq!(board.anchor_change(&blocks, 0), AnchorChange { gained: 3, blocked: 0 });
+
+        let mut blocks = Bitboard::new(5);
+        blocks.insert(&Position { x: 3, y: 3 });
+        assert_eq!(board.anchor_change(&blocks, 0).blocked, 1);
+    }
+
+    #[test]
+    fn should_warn_about_moves_that_leave_no_moves() {
+        let monomino = Piece::new(vec![Position { x: 0, y: 0 }], 1);
+        let mut domino = Piece::new(vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }], 2);
+        domino.id = 1;
+        let game = |size| {
+            let players = Players::new(vec![Player::new("Bob".to_string(), Color::Green, Color::LightGreen, vec![monomino.clone(), domino.clone()])]);
+            Game::new(GameRules { num_players: 1, ..GameRules::with_board_size(size, size) }, players)
+        };
+
+        // on a 2x2 board the domino cannot attach to the corner of the monomino
+        let small = game(2);
+        let candidate = small.legal_move(0, 0, Position { x: 0, y: 0 }).unwrap();
+        assert_eq!(small.blunder(&candidate), Some(Blunder::NoMovesLeft));
+
+        let large = game(3);
+        let candidate = large.legal_move(0, 0, Position { x: 0, y: 0 }).unwrap();
+        assert_eq!(large.blunder(&candidate), None);
+    }
+}
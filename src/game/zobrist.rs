@@ -0,0 +1,24 @@
+use crate::game::Position;
+
+const TURN_OFFSET: u64 = 1 << 48;
+
+/// The Zobrist key of a cell covered by the given player. Keys are derived from the cell and the
+/// player with a fixed mixing function instead of a random table, so hashes stay the same
+/// across runs and can be stored, for example in an opening book.
+pub fn key(position: &Position, player_index: usize) -> u64 {
+    let cell = ((position.y as u64) << 16) | position.x as u64;
+    mix((cell << 8) | player_index as u64)
+}
+
+/// The key mixed into a position hash for the player whose turn it is.
+pub fn turn_key(player_index: usize) -> u64 {
+    mix(TURN_OFFSET | player_index as u64)
+}
+
+/// The SplitMix64 finalizer, turning consecutive inputs into well distributed keys.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,10 @@
+pub mod bot;
+pub mod game;
+#[cfg(feature = "serde")]
+pub mod net;
+pub mod notify;
+pub mod persistence;
+/// The engine types bots and other frontends build on. Everything exported here is kept
+/// compatible between minor releases, the modules behind it may be reorganized.
+pub mod prelude;
+pub mod ui;
@@ -1,47 +1,430 @@
+use std::fmt::Display;
 use std::io;
+#[cfg(feature = "serde")]
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use ratatui::prelude::Color;
 
-use crate::game::{Game, Piece, Player, Players, Position};
+use blokus::bot::{self, Seat};
+use blokus::bot::limits::Limits;
+#[cfg(feature = "serde")]
+use blokus::bot::external::ExternalEngine;
+use blokus::bot::simulation::{play_out, SimulationResults};
+use blokus::game::{Game, Piece, Player, PlayerKind, Players};
+use blokus::game::board_mask::{generate_board_mask, parse_board_mask};
+use blokus::game::clock::{TimeControl, TimeoutAction};
+use blokus::game::handicap::Handicap;
+use blokus::game::piece_set::read_standard_piece_set;
+use blokus::game::puzzle::generate_puzzle;
+use blokus::game::rules::{GameRules, GameSettings, PRESETS};
+#[cfg(feature = "serde")]
+use blokus::net::{client, discovery};
+#[cfg(feature = "serde")]
+use blokus::net::host::Host;
+#[cfg(feature = "serde")]
+use blokus::net::server::Server;
+use blokus::notify::TurnCommand;
+use blokus::ui::{self, Keymap, Network, Setup};
 
-mod game;
-mod ui;
+const DEFAULT_SAVE_PATH: &str = "blokus-save.json";
+/// Read at startup if it exists, unless `--keymap` names another file.
+const DEFAULT_KEYMAP_PATH: &str = "blokus-keys.conf";
 
 fn main() -> io::Result<()>{
-    let piece_set = read_piece_set().unwrap();
-    let players = Players::new(vec![
-        Player::new("Bob".to_string(), Color::Green, Color::LightGreen, piece_set.clone()),
-        Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, piece_set.clone()),
-        Player::new("Eve".to_string(), Color::Yellow, Color::LightYellow, piece_set.clone()),
-        Player::new("Pete".to_string(), Color::Red, Color::LightRed, piece_set.clone()),
-    ]);
-    let mut game = Game::new(20, 20, players);
-    ui::run(&mut game)
-}
-
-fn read_piece_set() -> Result<Vec<Piece>, String> {
-    std::str::from_utf8(include_bytes!("res/standard_pieces"))
-        .unwrap()
-        .split("\n\n")
-        .map(Piece::from_str)
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let load_path = option_value(&args, "--load").map(PathBuf::from);
+    let save_path = load_path.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_SAVE_PATH));
+
+    let seed = parse_option::<u64>(&args, "--seed")?.unwrap_or_else(rand::random);
+    // imported settings are the base that the other options adjust
+    let mut settings = match option_value(&args, "--settings") {
+        #[cfg(feature = "serde")]
+        Some(path) => GameSettings::load(path)?,
+        _ => GameSettings::default()
+    };
+    if let Some(name) = option_value(&args, "--rules") {
+        settings.rules = GameRules::preset(name)
+            .ok_or_else(|| invalid_input(format!("Unknown --rules '{name}', expected one of {}", PRESETS.join(", "))))?;
+    }
+    if args.iter().any(|arg| arg == "--draft") {
+        settings.rules.draft = true;
+    }
+    if args.iter().any(|arg| arg == "--simultaneous") {
+        settings.rules.simultaneous = true;
+    }
+    apply_board_mask(&args, &mut settings.rules, seed)?;
+    if let Some(time_control) = parse_time_control(&args)? {
+        settings.time_control = Some(time_control);
+    }
+    #[cfg(feature = "serde")]
+    if let Some(path) = option_value(&args, "--export-settings") {
+        return settings.save(path);
+    }
+    if args.first().is_some_and(|arg| arg == "simulate") {
+        return simulate(&args, settings.rules, seed);
+    }
+    #[cfg(feature = "serde")]
+    if let Some(port) = parse_option::<u16>(&args, "--server")? {
+        return serve(&args, port, settings, seed);
+    }
+    #[cfg(feature = "serde")]
+    if let Some(address) = option_value(&args, "--fetch-replay") {
+        return fetch_replay(address, parse_option::<u64>(&args, "--match")?.unwrap_or(1));
+    }
+    let options = ui::Options {
+        save_path,
+        profile_log: option_value(&args, "--profile-log").map(PathBuf::from),
+        mirror_board: args.iter().any(|arg| arg == "--mirror-board"),
+        panel_left: args.iter().any(|arg| arg == "--panel-left"),
+        cursor_style: parse_option(&args, "--cursor")?.unwrap_or_default(),
+        cursor_blink: args.iter().any(|arg| arg == "--cursor-blink"),
+        analysis: args.iter().any(|arg| arg == "--analysis"),
+        warn_blunders: args.iter().any(|arg| arg == "--warn-blunders"),
+        keymap: load_keymap(&args)?,
+    };
+    #[cfg(feature = "serde")]
+    if let Some(index) = args.iter().position(|arg| arg == "--join") {
+        let address = match args.get(index + 1).filter(|arg| !arg.starts_with("--")) {
+            Some(address) => address.clone(),
+            None => match ui::pick_game(&options.keymap)? {
+                Some(address) => address.to_string(),
+                None => return Ok(())
+            }
+        };
+        // the login name is a fair guess for a nickname
+        let nickname = option_value(&args, "--name").map(str::to_string)
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_default();
+        return join(&address, &nickname, &options);
+    }
+
+    let piece_set = read_standard_piece_set().unwrap();
+    let puzzle = parse_option::<usize>(&args, "--puzzle")?;
+    let (rules, pieces) = match puzzle {
+        Some(num_pieces) => {
+            let puzzle = generate_puzzle(&piece_set, num_pieces, &mut StdRng::seed_from_u64(seed))
+                .ok_or_else(|| invalid_input(format!("No puzzle with {num_pieces} pieces found for seed {seed}")))?;
+            (puzzle.rules(), puzzle.pieces)
+        }
+        None => (settings.rules, piece_set)
+    };
+
+    let mut game = match load_path {
+        #[cfg(feature = "serde")]
+        Some(path) => Game::load(path)?,
+        _ => {
+            let players = default_players(&pieces, &parse_seats(&args, "--handicap")?, &parse_seats(&args, "--bots")?)?;
+            let setup = Setup { rules, players };
+            // puzzles are generated for their board and rack
+            let setup = match puzzle.is_none() && !args.iter().any(|arg| arg == "--no-setup") {
+                true => match ui::setup_game(setup, &options.keymap)? {
+                    Some(setup) => setup,
+                    None => return Ok(())
+                },
+                false => setup
+            };
+            start_game(setup.rules, setup.players, seed)
+        }
+    };
+    if let Some(command) = option_value(&args, "--on-turn") {
+        game.add_observer(TurnCommand::new(command.to_string()));
+    }
+    // a loaded game continues with the clock it was saved with
+    if let Some(time_control) = settings.time_control.filter(|_| game.clock().is_none()) {
+        game.start_clock(time_control, Instant::now());
+    }
+    let mut seats = seats(&args, &game)?;
+    let network = network(&args, &mut game, &mut seats)?;
+    ui::run(&mut game, &options, seats, network)
+}
+
+/// The keys of `--keymap`, or of the default keymap file if there is one.
+fn load_keymap(args: &[String]) -> io::Result<Keymap> {
+    match option_value(args, "--keymap") {
+        Some(path) => Keymap::load(path),
+        None if Path::new(DEFAULT_KEYMAP_PATH).exists() => Keymap::load(DEFAULT_KEYMAP_PATH),
+        None => Ok(Keymap::default())
+    }
+}
+
+/// `simulate --games <n>` plays games between the bots of `--bots` without the terminal UI, one
+/// seed after another starting from `--seed`, and prints how every seat did. `--no-book` leaves the
+/// opening book out to compare the raw strength of the bots.
+fn simulate(args: &[String], rules: GameRules, seed: u64) -> io::Result<()> {
+    let num_games = parse_option::<u64>(args, "--games")?.unwrap_or(10);
+    let piece_set = read_standard_piece_set().unwrap();
+    let handicaps = parse_seats(args, "--handicap")?;
+    let kinds = parse_seats(args, "--bots")?;
+    let mut results = SimulationResults::new(rules.num_players);
+    let mut names = vec![];
+    for game_seed in (0..num_games).map(|index| seed.wrapping_add(index)) {
+        let mut game = new_game(rules.clone(), piece_set.clone(), &handicaps, &kinds, game_seed)?;
+        let mut agents = seats(args, &game)?.into_iter()
+            .enumerate()
+            .map(|(player_index, seat)| seat.into_agent()
+                .ok_or_else(|| invalid_input(format!("simulate needs a bot on every seat, seat {} has none", player_index + 1))))
+            .collect::<io::Result<Vec<_>>>()?;
+        play_out(&mut game, &mut agents);
+        results.record(&game);
+        names = game.players().iter().map(|player| format!("{} ({:?})", player.name, player.kind)).collect();
+    }
+
+    println!("{} games from seed {seed}, {:.1} moves on average", results.games, results.average_length());
+    for (player_index, name) in names.iter().enumerate() {
+        println!("{:>2}. {name:<40} {:>5.1}% wins  {:>6.1} average score", player_index + 1, results.win_rate(player_index) * 100.0, results.average_score(player_index));
+    }
+    Ok(())
+}
+
+/// The seat of every player: the engine started by `--engine <seat>:<command>` if there is one,
+/// otherwise the bot of the player's kind. `--no-book` leaves the opening book out, `--bot-time`
+/// and `--bot-nodes` limit how long the bots think, `--bot-time` also how long an engine may
+/// take to answer. `--bot-memory` sets the megabytes of their transposition tables.
+fn seats(args: &[String], game: &Game) -> io::Result<Vec<Seat>> {
+    let engines = parse_seats::<String>(args, "--engine")?;
+    let opening_book = !args.iter().any(|arg| arg == "--no-book");
+    let limits = Limits {
+        time: parse_option::<DurationArg>(args, "--bot-time")?.map(|time| time.0),
+        nodes: parse_option::<u64>(args, "--bot-nodes")?,
+        memory_megabytes: parse_option::<usize>(args, "--bot-memory")?,
+    };
+    game.players().iter()
+        .enumerate()
+        .map(|(player_index, player)| match engines.iter().rev().find(|(seat, _)| *seat == player_index) {
+            #[cfg(feature = "serde")]
+            Some((_, command)) => ExternalEngine::spawn(command)
+                .map(|engine| match limits.time {
+                    Some(time) => engine.with_timeout(time),
+                    None => engine
+                })
+                .map(|engine| Seat::Agent(Box::new(engine)))
+                .map_err(|error| invalid_input(format!("Could not start the engine '{command}': {error}"))),
+            _ => Ok(bot::for_player(player.kind, game.seed() ^ player_index as u64, opening_book, limits))
+        })
+        .collect()
+}
+
+/// `--host <port>` seats a client on every seat without a bot, other than the seats of `--local`
+/// which are played at this terminal, e.g. `--local 1,2` for two players sharing it. Only the
+/// first seat is local by default. The clients join in the lobby, and their moves are checked
+/// against the game here before they are played. Pages in a browser connect to the same port over
+/// WebSocket.
+#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+fn network(args: &[String], game: &mut Game, seats: &mut [Seat]) -> io::Result<Network> {
+    #[cfg(feature = "serde")]
+    if let Some(port) = parse_option::<u16>(args, "--host")? {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|error| io::Error::new(error.kind(), format!("Could not host on port {port}: {error}")))?;
+        let local_seats = parse_seat_list(args, "--local")?.unwrap_or(vec![0]);
+        for (seat_index, seat) in seats.iter_mut().enumerate() {
+            if matches!(seat, Seat::Human) && !local_seats.contains(&seat_index) {
+                *seat = Seat::Remote;
+            }
+        }
+        let host = Host::start(listener, seats);
+        game.add_observer(host.broadcast());
+        // without the announcement, e.g. next to the mDNS responder of the system, the game is
+        // joined by its address
+        let _ = discovery::announce(&format!("{}'s game", game.players()[0].name), port);
+        return Ok(Network::Host(host));
+    }
+    Ok(Network::Local)
+}
+
+/// `--server <port>` serves matches to clients without the terminal UI, e.g. on a machine without
+/// players. Every match is played with the rules, bots and clock of the other options and seeded
+/// from `--seed` onwards. The server logs what happens to stdout, `--log-dir <dir>` keeps the
+/// finished games there. `GET /games/<id>/state` on the same port returns a running match as JSON.
+#[cfg(feature = "serde")]
+fn serve(args: &[String], port: u16, settings: GameSettings, seed: u64) -> io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|error| io::Error::new(error.kind(), format!("Could not serve on port {port}: {error}")))?;
+    let piece_set = read_standard_piece_set().unwrap();
+    let handicaps = parse_seats(args, "--handicap")?;
+    let kinds = parse_seats(args, "--bots")?;
+    let log_dir = option_value(args, "--log-dir").map(PathBuf::from);
+    let args = args.to_vec();
+    let new_match = move |match_id: u64| {
+        let mut game = new_game(settings.rules.clone(), piece_set.clone(), &handicaps, &kinds, seed.wrapping_add(match_id))?;
+        if let Some(time_control) = &settings.time_control {
+            game.start_clock(time_control.clone(), Instant::now());
+        }
+        let seats = seats(&args, &game)?;
+        Ok((game, seats))
+    };
+    println!("Serving on port {port}");
+    if let Err(error) = discovery::announce("Blokus server", port) {
+        println!("Not announced on the local network: {error}");
+    }
+    Server::start(listener, new_match, log_dir, io::stdout())?.run()
+}
+
+/// `--fetch-replay <host:port> --match <id>` downloads the replay of a finished match from a server,
+/// whose log names the ids of its matches, and saves it next to the saves. A host at a terminal
+/// sends the replay of its game whatever the id.
+#[cfg(feature = "serde")]
+fn fetch_replay(address: &str, match_id: u64) -> io::Result<()> {
+    let replay = client::fetch_replay(address, match_id)
+        .map_err(|error| io::Error::new(error.kind(), format!("Could not fetch the replay from {address}: {error}")))?;
+    let path = PathBuf::from(format!("match-{match_id}.replay.json"));
+    replay.save(&path)?;
+    println!("Saved the replay of {} moves to {}", replay.len(), path.display());
+    Ok(())
+}
+
+/// `--join <host:port>` plays the seat the host assigns in the game hosted there, the rules and
+/// bots are up to the host. Without an address, the games announced on the local network are
+/// listed to pick from. `--name <nickname>` is the name the others see.
+#[cfg(feature = "serde")]
+fn join(address: &str, nickname: &str, options: &ui::Options) -> io::Result<()> {
+    let (client, mut game) = client::join(address, nickname)
+        .map_err(|error| io::Error::new(error.kind(), format!("Could not join the game at {address}: {error}")))?;
+    let seats = (0..game.players().len())
+        .map(|seat| if seat == client.seat() { Seat::Human } else { Seat::Remote })
+        .collect();
+    game.add_observer(client.outbox());
+    ui::run(&mut game, options, seats, Network::Client(client))
+}
+
+fn new_game(rules: GameRules, piece_set: Vec<Piece>, handicaps: &[(usize, Handicap)], kinds: &[(usize, PlayerKind)], seed: u64) -> io::Result<Game> {
+    Ok(start_game(rules, default_players(&piece_set, handicaps, kinds)?, seed))
+}
+
+/// Only the first players take part if the rules are for fewer players.
+fn start_game(rules: GameRules, players: Vec<Player>, seed: u64) -> Game {
+    let players = players.into_iter().take(rules.num_players).collect();
+    Game::with_seed(rules, Players::new(players), seed)
+}
+
+/// A player for every seat a game may have, with the handicaps and kinds of `--handicap` and
+/// `--bots`. New games at the terminal start from these in the setup screen.
+fn default_players(piece_set: &[Piece], handicaps: &[(usize, Handicap)], kinds: &[(usize, PlayerKind)]) -> io::Result<Vec<Player>> {
+    let seats = [
+        ("Bob", Color::Green, Color::LightGreen),
+        ("Alice", Color::Blue, Color::LightBlue),
+        ("Eve", Color::Yellow, Color::LightYellow),
+        ("Pete", Color::Red, Color::LightRed),
+    ];
+    seats.into_iter()
+        .enumerate()
+        .map(|(seat, (name, color, secondary_color))| {
+            let mut rack = piece_set.to_vec();
+            handicaps.iter()
+                .filter(|(handicap_seat, _)| *handicap_seat == seat)
+                .try_for_each(|(_, handicap)| handicap.apply(&mut rack))
+                .map_err(|error| invalid_input(format!("Invalid --handicap for seat {}: {error}", seat + 1)))?;
+            let mut player = Player::new(name.to_string(), color, secondary_color, rack);
+            if let Some((_, kind)) = kinds.iter().rev().find(|(kind_seat, _)| *kind_seat == seat) {
+                player.kind = *kind;
+            }
+            Ok(player)
+        })
         .collect()
 }
 
-impl FromStr for Piece {
+/// Per-seat options are given as a comma separated list of `<seat>:<value>` with seats counted
+/// from 1, e.g. `--handicap 1:-X5,1:-F5,2:+1` or `--bots 2:random,3:random`.
+fn parse_seats<T: FromStr<Err: Display>>(args: &[String], option: &str) -> io::Result<Vec<(usize, T)>> {
+    let Some(value) = option_value(args, option) else {
+        return Ok(vec![]);
+    };
+    value.split(',')
+        .map(|entry| {
+            let (seat, entry_value) = entry.trim().split_once(':').ok_or("expected '<seat>:<value>'")?;
+            let seat = seat.parse::<usize>().ok().filter(|seat| *seat > 0).ok_or("seats are counted from 1")?;
+            Ok((seat - 1, entry_value.parse::<T>().map_err(|error| error.to_string())?))
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|error| invalid_input(format!("Invalid {option} '{value}': {error}")))
+}
+
+/// A comma separated list of seats counted from 1, e.g. `--local 1,3`.
+#[cfg(feature = "serde")]
+fn parse_seat_list(args: &[String], option: &str) -> io::Result<Option<Vec<usize>>> {
+    let Some(value) = option_value(args, option) else {
+        return Ok(None);
+    };
+    value.split(',')
+        .map(|seat| seat.trim().parse::<usize>().ok().filter(|seat| *seat > 0).map(|seat| seat - 1))
+        .collect::<Option<Vec<_>>>()
+        .map(Some)
+        .ok_or_else(|| invalid_input(format!("Invalid {option} '{value}': expected seats counted from 1, e.g. '1,2'")))
+}
+
+/// Applies `--board <path>` with a mask file and `--obstacles <count>` for generated obstacles.
+fn apply_board_mask(args: &[String], rules: &mut GameRules, seed: u64) -> io::Result<()> {
+    if let Some(path) = option_value(args, "--board") {
+        let mask = parse_board_mask(&std::fs::read_to_string(path)?)
+            .map_err(|error| invalid_input(format!("Invalid --board '{path}': {error}")))?;
+        rules.width = mask.width;
+        rules.height = mask.height;
+        rules.blocked_cells = mask.blocked;
+    }
+    if let Some(count) = parse_option::<usize>(args, "--obstacles")? {
+        let mut rng = StdRng::seed_from_u64(seed);
+        rules.blocked_cells.extend(generate_board_mask(rules.width, rules.height, count, &mut rng).blocked);
+    }
+    Ok(())
+}
+
+fn parse_time_control(args: &[String]) -> io::Result<Option<TimeControl>> {
+    let Some(budget) = parse_option::<DurationArg>(args, "--time")? else {
+        return Ok(None);
+    };
+    // time odds are given per seat as a comma separated list, e.g. `--seat-time 5m,15m`
+    let seat_budgets = match option_value(args, "--seat-time") {
+        Some(value) => value.split(',')
+            .map(|budget| budget.trim().parse::<DurationArg>().map(|budget| budget.0))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| invalid_input(format!("Invalid --seat-time '{value}': {error}")))?,
+        None => vec![]
+    };
+    let increment = parse_option::<DurationArg>(args, "--increment")?.map(|increment| increment.0).unwrap_or_default();
+    let timeout_action = match option_value(args, "--on-timeout") {
+        None | Some("pass") => TimeoutAction::Pass,
+        Some("forfeit") => TimeoutAction::Forfeit,
+        Some(other) => return Err(invalid_input(format!("Invalid --on-timeout '{other}', expected 'pass' or 'forfeit'")))
+    };
+    Ok(Some(TimeControl { budget: budget.0, seat_budgets, increment, timeout_action }))
+}
+
+fn parse_option<T: FromStr>(args: &[String], name: &str) -> io::Result<Option<T>> where T::Err: std::fmt::Display {
+    option_value(args, name)
+        .map(|value| value.parse::<T>().map_err(|error| invalid_input(format!("Invalid {name} '{value}': {error}"))))
+        .transpose()
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// A duration given as a number with an `h`, `m`, `s` or `ms` suffix, plain numbers are minutes.
+struct DurationArg(Duration);
+
+impl FromStr for DurationArg {
     type Err = String;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let blocks = string
-            .lines()
-            .enumerate()
-            .flat_map(|(y, line)| line.chars().enumerate().filter_map(move |(x, c)| match c {
-                'x' => Some(Position { x: x as u16, y: y as u16 }),
-                _ => None
-            }))
-            .collect::<Vec<_>>();
-
-        let bounding_box_dimension = (string.lines().count() - 1) as f32;
-        let pivot_position = bounding_box_dimension / 2.0;
-        Ok(Piece::new(blocks, pivot_position))
+        let (number, unit_millis) = match string.char_indices().last() {
+            _ if string.ends_with("ms") => (&string[..string.len() - 2], 1),
+            Some((index, 'h')) => (&string[..index], 3_600_000),
+            Some((index, 'm')) => (&string[..index], 60_000),
+            Some((index, 's')) => (&string[..index], 1000),
+            _ => (string, 60_000)
+        };
+        let number = number.parse::<u64>().map_err(|_| "expected a duration like 500ms, 90s, 5m or 1h".to_string())?;
+        let millis = number.checked_mul(unit_millis).ok_or("duration too long")?;
+        Ok(DurationArg(Duration::from_millis(millis)))
     }
-}
\ No newline at end of file
+}
+
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
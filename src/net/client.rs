@@ -0,0 +1,397 @@
+use std::io;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::game::Game;
+use crate::game::observer::GameObserver;
+use crate::game::replay::{Move, Replay};
+use crate::game::simultaneous::Commitment;
+use crate::net::{self, Connection, Incoming};
+use crate::net::protocol::{self, Action, ClientMessage, Link, PROTOCOL_VERSION, RECONNECT_GRACE, Rejection, SeatStatus, ServerMessage};
+
+/// The connection of a client to the host of a game. Messages of the host are received on a
+/// thread of their own, so that the UI can check for them between key presses. If the connection
+/// drops, that thread tries to resume the seat until `RECONNECT_GRACE` is over.
+pub struct Client {
+    seat: usize,
+    /// The token of the seat, it comes with every move.
+    token: u64,
+    connection: Connection,
+    receiver: Receiver<ServerMessage>,
+    reconnecting: Arc<AtomicBool>,
+    lobby: Vec<SeatStatus>,
+    started: bool,
+    connected: bool,
+    /// Why the host removed the client, if it did.
+    refusal: Option<String>,
+    /// The seat and text of the chat messages since the last `take_chat`.
+    chat: Vec<(usize, String)>,
+    /// Why the host did not play the last move, until it is taken.
+    rejection: Option<Rejection>,
+    /// How the seats are connected, as of the last heartbeat of the host, until it is taken.
+    links: Option<Vec<Link>>,
+    /// The replay the host recorded, once the game is over.
+    replay: Option<Replay>,
+}
+
+/// How long the client waits between two attempts to resume its seat.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Connects to the host at `address` and returns the client with the game so far, its player
+/// renamed to the nickname. Fails if the host speaks another protocol version.
+pub fn join(address: &str, nickname: &str) -> io::Result<(Client, Game)> {
+    let (connection, incoming, seat, token, game) = handshake(address, ClientMessage::Join { nickname: nickname.to_string() })?;
+    let (sender, receiver) = mpsc::channel();
+    let reconnecting = Arc::new(AtomicBool::new(false));
+    let receiving = Receiving { address: address.to_string(), token, connection: connection.clone(), sender, reconnecting: reconnecting.clone() };
+    thread::spawn(move || receiving.run(incoming));
+    let client = Client { seat, token, connection, receiver, reconnecting, lobby: vec![], started: false, connected: true, refusal: None, chat: vec![], rejection: None, links: None, replay: None };
+    Ok((client, *game))
+}
+
+/// Asks the host at `address` for the replay of the finished game with the id, see
+/// `ClientMessage::FetchReplay`.
+pub fn fetch_replay(address: &str, game_id: u64) -> io::Result<Replay> {
+    let (connection, mut incoming) = net::split(TcpStream::connect(address)?)?;
+    connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION })?;
+    connection.send(&ClientMessage::FetchReplay { game_id })?;
+    let ServerMessage::Hello { version } = incoming.receive()? else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the host did not say hello"));
+    };
+    protocol::check_version(version).map_err(|error| io::Error::new(error.kind(), format!("the host has an {error}")))?;
+    match incoming.receive()? {
+        ServerMessage::Replay(replay) => Ok(*replay),
+        ServerMessage::Refused(reason) => Err(io::Error::new(io::ErrorKind::NotFound, reason)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "the host did not send a replay"))
+    }
+}
+
+/// Says hello and asks for a seat, returns the connection with the seat, its token and the game.
+fn handshake(address: &str, request: ClientMessage) -> io::Result<(Connection, Incoming, usize, u64, Box<Game>)> {
+    let (connection, mut incoming) = net::split(TcpStream::connect(address)?)?;
+    connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION })?;
+    connection.send(&request)?;
+    let ServerMessage::Hello { version } = incoming.receive()? else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the host did not say hello"));
+    };
+    protocol::check_version(version).map_err(|error| io::Error::new(error.kind(), format!("the host has an {error}")))?;
+    match incoming.receive()? {
+        ServerMessage::SeatAssigned { seat, token, game } => Ok((connection, incoming, seat, token, game)),
+        // unlike a refused connection, there is no point in trying again
+        ServerMessage::Refused(reason) => Err(io::Error::new(io::ErrorKind::PermissionDenied, reason)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "the host did not assign a seat"))
+    }
+}
+
+/// The receiving end of the client, on a thread of its own.
+struct Receiving {
+    address: String,
+    token: u64,
+    /// Shared with the client, so that it sends over the resumed connection.
+    connection: Connection,
+    sender: Sender<ServerMessage>,
+    reconnecting: Arc<AtomicBool>,
+}
+
+impl Receiving {
+    /// Passes the messages of the host on to the client until the host turns it away, the seat
+    /// cannot be resumed any more or the client is gone.
+    fn run(self, mut incoming: Incoming) {
+        loop {
+            while let Ok(message) = incoming.receive() {
+                // answered right away, so that the round trip leaves out the UI
+                if let ServerMessage::Ping(heartbeat) = message {
+                    let _ = self.connection.send(&ClientMessage::Pong(heartbeat));
+                    continue;
+                }
+                let refused = matches!(message, ServerMessage::Refused(_));
+                if self.sender.send(message).is_err() || refused {
+                    return;
+                }
+            }
+            self.reconnecting.store(true, Ordering::Relaxed);
+            let Some((resumed, game)) = self.resume() else {
+                return;
+            };
+            incoming = resumed;
+            self.reconnecting.store(false, Ordering::Relaxed);
+            // the game of the host catches the client up on the moves it missed
+            if self.sender.send(ServerMessage::StateSync(game)).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn resume(&self) -> Option<(Incoming, Box<Game>)> {
+        let started = Instant::now();
+        while started.elapsed() < RECONNECT_GRACE {
+            thread::sleep(RECONNECT_INTERVAL);
+            match handshake(&self.address, ClientMessage::Resume { token: self.token }) {
+                Ok((connection, incoming, _, _, game)) => {
+                    self.connection.replace(&connection).ok()?;
+                    return Some((incoming, game));
+                }
+                Err(error) if error.kind() == io::ErrorKind::PermissionDenied => return None,
+                Err(_) => ()
+            }
+        }
+        None
+    }
+}
+
+impl Client {
+    /// The seat played at this machine.
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    /// Handles the messages the host sent since the last call, the states of the host replace
+    /// the game. Returns whether the game changed.
+    pub fn poll(&mut self, game: &mut Game) -> bool {
+        let mut changed = false;
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ServerMessage::StateSync(state)) => {
+                    game.sync_with(*state);
+                    changed = true;
+                }
+                Ok(ServerMessage::Lobby(seats)) => self.lobby = seats,
+                Ok(ServerMessage::GameStarted) => self.started = true,
+                Ok(ServerMessage::Refused(reason)) => self.refusal = Some(reason),
+                Ok(ServerMessage::Chat { seat, text }) => self.chat.push((seat, text)),
+                // the state of the host follows and takes the move back
+                Ok(ServerMessage::MoveRejected(rejection)) => self.rejection = Some(rejection),
+                Ok(ServerMessage::Links(links)) => self.links = Some(links),
+                Ok(ServerMessage::Replay(replay)) => self.replay = Some(*replay),
+                Ok(_) => (),
+                Err(TryRecvError::Empty) => return changed,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    return changed;
+                }
+            }
+        }
+    }
+
+    /// Who sits where, as of the last update of the lobby.
+    pub fn lobby(&self) -> &[SeatStatus] {
+        &self.lobby
+    }
+
+    /// Whether the host closed the lobby.
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Whether the client is connected to the host, it is not while it tries to resume its seat.
+    pub fn is_connected(&self) -> bool {
+        self.connected && !self.reconnecting.load(Ordering::Relaxed)
+    }
+
+    pub fn refusal(&self) -> Option<&str> {
+        self.refusal.as_deref()
+    }
+
+    /// Tells the host whether the player is ready to start.
+    pub fn set_ready(&self, ready: bool) {
+        // a host that is gone shows up in `is_connected`
+        let _ = self.connection.send(&ClientMessage::Ready(ready));
+    }
+
+    /// Sends a chat message, it comes back with the others once the host relayed it.
+    pub fn send_chat(&self, text: String) {
+        let _ = self.connection.send(&ClientMessage::Chat(text));
+    }
+
+    /// The chat messages received since the last call.
+    pub fn take_chat(&mut self) -> Vec<(usize, String)> {
+        std::mem::take(&mut self.chat)
+    }
+
+    /// Why the host rejected the last move, if it did since the last call.
+    pub fn take_rejection(&mut self) -> Option<Rejection> {
+        self.rejection.take()
+    }
+
+    /// How the seats are connected, if the host sent a heartbeat since the last call.
+    pub fn take_links(&mut self) -> Option<Vec<Link>> {
+        self.links.take()
+    }
+
+    /// The replay of the host, once the game is over.
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    /// The observer that sends the moves of this client's seat to the host. It has to be added to
+    /// the game the client plays on.
+    pub fn outbox(&self) -> Outbox {
+        Outbox { seat: self.seat, token: self.token, connection: self.connection.clone() }
+    }
+}
+
+/// Sends the moves made at this machine to the host. The game of the host is the authoritative
+/// one, its next state replaces the game of the client.
+pub struct Outbox {
+    seat: usize,
+    token: u64,
+    connection: Connection,
+}
+
+impl Outbox {
+    fn send(&self, action: Action) {
+        // a host that is gone no longer sends states, the game just stops moving
+        let _ = self.connection.send(&ClientMessage::MovePlayed { token: self.token, action });
+    }
+}
+
+impl GameObserver for Outbox {
+    fn on_piece_placed(&mut self, _game: &Game, played: &Move) {
+        if played.player == self.seat {
+            self.send(Action::Place(played.clone()));
+        }
+    }
+
+    /// The host checks the move and commits it on its own game.
+    fn on_committed(&mut self, _game: &Game, commitment: &Commitment) {
+        if let Some(played) = commitment.played.as_ref().filter(|played| played.player == self.seat) {
+            self.send(Action::Place(played.clone()));
+        }
+    }
+
+    fn on_passed(&mut self, _game: &Game, player_index: usize) {
+        if player_index == self.seat {
+            self.send(Action::Pass);
+        }
+    }
+
+    fn on_draft_pick(&mut self, _game: &Game, player_index: usize, pool_index: usize) {
+        if player_index == self.seat {
+            self.send(Action::DraftPick(pool_index));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use ratatui::style::Color;
+
+    use crate::bot::Seat;
+    use crate::game::{LegalMove, Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+    use crate::net::host::Host;
+
+    use super::*;
+
+    /// The move of the remote player of the second seat, the host hands the moves of its client
+    /// on while it polls.
+    fn remote_move(host: &mut Host, host_game: &mut Game, seat: Seat) -> LegalMove {
+        let Seat::Agent(mut remote) = seat else { panic!("Open seats have a remote player") };
+        let copy = host_game.clone();
+        let choosing = thread::spawn(move || remote.choose_move(&copy, 1));
+        while !choosing.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(host_game);
+        }
+        choosing.join().unwrap().unwrap()
+    }
+
+    #[test]
+    fn should_play_the_moves_of_the_client_on_the_host() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let mut host_game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, 1);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut seats = vec![Seat::Human, Seat::Remote];
+        let mut host = Host::start(listener, &mut seats);
+        host_game.add_observer(host.broadcast());
+        let joining = thread::spawn(move || join(&address, "Carol").unwrap());
+        while host.seats()[1] == SeatStatus::Open {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut host_game);
+        }
+        let (mut client, mut game) = joining.join().unwrap();
+        assert_eq!(client.seat(), 1);
+        game.add_observer(client.outbox());
+        client.set_ready(true);
+        while !client.is_started() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut host_game);
+            host.start_game();
+            client.poll(&mut game);
+        }
+        assert_eq!(client.lobby(), [SeatStatus::Host, SeatStatus::Joined { ready: true }]);
+
+        if game.active_player_index() == 0 {
+            let legal_move = host_game.legal_moves().remove(0);
+            assert!(host_game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position).unwrap());
+            while !client.poll(&mut game) {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let legal_move = game.legal_moves().remove(0);
+        assert!(game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position).unwrap());
+        let chosen = remote_move(&mut host, &mut host_game, seats.remove(1));
+        assert!(host_game.place_piece(chosen.piece_index, chosen.orientation, chosen.position).unwrap());
+
+        let mut state = game.clone();
+        while !client.poll(&mut state) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(state.replay(), game.replay());
+        assert_eq!(state.active_player_index(), game.active_player_index());
+    }
+
+    #[test]
+    fn should_commit_the_moves_of_the_client_on_the_host() {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let mut host_game = Game::with_seed(GameRules { num_players: 2, simultaneous: true, ..GameRules::with_board_size(14, 14) }, players, 1);
+        // the client commits first
+        if host_game.active_player_index() != 1 {
+            host_game.rematch();
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut seats = vec![Seat::Human, Seat::Remote];
+        let mut host = Host::start(listener, &mut seats);
+        host_game.add_observer(host.broadcast());
+        let joining = thread::spawn(move || join(&address, "Carol").unwrap());
+        while host.seats()[1] == SeatStatus::Open {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut host_game);
+        }
+        let (mut client, mut game) = joining.join().unwrap();
+        game.add_observer(client.outbox());
+        client.set_ready(true);
+        while !client.is_started() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut host_game);
+            host.start_game();
+            client.poll(&mut game);
+        }
+
+        let legal_move = game.legal_moves().remove(0);
+        assert!(game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position.clone()).unwrap());
+        let chosen = remote_move(&mut host, &mut host_game, seats.remove(1));
+        assert_eq!(chosen.position, legal_move.position);
+        assert!(host_game.place_piece(chosen.piece_index, chosen.orientation, chosen.position).unwrap());
+        assert!(host_game.has_committed(1));
+        assert_eq!(host_game.active_player_index(), 0);
+    }
+}
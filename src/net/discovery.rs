@@ -0,0 +1,289 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::net::protocol::PROTOCOL_VERSION;
+
+/// The DNS-SD service hosts announce their games under.
+pub const SERVICE: &str = "_blokus._tcp.local";
+
+const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// How long others may remember an answer, in seconds.
+const TTL: u32 = 120;
+
+/// A game announced on the local network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Announced {
+    pub name: String,
+    pub address: SocketAddr,
+    /// The protocol version of the host, only games of the same version can be joined.
+    pub version: Option<u32>,
+}
+
+/// Answers the mDNS queries for `SERVICE` on a thread of its own, for as long as the program runs.
+/// Fails if the port of mDNS is taken, e.g. by the responder of the system, the game can still be
+/// joined by its address then.
+pub fn announce(name: &str, port: u16) -> io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, MDNS_PORT))?;
+    socket.join_multicast_v4(&MDNS_GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    // a label has at most 63 bytes
+    let instance = name.char_indices().take_while(|(index, char)| index + char.len_utf8() <= 63).map(|(_, char)| char).collect::<String>();
+    thread::spawn(move || {
+        let mut buffer = [0; 1500];
+        while let Ok((length, source)) = socket.recv_from(&mut buffer) {
+            let Some(id) = asks_for_service(&buffer[..length]) else {
+                continue;
+            };
+            let Ok(address) = local_address_towards(source) else {
+                continue;
+            };
+            let response = response(id, &instance, address, port);
+            // queries from other ports than mDNS' own expect the answer right back
+            let target = match source.port() {
+                MDNS_PORT => SocketAddr::V4(SocketAddrV4::new(MDNS_GROUP, MDNS_PORT)),
+                _ => source
+            };
+            let _ = socket.send_to(&response, target);
+        }
+    });
+    Ok(())
+}
+
+/// Asks the local network for games and collects the answers until `timeout` is over.
+pub fn browse(timeout: Duration) -> io::Result<Vec<Announced>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    let mut query = header(0, 0, [1, 0, 0, 0]);
+    write_name(&mut query, SERVICE);
+    query.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    socket.send_to(&query, (MDNS_GROUP, MDNS_PORT))?;
+
+    let mut games: Vec<Announced> = vec![];
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0; 1500];
+    while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|left| !left.is_zero()) {
+        socket.set_read_timeout(Some(left))?;
+        let (length, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(error) if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => break,
+            Err(error) => return Err(error)
+        };
+        for game in parse_response(&buffer[..length], source) {
+            if !games.contains(&game) {
+                games.push(game);
+            }
+        }
+    }
+    Ok(games)
+}
+
+/// The id of the query if it asks for the games of `SERVICE`.
+fn asks_for_service(packet: &[u8]) -> Option<u16> {
+    let id = read_u16(packet, 0)?;
+    let is_response = read_u16(packet, 2)? & 0x8000 != 0;
+    let questions = read_u16(packet, 4)?;
+    let mut offset = 12;
+    for _ in 0..questions {
+        let (name, next) = read_name(packet, offset)?;
+        let record_type = read_u16(packet, next)?;
+        offset = next + 4;
+        if !is_response && name.eq_ignore_ascii_case(SERVICE) && matches!(record_type, TYPE_PTR | TYPE_ANY) {
+            return Some(id);
+        }
+    }
+    None
+}
+
+/// The address of this machine the querier can reach.
+fn local_address_towards(querier: SocketAddr) -> io::Result<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect(querier)?;
+    match socket.local_addr()? {
+        SocketAddr::V4(address) => Ok(*address.ip()),
+        SocketAddr::V6(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "mDNS is only announced over IPv4"))
+    }
+}
+
+/// The answer naming the game, with the port, the protocol version and the address of the host
+/// as additional records.
+fn response(id: u16, instance: &str, address: Ipv4Addr, port: u16) -> Vec<u8> {
+    let instance_name = format!("{instance}.{SERVICE}");
+    let host_name = format!("blokus-{}.local", address.to_string().replace('.', "-"));
+    // an authoritative response with one answer and three additional records
+    let mut packet = header(id, 0x8400, [0, 1, 0, 3]);
+
+    let mut ptr = vec![];
+    write_name(&mut ptr, &instance_name);
+    write_record(&mut packet, SERVICE, TYPE_PTR, &ptr);
+
+    let mut srv = vec![0, 0, 0, 0];
+    srv.extend_from_slice(&port.to_be_bytes());
+    write_name(&mut srv, &host_name);
+    write_record(&mut packet, &instance_name, TYPE_SRV, &srv);
+
+    let version = format!("protocol={PROTOCOL_VERSION}");
+    let mut txt = vec![version.len() as u8];
+    txt.extend_from_slice(version.as_bytes());
+    write_record(&mut packet, &instance_name, TYPE_TXT, &txt);
+
+    write_record(&mut packet, &host_name, TYPE_A, &address.octets());
+    packet
+}
+
+/// The games in the answer of a host, `source` is where it came from.
+fn parse_response(packet: &[u8], source: SocketAddr) -> Vec<Announced> {
+    let mut instances = vec![];
+    let mut ports = vec![];
+    let mut versions = vec![];
+    let mut addresses = vec![];
+    let Some(records) = parse_records(packet) else {
+        return vec![];
+    };
+    for (name, record_type, data_offset, data_length) in records {
+        match record_type {
+            TYPE_PTR if name.eq_ignore_ascii_case(SERVICE) => {
+                if let Some((instance, _)) = read_name(packet, data_offset) {
+                    instances.push(instance);
+                }
+            }
+            TYPE_SRV => {
+                if let (Some(port), Some((target, _))) = (read_u16(packet, data_offset + 4), read_name(packet, data_offset + 6)) {
+                    ports.push((name, port, target));
+                }
+            }
+            TYPE_TXT => {
+                let text = packet.get(data_offset + 1..data_offset + data_length).map(String::from_utf8_lossy).unwrap_or_default();
+                if let Some(version) = text.strip_prefix("protocol=").and_then(|version| version.parse::<u32>().ok()) {
+                    versions.push((name, version));
+                }
+            }
+            TYPE_A if data_length == 4 => {
+                let octets = &packet[data_offset..data_offset + 4];
+                addresses.push((name, Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3])));
+            }
+            _ => ()
+        }
+    }
+    instances.into_iter()
+        .filter_map(|instance| {
+            let (_, port, target) = ports.iter().find(|(name, _, _)| name.eq_ignore_ascii_case(&instance))?;
+            let ip = addresses.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(target))
+                .map_or(source.ip(), |(_, address)| (*address).into());
+            Some(Announced {
+                name: instance.strip_suffix(&format!(".{SERVICE}")).unwrap_or(&instance).to_string(),
+                address: SocketAddr::new(ip, *port),
+                version: versions.iter().find(|(name, _)| name.eq_ignore_ascii_case(&instance)).map(|(_, version)| *version),
+            })
+        })
+        .collect()
+}
+
+/// The name, type, offset and length of the data of every record in a response.
+fn parse_records(packet: &[u8]) -> Option<Vec<(String, u16, usize, usize)>> {
+    if read_u16(packet, 2)? & 0x8000 == 0 {
+        return None;
+    }
+    let questions = read_u16(packet, 4)?;
+    let records = (6..12).step_by(2).map(|offset| read_u16(packet, offset).map(usize::from)).sum::<Option<usize>>()?;
+    let mut offset = 12;
+    for _ in 0..questions {
+        offset = read_name(packet, offset)?.1 + 4;
+    }
+    let mut parsed = vec![];
+    for _ in 0..records {
+        let (name, next) = read_name(packet, offset)?;
+        let record_type = read_u16(packet, next)?;
+        let data_length = read_u16(packet, next + 8)? as usize;
+        let data_offset = next + 10;
+        packet.get(data_offset..data_offset + data_length)?;
+        parsed.push((name, record_type, data_offset, data_length));
+        offset = data_offset + data_length;
+    }
+    Some(parsed)
+}
+
+/// The header of a packet with the number of questions, answers, authority and additional records.
+fn header(id: u16, flags: u16, counts: [u16; 4]) -> Vec<u8> {
+    [id, flags].iter().chain(&counts).flat_map(|value| value.to_be_bytes()).collect()
+}
+
+fn write_record(packet: &mut Vec<u8>, name: &str, record_type: u16, data: &[u8]) {
+    write_name(packet, name);
+    packet.extend_from_slice(&record_type.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    packet.extend_from_slice(&TTL.to_be_bytes());
+    packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+}
+
+/// Writes the name label by label, the first label of an instance may contain dots of its own.
+fn write_name(packet: &mut Vec<u8>, name: &str) {
+    let (instance, service) = match name.strip_suffix(SERVICE) {
+        Some(instance) if !instance.is_empty() => (Some(instance.trim_end_matches('.')), SERVICE),
+        _ => (None, name)
+    };
+    for label in instance.into_iter().chain(service.split('.')) {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+}
+
+/// Reads a name, following compression pointers, and returns it with the offset after it.
+fn read_name(packet: &[u8], offset: usize) -> Option<(String, usize)> {
+    let mut labels = vec![];
+    let mut position = offset;
+    let mut end = None;
+    // pointers only lead backwards, a packet cannot have more labels than bytes
+    for _ in 0..packet.len() {
+        let length = *packet.get(position)? as usize;
+        match length {
+            0 => return Some((labels.join("."), end.unwrap_or(position + 1))),
+            _ if length & 0xC0 == 0xC0 => {
+                end.get_or_insert(position + 2);
+                position = (read_u16(packet, position)? & 0x3FFF) as usize;
+            }
+            _ => {
+                labels.push(String::from_utf8_lossy(packet.get(position + 1..position + 1 + length)?).into_owned());
+                position += 1 + length;
+            }
+        }
+    }
+    None
+}
+
+fn read_u16(packet: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*packet.get(offset)?, *packet.get(offset + 1)?]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_the_game_in_the_response_to_a_query() {
+        let mut query = header(7, 0, [1, 0, 0, 0]);
+        write_name(&mut query, SERVICE);
+        query.extend_from_slice(&TYPE_PTR.to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+        assert_eq!(asks_for_service(&query), Some(7));
+
+        let response = response(7, "Bob's game. Join!", Ipv4Addr::new(192, 168, 1, 20), 7788);
+        assert_eq!(asks_for_service(&response), None, "Responses are no queries");
+        let source = SocketAddr::from(([10, 0, 0, 1], MDNS_PORT));
+        assert_eq!(parse_response(&response, source), [Announced {
+            name: "Bob's game. Join!".to_string(),
+            address: SocketAddr::from(([192, 168, 1, 20], 7788)),
+            version: Some(PROTOCOL_VERSION),
+        }]);
+    }
+}
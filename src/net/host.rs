@@ -0,0 +1,828 @@
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::{Agent, largest_draft_piece, Seat};
+use crate::game::{Game, LegalMove};
+use crate::game::error::GameError;
+use crate::game::observer::GameObserver;
+use crate::game::replay::{Move, Replay};
+use crate::net::{self, Accepted, Connection, Incoming, http};
+use crate::net::protocol::{self, Action, ClientMessage, HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT, Link, MAX_CHAT_LENGTH, MAX_NICKNAME_LENGTH, PROTOCOL_VERSION, RECONNECT_GRACE, Rejection, SeatStatus, ServerMessage};
+
+/// How long a client may take to say hello and ask for a seat, a replay or the state.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A game hosted at this machine. Clients connect in the background and take the open seats in
+/// the lobby, the host starts the game once everybody is ready. The host keeps the authoritative
+/// game: the moves of a client are played by the `RemotePlayer` of its seat, and the `Broadcast`
+/// observer sends the game to every client after every turn. A client that drops out of the
+/// running game may resume its seat with its token for `RECONNECT_GRACE`.
+pub struct Host {
+    seats: Vec<SeatStatus>,
+    broadcast: Broadcast,
+    /// The client on every seat, to tell a client that left from one that took the seat after it.
+    client_ids: Vec<Option<u64>>,
+    next_client_id: u64,
+    /// The tokens of the seats, they resume a seat and come with every move.
+    tokens: Vec<Option<u64>>,
+    /// The nicknames the clients joined with, the players of their seats are renamed to them.
+    nicknames: Vec<Option<String>>,
+    /// The names of the players before any client joined.
+    default_names: Vec<String>,
+    /// When the client of the seat dropped out of the running game.
+    left_at: Vec<Option<Instant>>,
+    /// Where the moves of the remote seats go.
+    moves: Vec<Option<Sender<Option<Action>>>>,
+    events: Receiver<HostEvent>,
+    sender: Sender<HostEvent>,
+    started: bool,
+    notice: Option<String>,
+    /// The seat and text of the chat messages since the last `take_chat`.
+    chat: Vec<(usize, String)>,
+    /// The number of the last heartbeat and when it was sent.
+    heartbeat: (u64, Instant),
+    /// The round trip of the last heartbeat the client of the seat answered.
+    pings: Vec<Option<Duration>>,
+    /// When the client of the seat last answered a heartbeat, or took the seat.
+    answered_at: Vec<Option<Instant>>,
+    /// The links of the last heartbeat, until they are taken.
+    links: Option<Vec<Link>>,
+}
+
+pub(super) enum HostEvent {
+    /// A client said hello in the version of the host and asks for a seat.
+    Connected { connection: Connection, incoming: Incoming, request: SeatRequest },
+    /// A client asks for the replay of a finished game.
+    ReplayRequested { connection: Connection, game_id: u64 },
+    /// An HTTP request for the state of the game, see `http`.
+    StateRequested { connection: Connection, game_id: u64 },
+    Refused(String),
+    Message { client_id: u64, seat: usize, message: ClientMessage },
+    /// The answer to a heartbeat, with the time it arrived rather than the time the host got to it.
+    Pong { client_id: u64, seat: usize, heartbeat: u64, received: Instant },
+    Left { client_id: u64, seat: usize },
+}
+
+pub(super) enum SeatRequest {
+    Join { nickname: String },
+    /// Takes back the seat of the token.
+    Resume { token: u64 },
+}
+
+impl Host {
+    /// Accepts clients on `listener` from now on. Every `Seat::Remote` is seated with a
+    /// `RemotePlayer` and waits for a client, the human players take turns at the terminal of the
+    /// host.
+    pub fn start(listener: TcpListener, seats: &mut [Seat]) -> Self {
+        let host = Host::new(seats, true);
+        let accepted = host.sender.clone();
+        thread::spawn(move || accept_clients(listener, accepted));
+        host
+    }
+
+    /// A game without a terminal, every human player is seated with a `RemotePlayer` as well. The
+    /// clients are handed over with `seat_client`, see `Server`.
+    pub(super) fn headless(seats: &mut [Seat]) -> Self {
+        Host::new(seats, false)
+    }
+
+    fn new(seats: &mut [Seat], with_terminal: bool) -> Self {
+        let broadcast = Broadcast { clients: Arc::new(Mutex::new(vec![None; seats.len()])) };
+        let mut statuses = vec![];
+        let mut moves = vec![];
+        for (seat_index, seat) in seats.iter_mut().enumerate() {
+            let status = match seat {
+                Seat::Human if with_terminal => SeatStatus::Host,
+                Seat::Human | Seat::Remote => SeatStatus::Open,
+                Seat::Agent(_) => SeatStatus::Bot
+            };
+            moves.push((status == SeatStatus::Open).then(|| {
+                let (sender, receiver) = mpsc::channel();
+                *seat = Seat::Agent(Box::new(RemotePlayer { seat: seat_index, moves: receiver, gone: false, replies: broadcast.clone() }));
+                sender
+            }));
+            statuses.push(status);
+        }
+        let (sender, events) = mpsc::channel();
+        Host {
+            client_ids: vec![None; statuses.len()],
+            tokens: vec![None; statuses.len()],
+            nicknames: vec![None; statuses.len()],
+            default_names: vec![],
+            left_at: vec![None; statuses.len()],
+            broadcast,
+            seats: statuses,
+            next_client_id: 0,
+            moves,
+            events,
+            sender,
+            started: false,
+            notice: None,
+            chat: vec![],
+            heartbeat: (0, Instant::now()),
+            pings: vec![None; seats.len()],
+            answered_at: vec![None; seats.len()],
+            links: None,
+        }
+    }
+
+    /// The observer that keeps the clients up to date, it has to be added to the hosted game.
+    pub fn broadcast(&self) -> Broadcast {
+        self.broadcast.clone()
+    }
+
+    pub fn seats(&self) -> &[SeatStatus] {
+        &self.seats
+    }
+
+    /// What went wrong with the last client that was turned away.
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Whether every seat is taken by a client that is ready.
+    pub fn can_start(&self) -> bool {
+        self.seats.iter().all(|status| !matches!(status, SeatStatus::Open | SeatStatus::Joined { ready: false }))
+    }
+
+    /// Closes the lobby, if everybody is ready.
+    pub fn start_game(&mut self) {
+        if self.can_start() && !self.started {
+            self.started = true;
+            self.broadcast.send(&ServerMessage::GameStarted);
+        }
+    }
+
+    /// Removes the client from its seat in the lobby, so that somebody else may take it.
+    pub fn kick(&mut self, seat: usize) {
+        if self.started || !matches!(self.seats.get(seat), Some(SeatStatus::Joined { .. })) {
+            return;
+        }
+        if let Some(connection) = &self.broadcast.clients.lock().expect("Clients are not poisoned")[seat] {
+            let _ = connection.send(&ServerMessage::Refused("the host removed you from the game".to_string()));
+        }
+        self.free_seat(seat);
+    }
+
+    /// Seats the clients that connected since the last call, handles what they sent and sends the
+    /// heartbeats. Returns whether the game changed, i.e. whether the player of a client that did
+    /// not come back resigned.
+    pub fn poll(&mut self, game: &mut Game) -> bool {
+        let mut changed = false;
+        if self.heartbeat.1.elapsed() >= HEARTBEAT_INTERVAL {
+            self.send_heartbeat();
+        }
+        for seat in 0..self.seats.len() {
+            if self.left_at[seat].is_some_and(|left_at| left_at.elapsed() >= RECONNECT_GRACE) {
+                self.give_up_seat(seat);
+                game.resign(seat);
+                changed = true;
+            }
+        }
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                HostEvent::Connected { connection, incoming, request } => self.seat_client(game, connection, incoming, request),
+                HostEvent::ReplayRequested { connection, .. } => {
+                    let replay = game.is_over().then(|| game.replay()).ok_or_else(|| "the game is not over yet".to_string());
+                    send_replay(connection, replay);
+                }
+                // the host has just its own game, whatever the id
+                HostEvent::StateRequested { connection, .. } => http::respond_state(connection, game),
+                HostEvent::Refused(error) => self.notice = Some(error),
+                HostEvent::Message { client_id, seat, message: ClientMessage::Ready(ready) } if self.is_seated(client_id, seat) && !self.started => {
+                    self.seats[seat] = SeatStatus::Joined { ready };
+                    self.send_lobby();
+                }
+                HostEvent::Message { client_id, seat, message: ClientMessage::Chat(text) } if self.is_seated(client_id, seat) => self.relay_chat(seat, text),
+                HostEvent::Message { client_id, seat, message: ClientMessage::MovePlayed { token, action } } if self.is_seated(client_id, seat) => {
+                    self.forward_move(game, seat, token, action);
+                }
+                HostEvent::Message { .. } => (),
+                HostEvent::Pong { client_id, seat, heartbeat, received } if self.is_seated(client_id, seat) && heartbeat == self.heartbeat.0 => {
+                    self.pings[seat] = Some(received.saturating_duration_since(self.heartbeat.1));
+                    self.answered_at[seat] = Some(received);
+                }
+                HostEvent::Pong { .. } => (),
+                HostEvent::Left { client_id, seat } if self.is_seated(client_id, seat) => self.free_seat(seat),
+                HostEvent::Left { .. } => ()
+            }
+        }
+        self.rename_players(game);
+        changed
+    }
+
+    /// Renames the players of the seats to the nicknames of their clients, and back once a client
+    /// left the lobby.
+    fn rename_players(&mut self, game: &mut Game) {
+        if self.default_names.is_empty() {
+            self.default_names = game.players().iter().map(|player| player.name.clone()).collect();
+        }
+        let mut renamed = false;
+        for seat in 0..self.seats.len() {
+            let name = self.nicknames[seat].as_ref().unwrap_or(&self.default_names[seat]);
+            if game.players()[seat].name != *name {
+                game.rename_player(seat, name.clone());
+                renamed = true;
+            }
+        }
+        if renamed {
+            self.broadcast.sync(game);
+        }
+    }
+
+    /// Sends a chat message of the players at the terminal of the host, in the name of the first
+    /// of them.
+    pub fn send_chat(&mut self, text: String) {
+        if let Some(seat) = self.seats.iter().position(|status| *status == SeatStatus::Host) {
+            self.relay_chat(seat, text);
+        }
+    }
+
+    /// The chat messages since the last call, including the host's own.
+    pub fn take_chat(&mut self) -> Vec<(usize, String)> {
+        std::mem::take(&mut self.chat)
+    }
+
+    /// Passes a chat message on to every client, cut to `MAX_CHAT_LENGTH` and without control
+    /// characters that would garble the terminals.
+    fn relay_chat(&mut self, seat: usize, text: String) {
+        let text = text.chars().filter(|char| !char.is_control()).take(MAX_CHAT_LENGTH).collect::<String>();
+        if text.trim().is_empty() {
+            return;
+        }
+        self.broadcast.send(&ServerMessage::Chat { seat, text: text.clone() });
+        self.chat.push((seat, text));
+    }
+
+    /// How every seat is connected, as of the last heartbeat, if there was one since the last call.
+    pub fn take_links(&mut self) -> Option<Vec<Link>> {
+        self.links.take()
+    }
+
+    /// Tells everybody how the seats are connected and pings the clients again.
+    fn send_heartbeat(&mut self) {
+        let links = (0..self.seats.len()).map(|seat| self.link(seat)).collect::<Vec<_>>();
+        self.broadcast.send(&ServerMessage::Links(links.clone()));
+        self.links = Some(links);
+        self.heartbeat = (self.heartbeat.0 + 1, Instant::now());
+        self.broadcast.send(&ServerMessage::Ping(self.heartbeat.0));
+    }
+
+    fn link(&self, seat: usize) -> Link {
+        match self.seats[seat] {
+            SeatStatus::Host | SeatStatus::Bot => Link::Local,
+            _ if self.client_ids[seat].is_none() => Link::Lost,
+            _ if self.answered_at[seat].is_none_or(|answered_at| answered_at.elapsed() > HEARTBEAT_TIMEOUT) => Link::Lost,
+            _ => Link::Online { ping_millis: self.pings[seat].map(|ping| ping.as_millis() as u32) }
+        }
+    }
+
+    /// Whether the token resumes a seat of this game.
+    pub(super) fn holds_token(&self, token: u64) -> bool {
+        self.tokens.contains(&Some(token))
+    }
+
+    /// Whether a client could take a seat in the lobby.
+    pub(super) fn has_open_seat(&self) -> bool {
+        !self.started && self.seats.contains(&SeatStatus::Open)
+    }
+
+    /// Closes the connections of all clients, e.g. once the game is over.
+    pub(super) fn close(&self) {
+        for client in self.broadcast.clients.lock().expect("Clients are not poisoned").iter().flatten() {
+            client.close();
+        }
+    }
+
+    fn is_seated(&self, client_id: u64, seat: usize) -> bool {
+        self.client_ids[seat] == Some(client_id)
+    }
+
+    pub(super) fn seat_client(&mut self, game: &mut Game, connection: Connection, incoming: Incoming, request: SeatRequest) {
+        let seat = match request {
+            SeatRequest::Resume { token } => self.tokens.iter().position(|seat_token| *seat_token == Some(token)),
+            SeatRequest::Join { .. } => self.seats.iter().position(|status| *status == SeatStatus::Open).filter(|_| !self.started)
+        };
+        let Some(seat) = seat else {
+            let reason = match request {
+                SeatRequest::Resume { .. } => "the seat cannot be resumed",
+                SeatRequest::Join { .. } => "every seat is taken"
+            };
+            let _ = connection.send(&ServerMessage::Refused(reason.to_string()));
+            connection.close();
+            return;
+        };
+        if let SeatRequest::Join { nickname } = &request {
+            // the other clients get the new name with the game
+            self.nicknames[seat] = sanitize_nickname(nickname);
+            self.rename_players(game);
+        }
+        // the game so far catches a resumed client up on the moves it missed
+        let token = self.tokens[seat].unwrap_or_else(rand::random);
+        if connection.send(&ServerMessage::SeatAssigned { seat, token, game: Box::new(game.as_seen_by(seat)) }).is_err() {
+            return;
+        }
+        self.tokens[seat] = Some(token);
+        self.left_at[seat] = None;
+        self.pings[seat] = None;
+        self.answered_at[seat] = Some(Instant::now());
+        // the host may not have noticed yet that the old connection dropped
+        if let Some(dropped) = self.broadcast.clients.lock().expect("Clients are not poisoned")[seat].take() {
+            dropped.close();
+        }
+        let client_id = self.next_client_id;
+        self.next_client_id += 1;
+        let events = self.sender.clone();
+        thread::spawn(move || forward_messages(incoming, client_id, seat, events));
+        self.client_ids[seat] = Some(client_id);
+        self.broadcast.clients.lock().expect("Clients are not poisoned")[seat] = Some(connection);
+        if let SeatRequest::Join { .. } = request {
+            self.seats[seat] = SeatStatus::Joined { ready: false };
+            self.send_lobby();
+        }
+    }
+
+    /// Hands the move of a client to the player of its seat, if it came with the token of the seat
+    /// while the seat is on turn. A move out of turn would otherwise be taken for the next turn.
+    fn forward_move(&self, game: &Game, seat: usize, token: u64, action: Action) {
+        if self.tokens[seat] != Some(token) {
+            return self.broadcast.reject(seat, game, Rejection::WrongToken);
+        }
+        if !self.started || game.is_over() || game.active_player_index() != seat {
+            return self.broadcast.reject(seat, game, Rejection::NotYourTurn);
+        }
+        if let Some(moves) = &self.moves[seat] {
+            let _ = moves.send(Some(action));
+        }
+    }
+
+    /// Frees the seat in the lobby, or holds it for the client to resume in a running game.
+    fn free_seat(&mut self, seat: usize) {
+        if let Some(connection) = self.broadcast.clients.lock().expect("Clients are not poisoned")[seat].take() {
+            connection.close();
+        }
+        self.client_ids[seat] = None;
+        match self.started {
+            true => self.left_at[seat] = Some(Instant::now()),
+            false => {
+                self.seats[seat] = SeatStatus::Open;
+                self.tokens[seat] = None;
+                self.nicknames[seat] = None;
+                self.send_lobby();
+            }
+        }
+    }
+
+    /// The client did not come back in time, its player passes until it resigned.
+    fn give_up_seat(&mut self, seat: usize) {
+        self.left_at[seat] = None;
+        self.tokens[seat] = None;
+        self.seats[seat] = SeatStatus::Open;
+        if let Some(moves) = &self.moves[seat] {
+            let _ = moves.send(None);
+        }
+    }
+
+    fn send_lobby(&self) {
+        self.broadcast.send(&ServerMessage::Lobby(self.seats.clone()));
+    }
+}
+
+pub(super) fn accept_clients(listener: TcpListener, events: Sender<HostEvent>) {
+    for stream in listener.incoming() {
+        let events = events.clone();
+        // a slow client must not hold up the others
+        thread::spawn(move || {
+            let event = stream.and_then(handshake).unwrap_or_else(|error| HostEvent::Refused(error.to_string()));
+            let _ = events.send(event);
+        });
+    }
+}
+
+/// Exchanges the hellos and fails if the client speaks another protocol version. Returns what the
+/// client asks for, a seat, a replay or the state of the game.
+fn handshake(stream: TcpStream) -> io::Result<HostEvent> {
+    let address = stream.peer_addr()?;
+    // a client that never says anything must not hold the thread
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let (connection, mut incoming) = match net::accept(stream)? {
+        Accepted::Client(connection, incoming) => (connection, incoming),
+        Accepted::Http { connection, path } => return match http::state_path(&path) {
+            Some(game_id) => Ok(HostEvent::StateRequested { connection, game_id }),
+            None => {
+                http::respond_error(connection, "404 Not Found", "only /games/<id>/state is served");
+                Err(io::Error::new(io::ErrorKind::NotFound, format!("the client at {address} asked for {path}")))
+            }
+        }
+    };
+    let ClientMessage::Hello { version } = incoming.receive()? else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("the client at {address} did not say hello")));
+    };
+    // the client needs the version of the host to explain why it is turned away
+    connection.send(&ServerMessage::Hello { version: PROTOCOL_VERSION })?;
+    protocol::check_version(version).map_err(|error| io::Error::new(error.kind(), format!("the client at {address} has an {error}")))?;
+    let request = match incoming.receive()? {
+        ClientMessage::Join { nickname } => SeatRequest::Join { nickname },
+        ClientMessage::Resume { token } => SeatRequest::Resume { token },
+        ClientMessage::FetchReplay { game_id } => return Ok(HostEvent::ReplayRequested { connection, game_id }),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("the client at {address} asked for no seat")))
+    };
+    // the clients of a game may think about their moves for as long as they like
+    incoming.set_read_timeout(None)?;
+    Ok(HostEvent::Connected { connection, incoming, request })
+}
+
+/// Sends the replay and closes the connection, or tells the client why there is none.
+pub(super) fn send_replay(connection: Connection, replay: Result<Replay, String>) {
+    let _ = match replay {
+        Ok(replay) => connection.send(&ServerMessage::Replay(Box::new(replay))),
+        Err(reason) => connection.send(&ServerMessage::Refused(reason))
+    };
+    connection.close();
+}
+
+/// The nickname without control characters that would garble the terminals, cut to
+/// `MAX_NICKNAME_LENGTH`. `None` if nothing is left of it.
+fn sanitize_nickname(nickname: &str) -> Option<String> {
+    let nickname = nickname.chars().filter(|char| !char.is_control()).collect::<String>();
+    let nickname = nickname.trim().chars().take(MAX_NICKNAME_LENGTH).collect::<String>();
+    (!nickname.is_empty()).then_some(nickname)
+}
+
+/// Passes what a client sends on to the host.
+fn forward_messages(mut incoming: Incoming, client_id: u64, seat: usize, events: Sender<HostEvent>) {
+    while let Ok(message) = incoming.receive() {
+        let forwarded = match message {
+            ClientMessage::Pong(heartbeat) => events.send(HostEvent::Pong { client_id, seat, heartbeat, received: Instant::now() }).is_ok(),
+            message => events.send(HostEvent::Message { client_id, seat, message }).is_ok()
+        };
+        if !forwarded {
+            return;
+        }
+    }
+    let _ = events.send(HostEvent::Left { client_id, seat });
+}
+
+/// A player at another machine, seated at the game of the host. Only the moves of the client that
+/// are legal in the host's game are played, the client is told why any other move was rejected and
+/// waited for until it sends a legal one. Once the client is gone, the player passes.
+pub struct RemotePlayer {
+    seat: usize,
+    /// The moves of the client, `None` once it left.
+    moves: Receiver<Option<Action>>,
+    gone: bool,
+    /// Where the rejections go, to the client of the seat only.
+    replies: Broadcast,
+}
+
+impl RemotePlayer {
+    fn next_action(&mut self) -> Option<Action> {
+        if self.gone {
+            return None;
+        }
+        let action = self.moves.recv().ok().flatten();
+        self.gone = action.is_none();
+        action
+    }
+
+    fn reject(&self, game: &Game, rejection: Rejection) {
+        self.replies.reject(self.seat, game, rejection);
+    }
+}
+
+impl Agent for RemotePlayer {
+    fn choose_move(&mut self, game: &Game, player: usize) -> Option<LegalMove> {
+        loop {
+            match self.next_action() {
+                Some(Action::Place(played)) => match legal_move(game, player, &played) {
+                    Ok(legal_move) => return Some(legal_move),
+                    Err(rejection) => self.reject(game, rejection)
+                },
+                Some(Action::Pass) | None => return None,
+                Some(Action::DraftPick(_)) => self.reject(game, Rejection::NotDrafting)
+            }
+        }
+    }
+
+    fn choose_draft_pick(&mut self, game: &Game, _player: usize) -> usize {
+        let pool_size = game.draft().map_or(0, |draft| draft.pool().len());
+        loop {
+            match self.next_action() {
+                Some(Action::DraftPick(pool_index)) if pool_index < pool_size => return pool_index,
+                Some(Action::DraftPick(pool_index)) => self.reject(game, Rejection::NoSuchDraftPick(pool_index)),
+                Some(_) => self.reject(game, Rejection::Illegal(GameError::DraftInProgress)),
+                None => return largest_draft_piece(game)
+            }
+        }
+    }
+}
+
+/// The placement of `played` if it is a legal move of `player` in the game, otherwise why it is not.
+fn legal_move(game: &Game, player: usize, played: &Move) -> Result<LegalMove, Rejection> {
+    if played.player != player {
+        return Err(Rejection::NotYourSeat);
+    }
+    let piece_index = game.active_player_pieces().iter()
+        .position(|piece| piece.id() == played.piece_id)
+        .ok_or(Rejection::NotInRack { piece_id: played.piece_id })?;
+    game.can_place(piece_index, played.orientation, &played.position).map_err(Rejection::Illegal)?;
+    Ok(game.legal_move(piece_index, played.orientation, played.position.clone()).expect("Placeable pieces are legal moves"))
+}
+
+/// Sends the game to every client after every turn, without the moves the other players committed
+/// to in the current round of the simultaneous variant.
+#[derive(Clone)]
+pub struct Broadcast {
+    /// The connection of every seat with a client.
+    clients: Arc<Mutex<Vec<Option<Connection>>>>,
+}
+
+impl Broadcast {
+    fn send(&self, message: &ServerMessage) {
+        for client in self.clients.lock().expect("Clients are not poisoned").iter().flatten() {
+            // a client that is gone passes its turns, the others keep playing
+            let _ = client.send(message);
+        }
+    }
+
+    fn send_to(&self, seat: usize, message: &ServerMessage) {
+        if let Some(client) = &self.clients.lock().expect("Clients are not poisoned")[seat] {
+            let _ = client.send(message);
+        }
+    }
+
+    /// Sends every client the game as its seat may know it.
+    fn sync(&self, game: &Game) {
+        for (seat, client) in self.clients.lock().expect("Clients are not poisoned").iter().enumerate() {
+            if let Some(client) = client {
+                let _ = client.send(&ServerMessage::StateSync(Box::new(game.as_seen_by(seat))));
+            }
+        }
+    }
+
+    /// Tells the client of the seat why its move was not played, and hands it the game of the host
+    /// to undo the move it already made on its own board.
+    fn reject(&self, seat: usize, game: &Game, rejection: Rejection) {
+        self.send_to(seat, &ServerMessage::MoveRejected(rejection));
+        self.send_to(seat, &ServerMessage::StateSync(Box::new(game.as_seen_by(seat))));
+    }
+}
+
+impl GameObserver for Broadcast {
+    fn on_turn_changed(&mut self, game: &Game, _player_index: usize) {
+        self.sync(game);
+    }
+
+    fn on_game_over(&mut self, game: &Game) {
+        self.sync(game);
+        self.send(&ServerMessage::GameOver { scores: (0..game.players().len()).map(|player_index| game.score(player_index)).collect() });
+        self.send(&ServerMessage::Replay(Box::new(game.replay())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use ratatui::style::Color;
+
+    use crate::bot::random::RandomBot;
+    use crate::game::{Player, Players, Position};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+
+    use super::*;
+
+    fn poll_until(host: &mut Host, game: &mut Game, condition: impl Fn(&Host) -> bool) {
+        while !condition(host) {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(game);
+        }
+    }
+
+    fn game() -> Game {
+        game_with(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) })
+    }
+
+    fn game_with(rules: GameRules) -> Game {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        Game::with_seed(rules, players, 1)
+    }
+
+    /// Connects and asks for a seat, the hello of the host is already read.
+    fn connect(address: SocketAddr, request: ClientMessage) -> (Connection, Incoming) {
+        let (connection, mut incoming) = net::split(TcpStream::connect(address).unwrap()).unwrap();
+        connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION }).unwrap();
+        connection.send(&request).unwrap();
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Hello { version: PROTOCOL_VERSION })));
+        (connection, incoming)
+    }
+
+    #[test]
+    fn should_seat_clients_and_only_play_their_legal_moves() {
+        let mut game = game();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut seats = vec![Seat::Human, Seat::Remote];
+        let mut host = Host::start(listener, &mut seats);
+        game.add_observer(host.broadcast());
+        assert_eq!(host.seats(), [SeatStatus::Host, SeatStatus::Open]);
+
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: "Carol".to_string() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] == SeatStatus::Joined { ready: false });
+        let Ok(ServerMessage::SeatAssigned { seat: 1, token, .. }) = incoming.receive() else { panic!("Expected the second seat") };
+        assert!(!host.can_start());
+        connection.send(&ClientMessage::Ready(true)).unwrap();
+        poll_until(&mut host, &mut game, Host::can_start);
+        host.start_game();
+
+        if game.active_player_index() == 0 {
+            let legal_move = game.legal_moves().remove(0);
+            assert!(game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position).unwrap());
+        }
+        let legal_move = game.legal_moves().remove(0);
+        let piece_id = game.active_player_pieces()[legal_move.piece_index].id();
+        // the first piece has to cover a corner
+        for position in [Position { x: 5, y: 5 }, legal_move.position.clone()] {
+            let played = Move { player: 1, piece_id, orientation: legal_move.orientation, position };
+            connection.send(&ClientMessage::MovePlayed { token, action: Action::Place(played) }).unwrap();
+        }
+        let Seat::Agent(mut remote) = seats.remove(1) else { panic!("Open seats have a remote player") };
+        let copy = game.clone();
+        let choosing = thread::spawn(move || remote.choose_move(&copy, 1));
+        // the host hands the moves on while it polls
+        while !choosing.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut game);
+        }
+        assert_eq!(choosing.join().unwrap().unwrap().position, legal_move.position);
+        // the lobby and the turn of the host come first
+        let rejection = loop {
+            if let ServerMessage::MoveRejected(rejection) = incoming.receive().unwrap() {
+                break rejection;
+            }
+        };
+        assert_eq!(rejection, Rejection::Illegal(GameError::NoCornerContact));
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::StateSync(_))), "The client takes its move back");
+    }
+
+    #[test]
+    fn should_keep_the_human_players_at_the_terminal_of_the_host() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut seats = [Seat::Human, Seat::Remote, Seat::Human, Seat::Agent(Box::new(RandomBot::new(1)))];
+        let host = Host::start(listener, &mut seats);
+        assert_eq!(host.seats(), [SeatStatus::Host, SeatStatus::Open, SeatStatus::Host, SeatStatus::Bot]);
+        assert!(matches!(seats, [Seat::Human, Seat::Agent(_), Seat::Human, Seat::Agent(_)]));
+    }
+
+    #[test]
+    fn should_hold_the_seat_of_a_dropped_client() {
+        let mut game = game();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut host = Host::start(listener, &mut [Seat::Human, Seat::Remote]);
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: String::new() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] != SeatStatus::Open);
+        let Ok(ServerMessage::SeatAssigned { token, .. }) = incoming.receive() else { panic!("Expected a seat") };
+        connection.send(&ClientMessage::Ready(true)).unwrap();
+        poll_until(&mut host, &mut game, Host::can_start);
+        host.start_game();
+
+        connection.close();
+        poll_until(&mut host, &mut game, |host| host.left_at[1].is_some());
+        let resuming = thread::spawn(move || {
+            let (_, mut incoming) = connect(address, ClientMessage::Resume { token: token + 1 });
+            matches!(incoming.receive(), Ok(ServerMessage::Refused(_)))
+        });
+        while !resuming.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut game);
+        }
+        assert!(resuming.join().unwrap(), "Only the token of the seat resumes it");
+        let (_, mut incoming) = connect(address, ClientMessage::Resume { token });
+        poll_until(&mut host, &mut game, |host| host.left_at[1].is_none());
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::SeatAssigned { seat: 1, token: resumed, .. }) if resumed == token));
+    }
+
+    #[test]
+    fn should_relay_chat_messages_to_every_client() {
+        let mut game = game();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut host = Host::start(listener, &mut [Seat::Human, Seat::Remote]);
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: String::new() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] != SeatStatus::Open);
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::SeatAssigned { seat: 1, .. })));
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Lobby(_))));
+
+        connection.send(&ClientMessage::Chat("good\x1b[2Jluck".to_string())).unwrap();
+        poll_until(&mut host, &mut game, |host| !host.chat.is_empty());
+        host.send_chat("thanks".to_string());
+        assert_eq!(host.take_chat(), [(1, "good[2Jluck".to_string()), (0, "thanks".to_string())]);
+        assert!(host.take_chat().is_empty());
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Chat { seat: 1, text }) if text == "good[2Jluck"));
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Chat { seat: 0, text }) if text == "thanks"));
+    }
+
+    #[test]
+    fn should_show_the_ping_of_clients_that_answer_the_heartbeats() {
+        let mut game = game();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut host = Host::start(listener, &mut [Seat::Human, Seat::Remote]);
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: String::new() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] != SeatStatus::Open);
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::SeatAssigned { seat: 1, .. })));
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Lobby(_))));
+
+        host.heartbeat.1 -= HEARTBEAT_INTERVAL;
+        host.poll(&mut game);
+        assert_eq!(host.take_links(), Some(vec![Link::Local, Link::Online { ping_millis: None }]));
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Links(_))));
+        let Ok(ServerMessage::Ping(heartbeat)) = incoming.receive() else { panic!("Expected a heartbeat") };
+        connection.send(&ClientMessage::Pong(heartbeat)).unwrap();
+        poll_until(&mut host, &mut game, |host| host.pings[1].is_some());
+        host.heartbeat.1 -= HEARTBEAT_INTERVAL;
+        host.poll(&mut game);
+        assert!(matches!(host.take_links().as_deref(), Some([Link::Local, Link::Online { ping_millis: Some(_) }])));
+        assert_eq!(host.take_links(), None, "Links are only taken once per heartbeat");
+    }
+
+    #[test]
+    fn should_rename_the_seat_and_reject_moves_without_its_token_or_turn() {
+        let mut game = game();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut host = Host::start(listener, &mut [Seat::Human, Seat::Remote]);
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: " Carol\x1b the Great ".to_string() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] != SeatStatus::Open);
+        let Ok(ServerMessage::SeatAssigned { token, game: assigned, .. }) = incoming.receive() else { panic!("Expected a seat") };
+        assert_eq!(game.players()[1].name, "Carol the Gr");
+        assert_eq!(assigned.players()[1].name, "Carol the Gr", "The client gets the game with its nickname");
+
+        let played = Move { player: 1, piece_id: 0, orientation: 0, position: Position { x: 0, y: 0 } };
+        connection.send(&ClientMessage::MovePlayed { token: token + 1, action: Action::Place(played.clone()) }).unwrap();
+        // the game has not started yet, so the seat is not on turn
+        connection.send(&ClientMessage::MovePlayed { token, action: Action::Place(played) }).unwrap();
+        let rejected = thread::spawn(move || {
+            let mut rejections = vec![];
+            while rejections.len() < 2 {
+                if let ServerMessage::MoveRejected(rejection) = incoming.receive().unwrap() {
+                    rejections.push(rejection);
+                }
+            }
+            rejections
+        });
+        while !rejected.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            host.poll(&mut game);
+        }
+        assert_eq!(rejected.join().unwrap(), [Rejection::WrongToken, Rejection::NotYourTurn]);
+
+        connection.close();
+        poll_until(&mut host, &mut game, |host| host.seats()[1] == SeatStatus::Open);
+        assert_eq!(game.players()[1].name, "Alice", "The seat is free for the next client");
+    }
+
+    #[test]
+    fn should_hide_the_commitments_of_the_other_seats() {
+        let mut game = game_with(GameRules { num_players: 2, simultaneous: true, ..GameRules::with_board_size(14, 14) });
+        // the player at the terminal of the host commits first
+        if game.active_player_index() != 0 {
+            game.rematch();
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut host = Host::start(listener, &mut [Seat::Human, Seat::Remote]);
+        game.add_observer(host.broadcast());
+        let (connection, mut incoming) = connect(address, ClientMessage::Join { nickname: String::new() });
+        poll_until(&mut host, &mut game, |host| host.seats()[1] != SeatStatus::Open);
+        connection.send(&ClientMessage::Ready(true)).unwrap();
+        poll_until(&mut host, &mut game, Host::can_start);
+        host.start_game();
+
+        let legal_move = game.legal_moves().remove(0);
+        assert!(game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position).unwrap());
+        assert!(game.has_committed(0));
+        let state = loop {
+            if let ServerMessage::StateSync(state) = incoming.receive().unwrap() {
+                break state;
+            }
+        };
+        assert_eq!(state.active_player_index(), 1);
+        assert!(!state.has_committed(0), "The client of the second seat does not see the move of the first");
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::{self, BufRead};
+
+use serde::Serialize;
+
+use crate::game::{Game, PlayerStatus, Position, State};
+use crate::net::Connection;
+
+/// A request that opens with `GET`, either the handshake of a WebSocket or a plain request for
+/// the state of a game.
+pub(super) struct Request {
+    pub path: String,
+    pub websocket_key: Option<String>,
+}
+
+/// What dashboards and overlays get for `GET /games/<id>/state`.
+#[derive(Serialize)]
+struct StateView {
+    width: u16,
+    height: u16,
+    /// The rows of the board from the top, `.` for free cells, `#` for blocked ones and the index
+    /// of the player for covered ones.
+    board: Vec<String>,
+    active_player: usize,
+    moves: usize,
+    over: bool,
+    players: Vec<PlayerView>,
+}
+
+#[derive(Serialize)]
+struct PlayerView {
+    name: String,
+    status: PlayerStatus,
+    score: i32,
+    /// The names of the pieces the player has left.
+    rack: Vec<String>,
+}
+
+/// Whether the other side opens with an HTTP request rather than a message.
+pub(super) fn is_request(reader: &mut impl BufRead) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.starts_with(b"GET "))
+}
+
+/// Reads the request line and the headers, the body of a `GET` is empty.
+pub(super) fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut path = None;
+    let mut websocket_key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed during the request"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        match path {
+            None => path = Some(line.split(' ').nth(1).unwrap_or("/").to_string()),
+            Some(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("Sec-WebSocket-Key") {
+                        websocket_key = Some(value.trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+    Ok(Request { path: path.unwrap_or_default(), websocket_key })
+}
+
+/// The id of the game whose state the path asks for, `/games/<id>/state`.
+pub(super) fn state_path(path: &str) -> Option<u64> {
+    path.strip_prefix("/games/")?.strip_suffix("/state")?.parse().ok()
+}
+
+/// Answers with the state of the game and closes the connection.
+pub(super) fn respond_state(connection: Connection, game: &Game) {
+    match serde_json::to_string(&state_view(game)) {
+        Ok(body) => respond(connection, "200 OK", &body),
+        Err(error) => respond_error(connection, "500 Internal Server Error", &error.to_string())
+    }
+}
+
+/// Answers with the reason in a JSON object and closes the connection.
+pub(super) fn respond_error(connection: Connection, status: &str, reason: &str) {
+    let body = serde_json::json!({ "error": reason }).to_string();
+    respond(connection, status, &body);
+}
+
+fn respond(connection: Connection, status: &str, body: &str) {
+    // pages of other origins, e.g. stream overlays, may read the state, too
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    // a client that is gone does not need an answer
+    let _ = connection.write(response.as_bytes());
+    connection.close();
+}
+
+fn state_view(game: &Game) -> StateView {
+    let board = (0..game.height() as i32)
+        .map(|y| (0..game.width() as i32)
+            .map(|x| match game.board.get_state_on_position(&Position { x, y }) {
+                Ok(State::Occupied { player, .. }) => char::from_digit(player as u32, 10).unwrap_or('?'),
+                Ok(State::Blocked) => '#',
+                _ => '.'
+            })
+            .collect())
+        .collect();
+    let players = game.players().iter()
+        .enumerate()
+        .map(|(player_index, player)| PlayerView {
+            name: player.name.clone(),
+            status: player.status,
+            score: game.score(player_index),
+            rack: player.available_pieces.iter().map(|piece| piece.name().to_string()).collect(),
+        })
+        .collect();
+    StateView {
+        width: game.width(),
+        height: game.height(),
+        board,
+        active_player: game.active_player_index(),
+        moves: game.moves_played(),
+        over: game.is_over(),
+        players,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+
+    #[test]
+    fn should_read_the_path_of_a_state_request() {
+        let request = "GET /games/12/state HTTP/1.1\r\nHost: localhost:7777\r\nAccept: */*\r\n\r\n";
+        let mut reader = BufReader::new(request.as_bytes());
+        assert!(is_request(&mut reader).unwrap());
+        let request = read_request(&mut reader).unwrap();
+        assert_eq!(request.path, "/games/12/state");
+        assert_eq!(request.websocket_key, None);
+        assert_eq!(state_path(&request.path), Some(12));
+        assert_eq!(state_path("/games/12"), None);
+        assert_eq!(state_path("/games/twelve/state"), None);
+    }
+}
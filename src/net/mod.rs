@@ -0,0 +1,149 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub mod client;
+pub mod discovery;
+pub mod host;
+mod http;
+pub mod protocol;
+pub mod server;
+mod websocket;
+
+/// The largest message that is read, larger ones end the connection.
+const MAX_MESSAGE_LENGTH: u64 = 16 * 1024 * 1024;
+
+/// The sending half of a connection. Every message is one line of JSON, or one WebSocket message
+/// of JSON for browsers. Clones share the stream, a message is written in one piece even if
+/// several threads send at once.
+#[derive(Clone)]
+pub struct Connection {
+    stream: Arc<Mutex<TcpStream>>,
+    transport: Transport,
+}
+
+/// The receiving half of a connection.
+pub struct Incoming {
+    reader: BufReader<TcpStream>,
+    transport: Transport,
+}
+
+/// How the messages are framed on the stream. The messages are the same on every transport.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Transport {
+    Lines,
+    /// The host side of a WebSocket, e.g. to a page in a browser.
+    WebSocket,
+}
+
+/// Splits the stream into its sending and receiving half.
+pub fn split(stream: TcpStream) -> io::Result<(Connection, Incoming)> {
+    // moves are small and should not wait for more data to fill a packet
+    stream.set_nodelay(true)?;
+    let reader = BufReader::new(stream.try_clone()?);
+    Ok((Connection { stream: Arc::new(Mutex::new(stream)), transport: Transport::Lines }, Incoming { reader, transport: Transport::Lines }))
+}
+
+/// What the host accepted on its port.
+pub enum Accepted {
+    /// A client of the game. Browsers open the connection with a WebSocket handshake, which is
+    /// answered before the first message, other clients send lines right away.
+    Client(Connection, Incoming),
+    /// A plain HTTP request for the path, the connection is closed once it is answered.
+    Http { connection: Connection, path: String },
+}
+
+/// Splits a stream the host accepted, or reads the HTTP request it opens with.
+pub fn accept(stream: TcpStream) -> io::Result<Accepted> {
+    let (mut connection, mut incoming) = split(stream)?;
+    if http::is_request(&mut incoming.reader)? {
+        let request = http::read_request(&mut incoming.reader)?;
+        let Some(key) = request.websocket_key else {
+            return Ok(Accepted::Http { connection, path: request.path });
+        };
+        connection.write(websocket::handshake_response(&key).as_bytes())?;
+        connection.transport = Transport::WebSocket;
+        incoming.transport = Transport::WebSocket;
+    }
+    Ok(Accepted::Client(connection, incoming))
+}
+
+impl Connection {
+    pub fn send(&self, message: &impl Serialize) -> io::Result<()> {
+        let mut message = serde_json::to_string(message)?;
+        match self.transport {
+            Transport::Lines => {
+                message.push('\n');
+                self.write(message.as_bytes())
+            }
+            Transport::WebSocket => self.write(&websocket::text_frame(&message))
+        }
+    }
+
+    fn write(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut stream = self.stream.lock().expect("Senders do not panic while holding the stream");
+        stream.write_all(bytes)?;
+        stream.flush()
+    }
+
+    /// Sends over the stream of `other` from now on, also through the clones of this connection.
+    pub fn replace(&self, other: &Connection) -> io::Result<()> {
+        let stream = other.stream.lock().expect("Senders do not panic while holding the stream").try_clone()?;
+        *self.stream.lock().expect("Senders do not panic while holding the stream") = stream;
+        Ok(())
+    }
+
+    /// Closes both directions, the receiving half of the other side and of this side sees the end
+    /// of the stream.
+    pub fn close(&self) {
+        // the other side may have closed the connection already
+        if self.transport == Transport::WebSocket {
+            let _ = self.write(&websocket::CLOSE_FRAME);
+        }
+        let _ = self.stream.lock().expect("Senders do not panic while holding the stream").shutdown(Shutdown::Both);
+    }
+}
+
+impl Incoming {
+    /// Blocks until the next message arrives. Fails once the other side is gone.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        if self.transport == Transport::WebSocket {
+            return Ok(serde_json::from_slice(&websocket::read_message(&mut self.reader)?)?);
+        }
+        Ok(serde_json::from_str(&read_line(&mut self.reader, MAX_MESSAGE_LENGTH)?)?)
+    }
+
+    /// How long `receive` waits for the next message before it fails, `None` waits forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.reader.get_ref().set_read_timeout(timeout)
+    }
+}
+
+/// Reads the next line, without reading more than `limit` bytes of a line that does not end.
+fn read_line(reader: &mut impl BufRead, limit: u64) -> io::Result<String> {
+    let mut line = String::new();
+    let length = reader.take(limit).read_line(&mut line)?;
+    if length == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed"));
+    }
+    if length as u64 == limit && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the message is too long"));
+    }
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_refuse_lines_longer_than_the_limit() {
+        let mut reader = &b"{}\n[1, 2, 3]\n"[..];
+        assert_eq!(read_line(&mut reader, 8).unwrap(), "{}\n");
+        assert_eq!(read_line(&mut reader, 8).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
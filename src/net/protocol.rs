@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Game;
+use crate::game::error::GameError;
+use crate::game::replay::{Move, Replay};
+
+/// Version of the messages below. Bump it whenever a message changes, so that a host and a client
+/// of different builds refuse to play instead of misreading each other. `Hello` has to keep its
+/// shape across versions.
+pub const PROTOCOL_VERSION: u32 = 8;
+
+/// How long the host holds the seat of a client that dropped out of a running game. The client
+/// may resume its seat until then, afterwards its player resigns.
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(120);
+
+/// How often the host pings its clients and tells everybody how the seats are connected.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a client may leave the pings unanswered before its seat shows up as lost.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// The longest nickname a client is shown with, longer ones are cut.
+pub const MAX_NICKNAME_LENGTH: usize = 12;
+
+/// The longest chat message the host passes on, longer ones are cut.
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// What a client tells the host. The first message is always `Hello`, followed by `Join`, `Resume`
+/// or `FetchReplay`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Hello { version: u32 },
+    /// Asks for an open seat. The player of the seat is renamed to the nickname, unless it is
+    /// empty.
+    Join { nickname: String },
+    /// Takes back the seat of the token after the connection dropped.
+    Resume { token: u64 },
+    /// Asks for the replay of a finished game instead of a seat, the host answers with `Replay` or
+    /// `Refused` and closes the connection. A server numbers its matches in its log, a host at a
+    /// terminal has just its own game.
+    FetchReplay { game_id: u64 },
+    /// Whether the client is ready to start, while the game waits in the lobby.
+    Ready(bool),
+    /// A move of the client's seat, only accepted while it is the seat's turn and with the token
+    /// the host assigned to the seat.
+    MovePlayed { token: u64, action: Action },
+    /// A chat message for every player, relayed by the host.
+    Chat(String),
+    /// The answer to the `Ping` of the same number, right after it arrived.
+    Pong(u64),
+}
+
+/// What a player does on their turn.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Action {
+    /// Places the piece, or commits to it in the simultaneous variant.
+    Place(Move),
+    Pass,
+    /// The index of the piece in the draft pool.
+    DraftPick(usize),
+}
+
+/// What the host tells its clients. The host answers the `Hello` of a client with its own and
+/// closes the connection if the versions differ.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Hello { version: u32 },
+    /// The seat the client plays and the game so far. The token resumes the seat if the
+    /// connection drops.
+    SeatAssigned { seat: usize, token: u64, game: Box<Game> },
+    /// Who sits where while the game waits in the lobby, sent whenever it changes.
+    Lobby(Vec<SeatStatus>),
+    /// The host closed the lobby, the first turn comes next.
+    GameStarted,
+    /// The host turned the client away, e.g. because every seat is taken, or removed it from the
+    /// lobby. The connection is closed afterwards.
+    Refused(String),
+    /// The game after every turn. The host's game is the authoritative one, clients replace
+    /// theirs with it.
+    StateSync(Box<Game>),
+    /// The last move of the client was not played. The state of the host follows, so that the
+    /// client takes its move back.
+    MoveRejected(Rejection),
+    /// A chat message of the player on the seat, the sender gets its own messages back, too.
+    Chat { seat: usize, text: String },
+    /// A heartbeat, the client answers it with a `Pong` of the number.
+    Ping(u64),
+    /// How every seat is connected to the host, sent with every heartbeat.
+    Links(Vec<Link>),
+    GameOver { scores: Vec<i32> },
+    /// The moves of the game as the host recorded them, sent to every client after `GameOver` and
+    /// to those who fetch it later.
+    Replay(Box<Replay>),
+}
+
+/// Why the host did not play the move of a client.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Rejection {
+    /// The move names another player than the one on the client's seat.
+    NotYourSeat,
+    /// The move did not come with the token of the seat.
+    WrongToken,
+    /// The move came while another player is on turn, or before the game started.
+    NotYourTurn,
+    /// The piece is not among the pieces the player has left.
+    NotInRack { piece_id: usize },
+    /// A piece was picked while there is no draft.
+    NotDrafting,
+    NoSuchDraftPick(usize),
+    /// The engine does not allow the move in the game of the host.
+    Illegal(GameError),
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::NotYourSeat => write!(f, "The move is not one of your seat"),
+            Rejection::WrongToken => write!(f, "The move did not come with the token of your seat"),
+            Rejection::NotYourTurn => write!(f, "It is not your turn"),
+            Rejection::NotInRack { piece_id } => write!(f, "Piece {piece_id} is not among your pieces"),
+            Rejection::NotDrafting => write!(f, "There is no draft in progress"),
+            Rejection::NoSuchDraftPick(pool_index) => write!(f, "There is no piece {pool_index} in the pool"),
+            Rejection::Illegal(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Who takes a seat of a hosted game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SeatStatus {
+    /// A player at the terminal of the host.
+    Host,
+    Bot,
+    /// Waiting for a client.
+    Open,
+    Joined { ready: bool },
+}
+
+/// How a seat is connected to the host.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Link {
+    /// Played at the host, by a player at its terminal or by a bot.
+    Local,
+    /// The round trip of the last heartbeat in milliseconds, `None` until the client answered one.
+    Online { ping_millis: Option<u32> },
+    /// The client did not answer the heartbeats in time or dropped out, or never took the seat.
+    Lost,
+}
+
+/// Fails with a message naming both versions if the other side speaks another version.
+pub fn check_version(version: u32) -> io::Result<()> {
+    if version == PROTOCOL_VERSION {
+        return Ok(());
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+        "incompatible protocol version {version}, this build speaks version {PROTOCOL_VERSION}"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_other_versions() {
+        assert!(check_version(PROTOCOL_VERSION).is_ok());
+        let error = check_version(PROTOCOL_VERSION + 1).unwrap_err();
+        assert_eq!(error.to_string(), format!(
+            "incompatible protocol version {}, this build speaks version {PROTOCOL_VERSION}", PROTOCOL_VERSION + 1
+        ));
+        // a hello of any version can be read
+        let hello = serde_json::from_str::<ClientMessage>(r#"{"Hello":{"version":99}}"#).unwrap();
+        assert!(matches!(hello, ClientMessage::Hello { version: 99 }));
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::{self, Agent, Seat};
+use crate::bot::turn::{self, BotTurn};
+use crate::game::Game;
+use crate::game::replay::Replay;
+use crate::net::host::{self, Host, HostEvent, SeatRequest};
+use crate::net::http;
+use crate::net::protocol::{SeatStatus, ServerMessage};
+
+/// How often the server looks for new clients and finished turns.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// How many replays of finished matches the server keeps at hand, older ones are only read from
+/// the log directory.
+const KEPT_REPLAYS: usize = 100;
+
+/// Creates the game and the seats of the match with the given id.
+type NewMatch = Box<dyn FnMut(u64) -> io::Result<(Game, Vec<Seat>)>>;
+
+/// Serves matches without a terminal UI. Every client that joins takes a seat in the match that is
+/// forming, which starts once all of its clients are ready, and the next client opens a new match.
+/// Clients resume their seat in whichever match holds their token. The matches are hosted like
+/// the game of a `Host`, the server plays the turns of every seat on worker threads.
+pub struct Server {
+    matches: Vec<Match>,
+    next_match_id: u64,
+    new_match: NewMatch,
+    connections: Receiver<HostEvent>,
+    /// Where the finished games and their replays are saved, if anywhere.
+    log_dir: Option<PathBuf>,
+    /// The replays of the last finished matches, with the id of the match.
+    replays: VecDeque<(u64, Replay)>,
+    log: Box<dyn Write>,
+}
+
+/// A game served to clients, from its lobby to its end.
+struct Match {
+    id: u64,
+    game: Game,
+    host: Host,
+    /// The agent of every seat, while it is not thinking about a turn.
+    agents: Vec<Option<Box<dyn Agent>>>,
+    /// The turn of the active seat, and stale turns until their agents come back.
+    turns: Vec<BotTurn>,
+    /// The seats as of the last line in the log.
+    logged_seats: Vec<SeatStatus>,
+}
+
+impl Server {
+    /// Accepts clients on `listener` and opens the first match. Fails if a match has no seat for
+    /// a client, i.e. if there is a bot on every seat.
+    pub fn start(
+        listener: TcpListener,
+        new_match: impl FnMut(u64) -> io::Result<(Game, Vec<Seat>)> + 'static,
+        log_dir: Option<PathBuf>,
+        log: impl Write + 'static,
+    ) -> io::Result<Self> {
+        let (sender, connections) = mpsc::channel();
+        thread::spawn(move || host::accept_clients(listener, sender));
+        let mut server = Server { matches: vec![], next_match_id: 1, new_match: Box::new(new_match), connections, log_dir, replays: VecDeque::new(), log: Box::new(log) };
+        server.open_match()?;
+        Ok(server)
+    }
+
+    /// Serves the matches until a new one cannot be created.
+    pub fn run(mut self) -> io::Result<()> {
+        loop {
+            self.poll()?;
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Seats the clients that connected since the last call and moves every match along.
+    pub fn poll(&mut self) -> io::Result<()> {
+        while let Ok(event) = self.connections.try_recv() {
+            match event {
+                HostEvent::Connected { connection, incoming, request: request @ SeatRequest::Resume { token } } => {
+                    match self.matches.iter_mut().find(|served| served.host.holds_token(token)) {
+                        Some(served) => {
+                            served.host.seat_client(&mut served.game, connection, incoming, request);
+                            log_line(&mut self.log, served.id, "a client resumed its seat");
+                        }
+                        None => {
+                            let _ = connection.send(&ServerMessage::Refused("the seat cannot be resumed".to_string()));
+                            connection.close();
+                        }
+                    }
+                }
+                HostEvent::Connected { connection, incoming, request: request @ SeatRequest::Join { .. } } => {
+                    let served = self.matches.iter_mut().find(|served| served.host.has_open_seat()).expect("A match is always forming");
+                    served.host.seat_client(&mut served.game, connection, incoming, request);
+                }
+                HostEvent::ReplayRequested { connection, game_id } => {
+                    let replay = self.replay(game_id);
+                    host::send_replay(connection, replay);
+                }
+                HostEvent::StateRequested { connection, game_id } => {
+                    match self.matches.iter().find(|served| served.id == game_id) {
+                        Some(served) => http::respond_state(connection, &served.game),
+                        None => http::respond_error(connection, "404 Not Found", &format!("there is no running match {game_id}"))
+                    }
+                }
+                HostEvent::Refused(error) => {
+                    let _ = writeln!(self.log, "refused a client: {error}");
+                }
+                // the messages of seated clients go to the host of their match
+                _ => ()
+            }
+        }
+
+        for served in &mut self.matches {
+            served.step(&mut self.log);
+        }
+        let mut index = 0;
+        while index < self.matches.len() {
+            match self.matches[index].game.is_over() {
+                true => {
+                    let finished = self.matches.remove(index);
+                    self.finish(finished);
+                }
+                false => index += 1
+            }
+        }
+        if !self.matches.iter().any(|served| served.host.has_open_seat()) {
+            self.open_match()?;
+        }
+        Ok(())
+    }
+
+    fn open_match(&mut self) -> io::Result<()> {
+        let id = self.next_match_id;
+        let (mut game, mut seats) = (self.new_match)(id)?;
+        let host = Host::headless(&mut seats);
+        if !host.has_open_seat() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a match needs a seat without a bot"));
+        }
+        self.next_match_id += 1;
+        game.add_observer(host.broadcast());
+        let agents = seats.into_iter().map(Seat::into_agent).collect();
+        let logged_seats = host.seats().to_vec();
+        self.matches.push(Match { id, game, host, agents, turns: vec![], logged_seats });
+        log_line(&mut self.log, id, "waiting for players");
+        Ok(())
+    }
+
+    fn finish(&mut self, finished: Match) {
+        let scores = (0..finished.game.players().len())
+            .map(|player_index| format!("{} {}", finished.game.players()[player_index].name, finished.game.score(player_index)))
+            .collect::<Vec<_>>();
+        let replay = finished.game.replay();
+        log_line(&mut self.log, finished.id, &format!("game over after {} moves: {}", replay.len(), scores.join(", ")));
+        if let Some(log_dir) = &self.log_dir {
+            let path = log_dir.join(format!("match-{}.json", finished.id));
+            if let Err(error) = finished.game.save(&path) {
+                log_line(&mut self.log, finished.id, &format!("could not save the game to {}: {error}", path.display()));
+            }
+            let path = replay_path(log_dir, finished.id);
+            if let Err(error) = replay.save(&path) {
+                log_line(&mut self.log, finished.id, &format!("could not save the replay to {}: {error}", path.display()));
+            }
+        }
+        if self.replays.len() == KEPT_REPLAYS {
+            self.replays.pop_front();
+        }
+        self.replays.push_back((finished.id, replay));
+        // the clients got the end of the game already
+        finished.host.close();
+    }
+
+    /// The replay of the finished match, or why there is none.
+    fn replay(&self, match_id: u64) -> Result<Replay, String> {
+        if let Some((_, replay)) = self.replays.iter().find(|(id, _)| *id == match_id) {
+            return Ok(replay.clone());
+        }
+        if self.matches.iter().any(|served| served.id == match_id) {
+            return Err(format!("match {match_id} is not over yet"));
+        }
+        self.log_dir.as_ref()
+            .and_then(|log_dir| Replay::load(replay_path(log_dir, match_id)).ok())
+            .ok_or_else(|| format!("there is no replay of match {match_id}"))
+    }
+}
+
+impl Match {
+    /// Handles what the clients sent, starts the game once everybody is ready and plays the turns.
+    fn step(&mut self, log: &mut dyn Write) {
+        self.host.poll(&mut self.game);
+        for (seat, (logged, status)) in self.logged_seats.iter().zip(self.host.seats()).enumerate() {
+            if logged != status {
+                log_line(log, self.id, &format!("seat {} {}", seat + 1, describe(status)));
+            }
+        }
+        self.logged_seats = self.host.seats().to_vec();
+        if !self.host.is_started() {
+            if self.host.can_start() {
+                self.host.start_game();
+                // the clocks start fresh once the game begins, like after the lottery at a terminal
+                if let Some(time_control) = self.game.clock().map(|clock| clock.time_control().clone()) {
+                    self.game.start_clock(time_control, Instant::now());
+                }
+                log_line(log, self.id, "started");
+            }
+            return;
+        }
+
+        self.game.tick(Instant::now());
+        for (finished, agent, decision) in turn::take_finished(&mut self.turns) {
+            self.agents[finished.player_index()] = Some(agent);
+            // e.g. the clock passed the turn meanwhile
+            if finished.is_current(&self.game) {
+                bot::apply(decision, &mut self.game);
+            }
+        }
+        if !self.game.is_over() {
+            if let Some(agent) = self.agents[self.game.active_player_index()].take() {
+                self.turns.push(BotTurn::start(agent, &self.game));
+            }
+        }
+    }
+}
+
+fn describe(status: &SeatStatus) -> &'static str {
+    match status {
+        SeatStatus::Host => "is the host",
+        SeatStatus::Bot => "is a bot",
+        SeatStatus::Open => "is open",
+        SeatStatus::Joined { ready: false } => "joined",
+        SeatStatus::Joined { ready: true } => "is ready",
+    }
+}
+
+fn replay_path(log_dir: &Path, match_id: u64) -> PathBuf {
+    log_dir.join(format!("match-{match_id}.replay.json"))
+}
+
+fn log_line(log: &mut dyn Write, match_id: u64, line: &str) {
+    // a log that cannot be written must not stop the matches
+    let _ = writeln!(log, "match {match_id}: {line}");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::{SocketAddr, TcpStream};
+
+    use ratatui::style::Color;
+
+    use crate::game::{Player, Players};
+    use crate::game::piece_set::read_standard_piece_set;
+    use crate::game::rules::GameRules;
+    use crate::net::{self, Connection, Incoming};
+    use crate::net::client::fetch_replay;
+    use crate::net::protocol::{ClientMessage, PROTOCOL_VERSION};
+
+    use super::*;
+
+    fn new_match(match_id: u64) -> io::Result<(Game, Vec<Seat>)> {
+        let pieces = read_standard_piece_set().unwrap();
+        let players = Players::new(vec![
+            Player::new("Bob".to_string(), Color::Green, Color::LightGreen, pieces.clone()),
+            Player::new("Alice".to_string(), Color::Blue, Color::LightBlue, pieces),
+        ]);
+        let game = Game::with_seed(GameRules { num_players: 2, ..GameRules::with_board_size(14, 14) }, players, match_id);
+        Ok((game, vec![Seat::Human, Seat::Human]))
+    }
+
+    fn join(address: SocketAddr) -> (Connection, Incoming, usize) {
+        let (connection, mut incoming) = net::split(TcpStream::connect(address).unwrap()).unwrap();
+        connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION }).unwrap();
+        connection.send(&ClientMessage::Join { nickname: String::new() }).unwrap();
+        assert!(matches!(incoming.receive(), Ok(ServerMessage::Hello { .. })));
+        let Ok(ServerMessage::SeatAssigned { seat, .. }) = incoming.receive() else { panic!("Expected a seat") };
+        (connection, incoming, seat)
+    }
+
+    #[test]
+    fn should_open_a_new_match_once_the_forming_one_is_full() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut server = Server::start(listener, new_match, None, io::sink()).unwrap();
+        let mut clients = vec![];
+        for expected_seat in [0, 1, 0] {
+            let joining = thread::spawn(move || join(address));
+            while !joining.is_finished() {
+                thread::sleep(Duration::from_millis(10));
+                server.poll().unwrap();
+            }
+            let (connection, incoming, seat) = joining.join().unwrap();
+            assert_eq!(seat, expected_seat);
+            connection.send(&ClientMessage::Ready(true)).unwrap();
+            clients.push((connection, incoming));
+        }
+        while !server.matches[0].host.is_started() {
+            thread::sleep(Duration::from_millis(10));
+            server.poll().unwrap();
+        }
+        assert_eq!(server.matches.len(), 2);
+        assert!(!server.matches[1].host.is_started(), "The second match waits for its second player");
+    }
+
+    #[test]
+    fn should_send_the_replays_of_finished_matches() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut server = Server::start(listener, new_match, None, io::sink()).unwrap();
+        let (mut game, _) = new_match(7).unwrap();
+        let legal_move = game.legal_moves().remove(0);
+        game.place_piece(legal_move.piece_index, legal_move.orientation, legal_move.position).unwrap();
+        server.replays.push_back((7, game.replay()));
+
+        let fetching = thread::spawn(move || [7, 1, 8].map(|match_id| fetch_replay(&address, match_id).map_err(|error| error.to_string())));
+        while !fetching.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            server.poll().unwrap();
+        }
+        let [finished, forming, unknown] = fetching.join().unwrap();
+        assert_eq!(finished, Ok(game.replay()));
+        assert_eq!(forming, Err("match 1 is not over yet".to_string()));
+        assert_eq!(unknown, Err("there is no replay of match 8".to_string()));
+    }
+
+    fn get(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn should_serve_the_state_of_running_matches_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut server = Server::start(listener, new_match, None, io::sink()).unwrap();
+        let requesting = thread::spawn(move || [get(address, "/games/1/state"), get(address, "/games/2/state")]);
+        while !requesting.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+            server.poll().unwrap();
+        }
+        let [running, unknown] = requesting.join().unwrap();
+        let (head, body) = running.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        let state = serde_json::from_str::<serde_json::Value>(body).unwrap();
+        assert_eq!(state["board"].as_array().unwrap().len(), 14);
+        assert_eq!(state["board"][0], ".".repeat(14));
+        assert_eq!(state["players"][1]["name"], "Alice");
+        assert_eq!(state["players"][1]["rack"].as_array().unwrap().len(), 21);
+        assert!(unknown.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::{self, Read};
+
+use crate::net::MAX_MESSAGE_LENGTH;
+
+/// Appended to the key of the client to prove that the host speaks WebSocket, see RFC 6455.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+
+/// The frame that closes the connection, without a reason.
+pub(super) const CLOSE_FRAME: [u8; 2] = [0x80 | OPCODE_CLOSE, 0];
+
+/// The response that accepts the handshake with the key of the client.
+pub(super) fn handshake_response(key: &str) -> String {
+    format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )
+}
+
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+/// A text frame with the whole message. Frames of the host are not masked.
+pub(super) fn text_frame(message: &str) -> Vec<u8> {
+    let length = message.len();
+    let mut frame = vec![0x80 | OPCODE_TEXT];
+    match length {
+        0..=125 => frame.push(length as u8),
+        126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        _ => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(message.as_bytes());
+    frame
+}
+
+/// Reads frames until a message is complete. Control frames other than close carry no message,
+/// browsers do not ping on their own, so they are skipped rather than answered.
+pub(super) fn read_message(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut message = vec![];
+    loop {
+        let mut header = [0; 2];
+        reader.read_exact(&mut header)?;
+        let last = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let length = match header[1] & 0x7F {
+            126 => {
+                let mut length = [0; 2];
+                reader.read_exact(&mut length)?;
+                u16::from_be_bytes(length) as u64
+            }
+            127 => {
+                let mut length = [0; 8];
+                reader.read_exact(&mut length)?;
+                u64::from_be_bytes(length)
+            }
+            length => length as u64
+        };
+        if message.len() as u64 + length > MAX_MESSAGE_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the WebSocket message is too long"));
+        }
+        let mut mask = [0; 4];
+        if header[1] & 0x80 != 0 {
+            reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0; length as usize];
+        reader.read_exact(&mut payload)?;
+        payload.iter_mut().enumerate().for_each(|(index, byte)| *byte ^= mask[index % 4]);
+        match opcode {
+            OPCODE_CLOSE => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")),
+            OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if last {
+                    return Ok(message);
+                }
+            }
+            _ => ()
+        }
+    }
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for chunk in padded.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in chunk.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6u32)
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+    let mut digest = [0; 20];
+    for (index, value) in state.iter().enumerate() {
+        digest[index * 4..index * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, byte)| bits | (*byte as u32) << (16 - index * 8));
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => encoded.push(ALPHABET[(bits >> (18 - index * 6)) as usize & 0x3F] as char),
+                false => encoded.push('=')
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::net::http::{is_request, read_request};
+
+    use super::*;
+
+    #[test]
+    fn should_accept_the_handshake_of_a_browser() {
+        let request = "GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        let mut reader = BufReader::new(request.as_bytes());
+        assert!(is_request(&mut reader).unwrap());
+        let key = read_request(&mut reader).unwrap().websocket_key.unwrap();
+        // the example of RFC 6455
+        assert!(handshake_response(&key).contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        assert!(!is_request(&mut BufReader::new(&b"{\"Hello\":{\"version\":3}}\n"[..])).unwrap());
+    }
+
+    #[test]
+    fn should_read_masked_and_fragmented_messages() {
+        // "Hel" masked, a ping in between, and "lo" unmasked
+        let frames = [
+            &[0x01, 0x83, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d][..],
+            &[0x89, 0x00],
+            &[0x80, 0x02, b'l', b'o'],
+        ].concat();
+        assert_eq!(read_message(&mut &frames[..]).unwrap(), b"Hello");
+        assert_eq!(read_message(&mut &CLOSE_FRAME[..]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let long = "x".repeat(300);
+        assert_eq!(read_message(&mut &text_frame(&long)[..]).unwrap(), long.as_bytes());
+    }
+}
@@ -0,0 +1,37 @@
+use std::process::{Command, Stdio};
+
+use crate::game::Game;
+use crate::game::observer::GameObserver;
+
+/// Runs a shell command whenever it becomes a player's turn, e.g. to send a mail or call a
+/// webhook with `curl`. The command learns whose turn it is from the environment variables
+/// `BLOKUS_PLAYER`, `BLOKUS_PLAYER_INDEX` and `BLOKUS_MOVE_NUMBER`.
+pub struct TurnCommand {
+    command: String,
+}
+
+impl TurnCommand {
+    pub fn new(command: String) -> Self {
+        TurnCommand { command }
+    }
+}
+
+impl GameObserver for TurnCommand {
+    fn on_turn_changed(&mut self, game: &Game, player_index: usize) {
+        let spawned = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("BLOKUS_PLAYER", &game.players()[player_index].name)
+            .env("BLOKUS_PLAYER_INDEX", player_index.to_string())
+            .env("BLOKUS_MOVE_NUMBER", (game.moves_played() + 1).to_string())
+            // the output would end up in the middle of the terminal UI
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        // a failing notification must not end the game, and the game does not wait for it
+        if let Ok(mut child) = spawned {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const CHECKSUM_HEADER: &str = "blokus-checksum:";
+
+/// Writes `contents` to `path` so that a crash at any point leaves either the old or the new
+/// file behind. The data is written to a temporary file next to `path`, synced and then renamed
+/// over the destination. The previous version is kept as a backup so a damaged file can still be
+/// repaired on load.
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let temp_path = sibling_path(path, "tmp");
+    {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(format!("{CHECKSUM_HEADER}{:016x}\n", checksum(contents)).as_bytes())?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    if read_verified(path).is_ok() {
+        fs::copy(path, sibling_path(path, "bak"))?;
+    }
+    fs::rename(&temp_path, path)?;
+    if let Some(directory) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        // persists the rename itself, not supported on every platform
+        let _ = File::open(directory).and_then(|directory| directory.sync_all());
+    }
+    Ok(())
+}
+
+/// Reads a file written by [`write_atomic`] and verifies its checksum. If the file is missing,
+/// truncated or otherwise damaged, a complete temporary file from an interrupted write or the
+/// backup of the previous version is used instead and restored to `path`.
+pub fn read_atomic(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let error = match read_verified(path) {
+        Ok(contents) => return Ok(contents),
+        Err(error) => error
+    };
+    for recovery_path in [sibling_path(path, "tmp"), sibling_path(path, "bak")] {
+        if let Ok(contents) = read_verified(&recovery_path) {
+            write_atomic(path, &contents)?;
+            return Ok(contents);
+        }
+    }
+    Err(error)
+}
+
+fn read_verified(path: &Path) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    let corrupted = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {reason}", path.display()));
+
+    let header_end = data.iter().position(|byte| *byte == b'\n').ok_or_else(|| corrupted("missing checksum header"))?;
+    let header = std::str::from_utf8(&data[..header_end]).map_err(|_| corrupted("invalid checksum header"))?;
+    let expected = header.strip_prefix(CHECKSUM_HEADER)
+        .and_then(|checksum| u64::from_str_radix(checksum, 16).ok())
+        .ok_or_else(|| corrupted("invalid checksum header"))?;
+
+    let contents = data[header_end + 1..].to_vec();
+    if checksum(&contents) != expected {
+        return Err(corrupted("checksum mismatch, the file is truncated or damaged"));
+    }
+    Ok(contents)
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
+
+/// 64 bit FNV-1a, good enough to detect truncated or partially written files.
+fn checksum(contents: &[u8]) -> u64 {
+    contents.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_path(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("blokus-persistence-{name}-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        directory.join("file.json")
+    }
+
+    #[test]
+    fn should_write_and_read_file() {
+        let path = test_path("round-trip");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(read_atomic(&path).unwrap(), b"second");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn should_repair_truncated_file_from_backup() {
+        let path = test_path("repair");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() - 2]).unwrap();
+
+        assert_eq!(read_atomic(&path).unwrap(), b"first");
+        assert_eq!(read_verified(&path).unwrap(), b"first");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn should_report_damaged_file_without_backup() {
+        let path = test_path("damaged");
+        fs::write(&path, "blokus-checksum:0000000000000000\ncontent").unwrap();
+
+        assert_eq!(read_atomic(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+pub use crate::bot::{Agent, Seat};
+pub use crate::game::{Game, LegalMove, Player, PlayerKind, Players, PlayerStatus, Position, State};
+pub use crate::game::clock::{TimeControl, TimeoutAction};
+pub use crate::game::error::GameError;
+pub use crate::game::observer::GameObserver;
+pub use crate::game::piece::{Orientation, Piece};
+pub use crate::game::piece_set::read_standard_piece_set;
+pub use crate::game::replay::{Move, Replay};
+pub use crate::game::rules::{GameRules, GameSettings, Scoring, Start};
@@ -1,26 +1,71 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use ratatui::Frame;
-use ratatui::layout::{Corner, Rect};
+use ratatui::layout::{Corner, Margin, Rect};
 use ratatui::prelude::{Color, Line, Span, Style, Stylize};
-use ratatui::widgets::{Block, Borders, Padding, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap};
 
-use crate::game::{Board, Game, Piece, Player, Position};
-use crate::ui::{AppEvent, BLOCK, Cursor, Module, ModuleKind, RenderCanvas, SHADED_BLOCK, UI_OFFSET};
-use crate::ui::scrollbars::VerticalScrollBar;
+use crate::game::{Board, Game, LegalMove, Orientation, Piece, Player, PlayerStatus, Position};
+use crate::bot::analysis::{heatmap, Heatmap, suggestions};
+use crate::game::error::GameError;
+use crate::game::puzzle::SolutionStep;
+use crate::game::stats::Blunder;
+use crate::ui::{AppEvent, BLOCK, BLOCKED_TILE, Cursor, CursorStyle, JUMP_DISTANCE, Module, ModuleKind, MouseInput, Options, RenderCanvas, SHADED_BLOCK, UI_OFFSET};
+use crate::ui::scrollbars::ScrollBar;
 
 pub struct BoardDisplay {
     cursors: [Cursor; 4],
     cursor: Cursor,
     index: usize,
-    vertical_scrollbar: VerticalScrollBar,
-    state: State
+    vertical_scrollbar: ScrollBar,
+    horizontal_scrollbar: ScrollBar,
+    /// Where the board was rendered last, to find the cells that are clicked.
+    area: Rect,
+    state: State,
+    rotation_preview: bool,
+    show_move_numbers: bool,
+    /// Highlights the cells the active player may attach their next piece to.
+    show_anchors: bool,
+    /// Only affects rendering and the direction of the arrow keys, positions on the board are
+    /// unchanged.
+    mirrored: bool,
+    cursor_style: CursorStyle,
+    cursor_blink: bool,
+    /// The solution shown in puzzles, `Some` while it is toggled on, even if there is none.
+    solution: Option<Option<Vec<SolutionStep>>>,
+    /// Whether the heatmap may be shown at all, see `Options::analysis`.
+    analysis: bool,
+    /// The heatmap of the active player while it is toggled on.
+    heatmap: Option<Heatmap>,
+    /// The suggested moves of this turn and the one shown, once a hint was asked for.
+    hints: Option<(Vec<LegalMove>, usize)>,
+    warn_blunders: bool,
+    /// The blunder the selected piece would be at the cursor, while the player is asked to confirm
+    /// the placement.
+    blunder: Option<Blunder>,
+    /// When the last placement was rejected, the selected piece flashes for a moment after that.
+    rejected_at: Option<Instant>
 }
 
+/// How long the selected piece flashes after its placement was rejected.
+const REJECTION_FLASH: Duration = Duration::from_millis(600);
+/// How long the piece is shown and hidden in turns while it flashes.
+const FLASH_STEP: Duration = Duration::from_millis(100);
+
+/// How many suggestions the hint key cycles through.
+const HINT_COUNT: usize = 5;
+
+/// The colors of the heatmap from the least to the most valuable cells.
+const HEATMAP_COLORS: [Color; 5] = [Color::Blue, Color::Cyan, Color::Green, Color::Yellow, Color::Red];
+
+/// Neighbouring pieces of a solution get different colors so that their outlines are visible.
+const SOLUTION_COLORS: [Color; 4] = [Color::Cyan, Color::Magenta, Color::Yellow, Color::LightGreen];
+
 struct IndexedPiece {
     piece: Piece,
     index: usize,
-    rotations: u16
+    orientation: usize
 }
 
 enum State {
@@ -30,7 +75,7 @@ enum State {
 }
 
 impl BoardDisplay {
-    pub fn new(width: u16, height: u16, player_index: usize) -> Self {
+    pub fn new(width: u16, height: u16, player_index: usize, options: &Options) -> Self {
         let cursors = [
             Cursor::simple(Corner::TopLeft, width, height),
             Cursor::simple(Corner::TopRight, width, height),
@@ -42,39 +87,141 @@ impl BoardDisplay {
             cursors,
             cursor,
             index: player_index,
-            vertical_scrollbar: VerticalScrollBar::default(),
-            state: State::Default
+            vertical_scrollbar: ScrollBar::vertical(),
+            horizontal_scrollbar: ScrollBar::horizontal(),
+            area: Rect::default(),
+            state: State::Default,
+            rotation_preview: false,
+            show_move_numbers: false,
+            show_anchors: false,
+            mirrored: options.mirror_board,
+            cursor_style: options.cursor_style,
+            cursor_blink: options.cursor_blink,
+            solution: None,
+            analysis: options.analysis,
+            heatmap: None,
+            hints: None,
+            warn_blunders: options.warn_blunders,
+            blunder: None,
+            rejected_at: None
         }
     }
 
-    pub fn render_cursor(&mut self, lines: &mut [Line<'_>], board: &Board, color_map: &HashMap<usize, (Color, Color)>, player: &Player) {
+    pub fn render_cursor(&mut self, lines: &mut [Line<'_>], game: &Game, color_map: &HashMap<usize, (Color, Color)>) {
+        let player = game.active_player();
         match &self.state {
-            State::PieceSelected(indexed_piece) => self.render_piece_cursor(lines, indexed_piece, board, color_map, player),
+            State::PieceSelected(indexed_piece) => {
+                self.render_piece_cursor(lines, indexed_piece, game, color_map);
+                if self.rotation_preview {
+                    self.render_rotation_preview(lines, indexed_piece, player);
+                }
+            }
             State::Default => self.render_simple_cursor(lines, player),
             _ => ()
         }
     }
 
-    fn render_piece_cursor(&self, lines: &mut [Line<'_>], indexed_piece: &IndexedPiece, board: &Board, color_map: &HashMap<usize, (Color, Color)>, player: &Player) {
-        let piece = &indexed_piece.piece;
-        let cursor_position = &self.cursor.area;
-        for block in piece.blocks() {
-            let line = (cursor_position.y + block.y) as usize;
-            let column = (cursor_position.x + block.x) as usize;
-            let content = match board.get_state_on_position(&Position { x: column as u16, y: line as u16 }).expect("Out of bounds") {
-                crate::game::State::Free => Span::styled(BLOCK, Style::default().fg(player.secondary_color)),
-                crate::game::State::Occupied(player_index) => {
+    /// Whether the piece is hidden at the moment, as part of the flash after a rejected placement.
+    fn is_flashed_out(&self, now: Instant) -> bool {
+        let Some(elapsed) = self.rejected_at.map(|rejected_at| now.saturating_duration_since(rejected_at)) else {
+            return false;
+        };
+        elapsed < REJECTION_FLASH && (elapsed.as_millis() / FLASH_STEP.as_millis()).is_multiple_of(2)
+    }
+
+    /// Draws the selected piece green where it could be placed and red where it could not.
+    fn render_piece_cursor(&self, lines: &mut [Line<'_>], indexed_piece: &IndexedPiece, game: &Game, color_map: &HashMap<usize, (Color, Color)>) {
+        if self.is_flashed_out(Instant::now()) {
+            return;
+        }
+        let cursor_position = self.cursor_position();
+        let legal = game.can_place(indexed_piece.index, indexed_piece.orientation, &cursor_position).is_ok();
+        let ghost_color = if legal { Color::Green } else { Color::Red };
+        for block in indexed_piece.blocks() {
+            let position = &cursor_position + &block;
+            // the cursor keeps the piece on the board
+            let (Some((column, line)), Some(state)) = (position.cell(), game.board.get_state_on_position(&position).ok()) else {
+                continue;
+            };
+            let content = match state {
+                crate::game::State::Free => Span::styled(BLOCK, Style::default().fg(ghost_color)),
+                crate::game::State::Blocked => Span::styled(SHADED_BLOCK, Style::default().fg(ghost_color).bg(Color::DarkGray)),
+                crate::game::State::Occupied { player: player_index, .. } => {
                     let (color, _) = *color_map.get(&player_index).unwrap();
-                    Span::styled(SHADED_BLOCK, Style::default().fg(player.color).bg(color))
+                    Span::styled(SHADED_BLOCK, Style::default().fg(ghost_color).bg(color))
                 }
             };
             lines[line].spans[column] = content;
         }
     }
 
+    /// Shades the cells the piece would cover after rotating it, without touching the cells that
+    /// are already covered by the piece in its current orientation.
+    fn render_rotation_preview(&self, lines: &mut [Line<'_>], indexed_piece: &IndexedPiece, player: &Player) {
+        let (rotated_cursor, rotated_orientation) = self.cursor_after_rotation(indexed_piece);
+        let cursor_position = self.cursor_position();
+        let current_blocks = indexed_piece.blocks()
+            .map(|block| &cursor_position + &block)
+            .collect::<Vec<_>>();
+        let rotated_cursor_position = Position { x: rotated_cursor.area.x as i32, y: rotated_cursor.area.y as i32 };
+        for block in indexed_piece.piece.orientation(rotated_orientation).expect("Orientation out of range").blocks() {
+            let position = &rotated_cursor_position + &block;
+            if current_blocks.contains(&position) {
+                continue;
+            }
+            let Some((column, line)) = position.cell() else {
+                continue;
+            };
+            if let Some(span) = lines.get_mut(line).and_then(|line| line.spans.get_mut(column)) {
+                *span = Span::styled(SHADED_BLOCK, Style::default().fg(player.secondary_color));
+            }
+        }
+    }
+
+    fn render_solution(&self, lines: &mut [Line<'_>], game: &Game) {
+        let Some(Some(solution)) = &self.solution else {
+            return;
+        };
+        for (index, step) in solution.iter().enumerate() {
+            let orientation = game.active_player_pieces()[step.piece_index].orientation(step.orientation).expect("Orientation out of range");
+            for block in orientation.blocks() {
+                let Some((column, line)) = (&step.position + &block).cell() else {
+                    continue;
+                };
+                lines[line].spans[column] = Span::styled(SHADED_BLOCK, Style::default().fg(SOLUTION_COLORS[index % SOLUTION_COLORS.len()]));
+            }
+        }
+    }
+
+    /// Colors the free cells by their value, the cells without a legal move stay as they are.
+    fn render_heatmap(&self, lines: &mut [Line<'_>]) {
+        let Some((heatmap, (lowest, highest))) = self.heatmap.as_ref().and_then(|heatmap| Some((heatmap, heatmap.range()?))) else {
+            return;
+        };
+        for (y, line) in lines.iter_mut().enumerate() {
+            for (x, span) in line.spans.iter_mut().enumerate() {
+                let Some(value) = heatmap.value(&Position { x: x as i32, y: y as i32 }) else {
+                    continue;
+                };
+                let grade = (value - lowest) as usize * (HEATMAP_COLORS.len() - 1) / (highest - lowest).max(1) as usize;
+                *span = Span::styled(SHADED_BLOCK, Style::default().fg(HEATMAP_COLORS[grade]));
+            }
+        }
+    }
+
+    fn cursor_position(&self) -> Position {
+        Position { x: self.cursor.area.x as i32, y: self.cursor.area.y as i32 }
+    }
+
     fn render_simple_cursor(&mut self, lines: &mut [Line<'_>], player: &Player) {
         let cursor_position = &self.cursor.area;
-        lines[cursor_position.y as usize].spans[cursor_position.x as usize] = Span::styled(BLOCK, Style::default().fg(player.color));
+        let span = &mut lines[cursor_position.y as usize].spans[cursor_position.x as usize];
+        let cursor = match self.cursor_style {
+            CursorStyle::Solid => Span::styled(BLOCK, Style::default().fg(player.color)),
+            CursorStyle::Inverse => span.clone().reversed(),
+            CursorStyle::Brackets => Span::styled("[]", Style::default().fg(player.color).bold())
+        };
+        *span = if self.cursor_blink { cursor.slow_blink() } else { cursor };
     }
 
     fn select_piece(&mut self, index: usize, game: &Game) {
@@ -82,9 +229,31 @@ impl BoardDisplay {
 
         self.cursor.area.width = piece.num_columns();
         self.cursor.area.height = piece.num_lines();
-        self.cursor.area.x = self.cursor.area.x.clamp(0, game.width() - piece.num_columns());
-        self.cursor.area.y = self.cursor.area.y.clamp(0, game.height() - piece.num_lines());
-        self.state = State::PieceSelected(IndexedPiece { piece, index, rotations: 0 });
+        self.cursor.area.x = self.cursor.area.x.clamp(0, game.width().saturating_sub(piece.num_columns()));
+        self.cursor.area.y = self.cursor.area.y.clamp(0, game.height().saturating_sub(piece.num_lines()));
+        self.state = State::PieceSelected(IndexedPiece { piece, index, orientation: 0 });
+    }
+
+    /// Shows the next suggested move as the selected piece, which can be placed with Enter or
+    /// moved like any other piece. The suggestions start over after the last one.
+    fn show_next_hint(&mut self, game: &Game) {
+        let (moves, shown) = match self.hints.take() {
+            Some((moves, shown)) => {
+                let next = (shown + 1) % moves.len().max(1);
+                (moves, next)
+            }
+            None => (suggestions(game, HINT_COUNT), 0)
+        };
+        if let Some(hint) = moves.get(shown) {
+            self.select_piece(hint.piece_index, game);
+            let orientation = game.active_player_pieces()[hint.piece_index].orientation(hint.orientation).expect("Orientation out of range");
+            // legal moves are on the board and blocks start at zero, so the position is not negative
+            self.cursor.area = Rect::new(hint.position.x as u16, hint.position.y as u16, orientation.num_columns(), orientation.num_lines());
+            if let State::PieceSelected(indexed_piece) = &mut self.state {
+                indexed_piece.orientation = hint.orientation;
+            }
+        }
+        self.hints = Some((moves, shown));
     }
 
     /// As pieces are centered in a rectangular bounding box, the blocks that belong to a piece
@@ -93,62 +262,189 @@ impl BoardDisplay {
     /// offset, then the piece is rotated, and finally the cursor is moved back according to the
     /// new offset.
     fn rotate_piece(&mut self) {
-        if let State::PieceSelected(indexed_piece) = &mut self.state {
-            // unapply the cursor offset
-            self.cursor.move_cursor(-(indexed_piece.piece.bounding_box_offset.x as i32), -(indexed_piece.piece.bounding_box_offset.y as i32));
+        if let State::PieceSelected(indexed_piece) = &self.state {
+            let (cursor, orientation) = self.cursor_after_rotation(indexed_piece);
+            self.cursor = cursor;
+            if let State::PieceSelected(indexed_piece) = &mut self.state {
+                indexed_piece.orientation = orientation;
+            }
+        }
+    }
 
-            indexed_piece.rotate();
-            // swap the width and height
-            self.cursor.rotate_cursor();
-            // reapply the cursor offset with the rotated piece
-            self.cursor.move_cursor(indexed_piece.piece.bounding_box_offset.x as i32, indexed_piece.piece.bounding_box_offset.y as i32);
+    /// Mirrors the selected piece. The bounding box keeps its size, only the offset of the blocks
+    /// inside it changes, which the cursor counteracts as in `rotate_piece`.
+    fn flip_piece(&mut self) {
+        if let State::PieceSelected(indexed_piece) = &mut self.state {
+            let offset = &indexed_piece.current().bounding_box_offset;
+            self.cursor.move_cursor(-offset.x, -offset.y);
+            indexed_piece.orientation = indexed_piece.current().flipped();
+            let offset = &indexed_piece.current().bounding_box_offset;
+            self.cursor.move_cursor(offset.x, offset.y);
         }
     }
 
-    fn place_piece(&mut self, game: &mut Game) -> Option<AppEvent> {
+    fn cursor_after_rotation(&self, indexed_piece: &IndexedPiece) -> (Cursor, usize) {
+        let mut cursor = self.cursor.clone();
+        // unapply the cursor offset
+        let offset = &indexed_piece.current().bounding_box_offset;
+        cursor.move_cursor(-offset.x, -offset.y);
+
+        let rotated_orientation = indexed_piece.current().rotated();
+        // swap the width and height
+        cursor.rotate_cursor();
+        // reapply the cursor offset with the rotated piece
+        let offset = &indexed_piece.piece.orientation(rotated_orientation).expect("Orientation out of range").bounding_box_offset;
+        cursor.move_cursor(offset.x, offset.y);
+        (cursor, rotated_orientation)
+    }
+
+    /// Places the selected piece at the cursor. Unless `confirmed`, a placement that looks like a
+    /// blunder is held back until the player confirms it.
+    fn place_piece(&mut self, game: &mut Game, confirmed: bool) -> Option<AppEvent> {
+        self.blunder = None;
         match &self.state {
-            State::PieceSelected(indexed_piece) => if game.place_piece(indexed_piece.index, indexed_piece.rotations, Position { x: self.cursor.area.x, y: self.cursor.area.y }).expect("Out of bounds") {
-                self.state = State::Default;
-                Some(AppEvent::PiecePlaced)
-            } else {
-                None
-                // render failure animation
+            State::PieceSelected(indexed_piece) if self.warn_blunders && !confirmed => {
+                self.blunder = game.legal_move(indexed_piece.index, indexed_piece.orientation, self.cursor_position())
+                    .and_then(|candidate| game.blunder(&candidate));
+                match self.blunder {
+                    Some(_) => None,
+                    None => self.place_piece(game, true)
+                }
+            }
+            State::PieceSelected(indexed_piece) => match game.place_piece(indexed_piece.index, indexed_piece.orientation, self.cursor_position()) {
+                Ok(true) => {
+                    self.state = State::Default;
+                    Some(AppEvent::PiecePlaced)
+                }
+                _ => {
+                    let error = game.can_place(indexed_piece.index, indexed_piece.orientation, &self.cursor_position()).err();
+                    self.rejected_at = Some(Instant::now());
+                    error.map(|error| AppEvent::Status(rejection_message(&error)))
+                }
             }
             _ => None
         }
     }
 
-    fn is_enabled(&self) -> bool {
-        match self.state {
-            State::Disabled => false,
-            _ => true
+    /// The cursor where it is shown, which is on the other side of the board when it is mirrored.
+    fn shown_cursor(&self) -> Cursor {
+        let mut cursor = self.cursor.clone();
+        if self.mirrored {
+            cursor.area.x = cursor.max_x.saturating_sub(cursor.area.x + cursor.area.width);
         }
+        cursor
+    }
+
+    /// The column and line of the board shown at a cell of the terminal.
+    fn clicked_cell(&self, position: ratatui::layout::Position, game: &Game) -> Option<(u16, u16)> {
+        let inner = self.area.inner(&Margin::new(1, 1));
+        if !inner.contains(position) {
+            return None;
+        }
+        // every cell of the board is two characters wide
+        let column = (position.x - inner.x) / 2 + self.horizontal_scrollbar.offset();
+        let line = position.y - inner.y + self.vertical_scrollbar.offset();
+        if column >= game.width() || line >= game.height() {
+            return None;
+        }
+        let column = if self.mirrored { game.width() - 1 - column } else { column };
+        Some((column, line))
+    }
+
+    /// Moves the cursor to the clicked cell. Clicking the cell of the cursor again places the
+    /// selected piece there, just like Enter.
+    fn click(&mut self, position: ratatui::layout::Position, game: &mut Game) -> Option<AppEvent> {
+        let (column, line) = self.clicked_cell(position, game)?;
+        // a selected piece is kept on the board with its top left corner as close as possible
+        let x = column.min(self.cursor.max_x.saturating_sub(self.cursor.area.width));
+        let y = line.min(self.cursor.max_y.saturating_sub(self.cursor.area.height));
+        if matches!(self.state, State::PieceSelected(_)) && (x, y) == (self.cursor.area.x, self.cursor.area.y) {
+            return self.place_piece(game, false);
+        }
+        self.cursor.area.x = x;
+        self.cursor.area.y = y;
+        None
+    }
+
+    fn switch_player(&mut self, index: usize) {
+        let original_cursor = &mut self.cursors[self.index];
+        original_cursor.area.x = self.cursor.area.x;
+        original_cursor.area.y = self.cursor.area.y;
+        self.cursor = self.cursors[index].clone();
+        self.index = index;
+    }
+
+    fn is_enabled(&self) -> bool {
+        !matches!(self.state, State::Disabled)
     }
 }
 
 impl Module for BoardDisplay {
     fn update(&mut self, event: AppEvent, game: &mut Game) -> Option<AppEvent> {
-        if let AppEvent::PiecePlaced = event {
-            let index = game.active_player_index();
-            let original_cursor = &mut self.cursors[self.index];
-            original_cursor.area.x = self.cursor.area.x;
-            original_cursor.area.y = self.cursor.area.y;
-            self.cursor = self.cursors[index].clone();
-            self.index = index;
-        }
-        if !self.is_enabled() {
-            if let AppEvent::PieceSelected(piece_index) = event {
-                self.select_piece(piece_index, game);
-            }
-        } else {
+        match event {
+            AppEvent::PreviewRotation => self.rotation_preview = true,
+            AppEvent::None => (),
+            _ => self.rotation_preview = false
+        }
+        if event.is_move_input() || matches!(event, AppEvent::Decline | AppEvent::TurnPassed) {
+            self.blunder = None;
+            self.rejected_at = None;
+        }
+        match event {
+            AppEvent::ToggleMoveNumbers if game.is_over() => self.show_move_numbers = !self.show_move_numbers,
+            AppEvent::ToggleAnchors if !game.is_over() => self.show_anchors = !self.show_anchors,
+            AppEvent::Hint if game.rules().free_placement && !game.is_over() => {
+                self.solution = match self.solution {
+                    Some(_) => None,
+                    None => Some(game.solve_remaining())
+                };
+            }
+            AppEvent::ToggleHeatmap if self.analysis && !game.is_over() => {
+                self.heatmap = match self.heatmap {
+                    Some(_) => None,
+                    None => Some(heatmap(game))
+                };
+            }
+            AppEvent::Hint if self.is_enabled() && !game.is_over() && game.draft().is_none() => self.show_next_hint(game),
+            AppEvent::OpenPieceSelection | AppEvent::PieceSelected(_) => self.hints = None,
+            AppEvent::PiecePlaced => {
+                self.switch_player(game.active_player_index());
+                self.solution = None;
+                self.hints = None;
+                self.heatmap = self.heatmap.take().map(|_| heatmap(game));
+            }
+            AppEvent::TurnPassed => {
+                self.switch_player(game.active_player_index());
+                self.state = State::Default;
+                self.solution = None;
+                self.hints = None;
+                self.heatmap = self.heatmap.take().map(|_| heatmap(game));
+            }
+            _ => ()
+        }
+        // pieces clicked in the piece panel are selected without opening it first
+        if let AppEvent::PieceSelected(piece_index) = event {
+            self.select_piece(piece_index, game);
+        } else if self.is_enabled() {
             match event {
                 AppEvent::MoveUp => self.cursor.move_up(1),
                 AppEvent::MoveDown => self.cursor.move_down(1),
+                AppEvent::MoveLeft if self.mirrored => self.cursor.move_right(1),
+                AppEvent::MoveRight if self.mirrored => self.cursor.move_left(1),
                 AppEvent::MoveLeft => self.cursor.move_left(1),
                 AppEvent::MoveRight => self.cursor.move_right(1),
+                AppEvent::JumpUp => self.cursor.move_up(JUMP_DISTANCE),
+                AppEvent::JumpDown => self.cursor.move_down(JUMP_DISTANCE),
+                AppEvent::JumpLeft if self.mirrored => self.cursor.move_right(JUMP_DISTANCE),
+                AppEvent::JumpRight if self.mirrored => self.cursor.move_left(JUMP_DISTANCE),
+                AppEvent::JumpLeft => self.cursor.move_left(JUMP_DISTANCE),
+                AppEvent::JumpRight => self.cursor.move_right(JUMP_DISTANCE),
                 AppEvent::OpenPieceSelection => self.state = State::Disabled,
                 AppEvent::Rotate => self.rotate_piece(),
-                AppEvent::Select => return self.place_piece(game),
+                AppEvent::Flip if game.rules().allow_flips => self.flip_piece(),
+                AppEvent::Select => return self.place_piece(game, false),
+                AppEvent::Confirm if self.blunder.is_some() => return self.place_piece(game, true),
+                AppEvent::Mouse(MouseInput::Click(position)) if !game.is_over() => return self.click(position, game),
                 _ => ()
             }
         }
@@ -163,25 +459,45 @@ impl Module for BoardDisplay {
         let width = display_width.min(area.width);
         let height = display_height.min(area.height);
         let board_render_area = Rect { x: area.x, y: area.y, width, height};
+        self.area = board_render_area;
         self.vertical_scrollbar.update_scrollbar(board_render_area, &self.cursor);
+        self.horizontal_scrollbar.update_scrollbar(board_render_area, &self.shown_cursor());
 
         let board = &game.board;
         let color_map = game.get_color_map();
-        let colored_board = ColoredBoard { board, colors: &color_map };
+        let anchors_of = (self.show_anchors && !game.is_over()).then(|| game.active_player_index());
+        let colored_board = ColoredBoard { board, colors: &color_map, show_move_numbers: self.show_move_numbers, anchors_of };
         let mut lines = colored_board.render();
+        self.render_solution(&mut lines, game);
+        self.render_heatmap(&mut lines);
 
-        if self.is_enabled() {
-            self.render_cursor(&mut lines, board, &color_map, game.active_player());
+        if self.is_enabled() && !game.is_over() {
+            self.render_cursor(&mut lines, game, &color_map);
+        }
+        if self.mirrored {
+            lines.iter_mut().for_each(|line| line.spans.reverse());
         }
 
         let border_color = if self.is_enabled() { Color::default() } else { Color::Gray };
+        let title = match &self.state {
+            _ if game.is_over() && game.rules().free_placement && game.players()[0].status == PlayerStatus::Finished => "Solved - n: numbers, u: pieces".to_string(),
+            _ if game.is_over() => "Game over - n: numbers, u: pieces".to_string(),
+            _ if matches!(self.solution, Some(None)) => "Board - no solution from here".to_string(),
+            _ if self.hints.as_ref().is_some_and(|(moves, _)| moves.is_empty()) => "Board - no move left".to_string(),
+            State::PieceSelected(indexed_piece) => match &self.hints {
+                Some((moves, shown)) => format!("Board - hint {}/{}: {}, ?: next", shown + 1, moves.len(), indexed_piece.piece.name()),
+                None => format!("Board - {}", indexed_piece.piece.name())
+            }
+            _ if self.heatmap.is_some() => "Board - heatmap, a: hide".to_string(),
+            _ => "Board".to_string()
+        };
 
         frame.render_widget(
             Paragraph::new(lines)
                 .not_underlined()
-                .scroll((self.vertical_scrollbar.offset(), 0))
+                .scroll((self.vertical_scrollbar.offset(), self.horizontal_scrollbar.offset() * 2))
                 .block(Block::default()
-                    .title("Board")
+                    .title(title)
                     .borders(Borders::ALL)
                     .style(Style::default().fg(border_color))
                     .padding(Padding::zero())
@@ -190,6 +506,10 @@ impl Module for BoardDisplay {
         );
 
         self.vertical_scrollbar.render_scrollbar(frame, display_height, board_render_area);
+        self.horizontal_scrollbar.render_scrollbar(frame, display_width, board_render_area);
+        if let Some(blunder) = self.blunder {
+            render_blunder_warning(frame, board_render_area, blunder);
+        }
     }
 
     fn kind(&self) -> ModuleKind {
@@ -197,22 +517,64 @@ impl Module for BoardDisplay {
     }
 }
 
+/// Why the piece cannot be placed, in the words of the rules rather than those of the engine.
+fn rejection_message(error: &GameError) -> &'static str {
+    match error {
+        GameError::Overlap => "The piece covers another piece.",
+        GameError::EdgeContact => "Pieces of your color may only touch at their corners.",
+        GameError::NoCornerContact => "The piece has to touch a corner of your pieces, or a start square.",
+        GameError::FlipsNotAllowed => "Pieces may not be mirrored in this game.",
+        GameError::DraftInProgress => "Pieces are placed once the draft is over.",
+        GameError::OutOfBounds(_) => "The piece does not fit on the board there.",
+        GameError::NoSuchPiece(_) | GameError::NoSuchOrientation { .. } => "That piece is no longer available.",
+    }
+}
+
+fn render_blunder_warning(frame: &mut Frame, area: Rect, blunder: Blunder) {
+    let warning = match blunder {
+        Blunder::NoMovesLeft => "This leaves you without a legal move for your other pieces.".to_string(),
+        Blunder::AnchorsLost { before, after } => format!("This leaves you with {after} of your {before} corners to attach to."),
+    };
+    let lines = vec![Line::from(warning), Line::from(""), Line::from("y: place anyway, Esc: cancel")];
+    let width = 36.min(area.width);
+    let height = 7.min(area.height);
+    let popup = Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(Block::default().title("Blunder?").borders(Borders::ALL)), popup);
+}
+
 struct ColoredBoard<'a> {
     board: &'a Board,
-    colors: &'a HashMap<usize, (Color, Color)>
+    colors: &'a HashMap<usize, (Color, Color)>,
+    /// Labels every placed piece with the number of the move it was placed in.
+    show_move_numbers: bool,
+    /// Highlights the free cells the given player may attach their next piece to.
+    anchors_of: Option<usize>
 }
 
 impl <'a> RenderCanvas for ColoredBoard<'a> {
     fn render(&self) -> Vec<Line<'_>> {
         let mut lines: Vec<Line<'_>> = vec![];
+        let anchors = self.anchors_of.map(|player_index| (self.board.anchors(player_index), self.colors.get(&player_index).unwrap().1));
         for y in 0..self.board.height {
             let mut line = vec![];
             for x in 0..self.board.width {
-                let color = match self.board.get_state_on_position(&Position { x, y }).unwrap() {
-                    crate::game::State::Free => Color::Gray,
-                    crate::game::State::Occupied(player_id) => self.colors.get(&player_id).unwrap().0
+                let position = Position { x: x as i32, y: y as i32 };
+                let span = match self.board.get_state_on_position(&position).unwrap() {
+                    crate::game::State::Free => match anchors {
+                        Some((anchors, color)) if anchors.contains(&position) => Span::styled(BLOCK, Style::default().fg(color)),
+                        _ => Span::styled(BLOCK, Style::default().fg(Color::Gray))
+                    },
+                    crate::game::State::Blocked => Span::styled(BLOCKED_TILE, Style::default().fg(Color::DarkGray)),
+                    crate::game::State::Occupied { player: player_id, move_number, .. } if self.show_move_numbers => {
+                        let color = self.colors.get(&player_id).unwrap().0;
+                        Span::styled(format!("{:>2}", move_number % 100), Style::default().fg(Color::Black).bg(color))
+                    }
+                    crate::game::State::Occupied { player: player_id, .. } => {
+                        Span::styled(BLOCK, Style::default().fg(self.colors.get(&player_id).unwrap().0))
+                    }
                 };
-                line.push(Span::styled(BLOCK, Style::default().fg(color)))
+                line.push(span)
             }
             lines.push(line.into());
         }
@@ -221,8 +583,12 @@ impl <'a> RenderCanvas for ColoredBoard<'a> {
 }
 
 impl IndexedPiece {
-    fn rotate(&mut self) {
-        self.rotations = (self.rotations + 1) % 4;
-        self.piece.rotate();
+    fn current(&self) -> &Orientation {
+        self.piece.orientation(self.orientation).expect("Orientation out of range")
     }
+
+    fn blocks(&self) -> impl Iterator<Item=Position> + '_ {
+        self.current().blocks()
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::sync::mpsc::{Receiver, Sender};
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::Line;
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+use crate::game::Game;
+use crate::ui::{AppEvent, ChatInput, Module, ModuleKind};
+
+/// The messages of a network game and the line being typed. While the chat has the input focus,
+/// every key goes into the line, see `Focus`.
+pub struct ChatDisplay {
+    /// The seat and text of every message so far.
+    messages: Vec<(usize, String)>,
+    input: String,
+    focused: bool,
+    /// How many messages the history is scrolled up from the latest one.
+    scroll: usize,
+    received: Receiver<(usize, String)>,
+    outgoing: Sender<String>,
+}
+
+impl ChatDisplay {
+    /// A chat showing the messages from `received` that sends the typed lines to `outgoing`.
+    pub fn new(received: Receiver<(usize, String)>, outgoing: Sender<String>) -> Self {
+        ChatDisplay { messages: vec![], input: String::new(), focused: false, scroll: 0, received, outgoing }
+    }
+}
+
+impl Module for ChatDisplay {
+    fn update(&mut self, event: AppEvent, _game: &mut Game) -> Option<AppEvent> {
+        let AppEvent::Chat(input) = event else {
+            return None;
+        };
+        match input {
+            ChatInput::Open => self.focused = true,
+            ChatInput::Leave => self.focused = false,
+            ChatInput::Char(char) => self.input.push(char),
+            ChatInput::Backspace => {
+                self.input.pop();
+            }
+            ChatInput::Send if !self.input.trim().is_empty() => {
+                // the message shows up once the host relayed it
+                let _ = self.outgoing.send(std::mem::take(&mut self.input));
+                self.scroll = 0;
+            }
+            ChatInput::Send => (),
+            ChatInput::ScrollUp => self.scroll = (self.scroll + 1).min(self.messages.len().saturating_sub(1)),
+            ChatInput::ScrollDown => self.scroll = self.scroll.saturating_sub(1)
+        }
+        None
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, game: &mut Game) {
+        self.messages.extend(self.received.try_iter());
+        let visible = self.messages.len() - self.scroll.min(self.messages.len());
+        let mut lines = self.messages[..visible].iter()
+            .map(|(seat, text)| {
+                let (name, color) = game.players().get(*seat).map_or(("?", Color::default()), |player| (player.name.as_str(), player.color));
+                Line::from(vec![Span::styled(format!("{name}: "), Style::default().fg(color)), Span::raw(text.as_str())])
+            })
+            .collect::<Vec<_>>();
+        if self.focused {
+            lines.push(Line::from(format!("> {}_", self.input)));
+        }
+        // the latest messages stay in view, older ones scroll out at the top
+        let inner_height = area.height.saturating_sub(2) as usize;
+        let skipped = lines.len().saturating_sub(inner_height);
+        let title = match (self.focused, self.scroll) {
+            (true, _) => "Chat - Enter: send, Esc: leave".to_string(),
+            (false, 0) => "Chat - t: type".to_string(),
+            (false, scroll) => format!("Chat - {scroll} newer"),
+        };
+        frame.render_widget(
+            Paragraph::new(lines.split_off(skipped)).wrap(Wrap { trim: false }).block(Block::default().borders(Borders::ALL).title(title)),
+            area
+        )
+    }
+
+    fn kind(&self) -> ModuleKind {
+        ModuleKind::Chat
+    }
+}
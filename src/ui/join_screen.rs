@@ -0,0 +1,100 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::net::discovery::{self, Announced};
+use crate::net::protocol::PROTOCOL_VERSION;
+use crate::ui::{AppEvent, enter_screen, Focus, Keymap, leave_screen, poll_event};
+
+/// How long one search for games waits for answers, the list is refreshed after every search.
+const BROWSE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// The games found on the local network, the player picks the one to join.
+struct JoinScreen {
+    games: Vec<Announced>,
+    selected: usize,
+    searched: bool,
+    error: Option<String>,
+}
+
+/// Lists the games announced on the local network until the player picks one. Returns the address
+/// of the game, `None` if the player quit.
+pub fn pick_game(keymap: &Keymap) -> io::Result<Option<SocketAddr>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        let games = discovery::browse(BROWSE_TIMEOUT).map_err(|error| error.to_string());
+        if sender.send(games).is_err() {
+            return;
+        }
+    });
+    let mut terminal = enter_screen()?;
+    let mut screen = JoinScreen { games: vec![], selected: 0, searched: false, error: None };
+    let picked = loop {
+        if let Some(result) = receiver.try_iter().last() {
+            screen.searched = true;
+            match result {
+                Ok(games) => {
+                    screen.games = games;
+                    screen.error = None;
+                    screen.selected = screen.selected.min(screen.games.len().saturating_sub(1));
+                }
+                Err(error) => screen.error = Some(error)
+            }
+        }
+        terminal.draw(|frame| screen.render(frame))?;
+        match poll_event(Focus::Board, keymap)? {
+            AppEvent::Quit | AppEvent::Decline => break None,
+            AppEvent::MoveUp => screen.selected = screen.selected.saturating_sub(1),
+            AppEvent::MoveDown => screen.selected = (screen.selected + 1).min(screen.games.len().saturating_sub(1)),
+            AppEvent::Select => {
+                // a host of another version would turn the client away right after the handshake
+                if let Some(game) = screen.games.get(screen.selected).filter(|game| is_compatible(game)) {
+                    break Some(game.address);
+                }
+            }
+            _ => ()
+        }
+    };
+    leave_screen()?;
+    Ok(picked)
+}
+
+fn is_compatible(game: &Announced) -> bool {
+    game.version.is_none_or(|version| version == PROTOCOL_VERSION)
+}
+
+impl JoinScreen {
+    fn render(&self, frame: &mut Frame) {
+        let mut lines = vec![];
+        for (index, game) in self.games.iter().enumerate() {
+            let marker = if index == self.selected { ">" } else { " " };
+            let style = match is_compatible(game) {
+                true => Style::default(),
+                false => Style::default().fg(Color::DarkGray)
+            };
+            let mut spans = vec![Span::styled(format!("{marker} {:<30} {}", game.name, game.address), style)];
+            if let Some(version) = game.version.filter(|_| !is_compatible(game)) {
+                spans.push(Span::styled(format!("  protocol version {version}"), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        if self.games.is_empty() {
+            lines.push(Line::from(match self.searched {
+                true => "No games found yet, still searching...",
+                false => "Searching..."
+            }));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("Enter: join the game, q: quit"));
+        if let Some(error) = &self.error {
+            lines.push(Line::styled(format!("Could not search: {error}"), Style::default().fg(Color::Red)));
+        }
+        let block = Block::default().title("Games on the local network").borders(Borders::ALL);
+        frame.render_widget(Paragraph::new(lines).block(block), frame.size());
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::io;
+use std::mem::discriminant;
+use std::path::Path;
+use std::str::FromStr;
+
+use crossterm::event::KeyCode;
+
+use crate::ui::AppEvent;
+
+/// Which key triggers which action. A keymap file has one action per line, followed by `=` and
+/// the keys that trigger it, separated by commas, e.g. `Rotate = r, Space`. Actions that are not
+/// listed keep their default keys, lines starting with `#` are comments.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: HashMap<KeyCode, AppEvent>,
+    /// The keys of every action in the order they are listed, the first one is shown in hints.
+    keys: Vec<(AppEvent, Vec<KeyCode>)>
+}
+
+/// The actions that can be bound, with the name used in keymap files and their default keys.
+fn actions() -> Vec<(&'static str, AppEvent, Vec<KeyCode>)> {
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    let mut actions = vec![
+        ("Quit", AppEvent::Quit, vec![KeyCode::Char('q')]),
+        ("MoveUp", AppEvent::MoveUp, vec![KeyCode::Up, KeyCode::Char('k')]),
+        ("MoveDown", AppEvent::MoveDown, vec![KeyCode::Down, KeyCode::Char('j')]),
+        ("MoveLeft", AppEvent::MoveLeft, vec![KeyCode::Left, KeyCode::Char('h')]),
+        ("MoveRight", AppEvent::MoveRight, vec![KeyCode::Right, KeyCode::Char('l')]),
+        ("JumpUp", AppEvent::JumpUp, vec![KeyCode::Char('K')]),
+        ("JumpDown", AppEvent::JumpDown, vec![KeyCode::Char('J')]),
+        ("JumpLeft", AppEvent::JumpLeft, vec![KeyCode::Char('H')]),
+        ("JumpRight", AppEvent::JumpRight, vec![KeyCode::Char('L')]),
+        ("PageUp", AppEvent::PageUp, vec![KeyCode::PageUp]),
+        ("PageDown", AppEvent::PageDown, vec![KeyCode::PageDown]),
+        ("Home", AppEvent::Home, vec![KeyCode::Home]),
+        ("End", AppEvent::End, vec![KeyCode::End]),
+        ("OpenPieceSelection", AppEvent::OpenPieceSelection, vec![KeyCode::Char('i')]),
+        ("CyclePieceOrder", AppEvent::CyclePieceOrder, vec![KeyCode::Char('O')]),
+        ("Select", AppEvent::Select, vec![KeyCode::Enter]),
+        ("Rotate", AppEvent::Rotate, vec![KeyCode::Char('c')]),
+        ("Flip", AppEvent::Flip, vec![KeyCode::Char('f')]),
+        ("PreviewRotation", AppEvent::PreviewRotation, vec![KeyCode::Char('C')]),
+        ("ToggleDebugOverlay", AppEvent::ToggleDebugOverlay, vec![KeyCode::F(12)]),
+        ("ToggleMoveNumbers", AppEvent::ToggleMoveNumbers, vec![KeyCode::Char('n')]),
+        ("Pass", AppEvent::Pass, vec![KeyCode::Char('p')]),
+        ("ToggleUnplacedPieces", AppEvent::ToggleUnplacedPieces, vec![KeyCode::Char('u')]),
+        // h moves the cursor like in vim
+        ("Hint", AppEvent::Hint, vec![KeyCode::Char('?')]),
+        ("ToggleHeatmap", AppEvent::ToggleHeatmap, vec![KeyCode::Char('a')]),
+        ("ToggleAnchors", AppEvent::ToggleAnchors, vec![KeyCode::Char('o')]),
+        ("RequestTakeback", AppEvent::RequestTakeback, vec![KeyCode::Char('t')]),
+        ("Confirm", AppEvent::Confirm, vec![KeyCode::Char('y')]),
+        ("Decline", AppEvent::Decline, vec![KeyCode::Esc]),
+    ];
+    #[cfg(feature = "serde")]
+    actions.extend([
+        ("Save", AppEvent::Save, vec![KeyCode::Char('s')]),
+        ("Kick", AppEvent::Kick, vec![KeyCode::Char('x')]),
+    ]);
+    actions
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::bind(actions()).expect("The default keys do not conflict")
+    }
+}
+
+impl Keymap {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)?
+            .parse()
+            .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {reason}", path.display())))
+    }
+
+    /// What the key does, keys without a binding only dismiss overlays.
+    pub(crate) fn event(&self, key: KeyCode) -> AppEvent {
+        self.bindings.get(&key).copied().unwrap_or(AppEvent::OtherKey)
+    }
+
+    /// The first key of the action, as it is shown to the player. `None` if it has no key.
+    pub(crate) fn key_for(&self, event: AppEvent) -> Option<String> {
+        let (_, keys) = self.keys.iter().find(|(action, _)| discriminant(action) == discriminant(&event))?;
+        keys.first().map(|key| match key {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(char) => char.to_string(),
+            key => key_name(*key)
+        })
+    }
+
+    /// The keys that label the pieces in the piece panel: the digits, then the letters that do
+    /// not trigger an action, so that selecting a piece by its label never takes away a key.
+    pub(crate) fn quick_keys(&self) -> Vec<char> {
+        ('1'..='9').chain(['0']).chain('a'..='z').chain('A'..='Z')
+            .filter(|char| !self.bindings.contains_key(&KeyCode::Char(*char)))
+            .collect()
+    }
+
+    /// Fails if a key would trigger two actions, or if there is no key left to quit with.
+    fn bind(actions: Vec<(&'static str, AppEvent, Vec<KeyCode>)>) -> Result<Self, String> {
+        let mut bindings = HashMap::new();
+        let mut names = HashMap::new();
+        let mut action_keys = vec![];
+        for (name, event, keys) in actions {
+            if matches!(event, AppEvent::Quit) && keys.is_empty() {
+                return Err("Quit needs a key".to_string());
+            }
+            for key in &keys {
+                if let Some(other) = names.insert(*key, name) {
+                    return Err(format!("{} is bound to both {other} and {name}", key_name(*key)));
+                }
+                bindings.insert(*key, event);
+            }
+            action_keys.push((event, keys));
+        }
+        Ok(Keymap { bindings, keys: action_keys })
+    }
+}
+
+impl FromStr for Keymap {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut actions = actions();
+        for (number, line) in string.lines().enumerate().map(|(index, line)| (index + 1, line.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, keys) = line.split_once('=').ok_or_else(|| format!("line {number}: expected 'Action = keys'"))?;
+            let (_, _, bound) = actions.iter_mut()
+                .find(|(action, _, _)| action.eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| format!("line {number}: unknown action '{}'", name.trim()))?;
+            // an empty list unbinds the action
+            *bound = keys.split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| parse_key(key).ok_or_else(|| format!("line {number}: unknown key '{key}'")))
+                .collect::<Result<_, _>>()?;
+        }
+        Keymap::bind(actions)
+    }
+}
+
+/// A single character is the key of that character, other keys go by their name, e.g. `Enter`,
+/// `Space` or `F5`.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(char));
+    }
+    let key = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "space" => KeyCode::Char(' '),
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        function => return function.strip_prefix('f')?.parse().ok().map(KeyCode::F)
+    };
+    Some(key)
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(char) => format!("'{char}'"),
+        KeyCode::F(number) => format!("F{number}"),
+        key => format!("{key:?}")
+    }
+}
@@ -0,0 +1,90 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::game::Game;
+use crate::net::protocol::SeatStatus;
+use crate::ui::{AppEvent, BLOCK, Network};
+
+/// Shows who sits where before a network game starts. The host starts the game once every client
+/// is ready and may remove a client from its seat, clients tell the host whether they are ready.
+pub(crate) struct Lobby {
+    /// The seat the host would remove.
+    selected: usize,
+}
+
+impl Lobby {
+    /// A lobby for network games that have not started yet.
+    pub fn for_network(network: &Network) -> Option<Self> {
+        network.in_lobby().then_some(Lobby { selected: 0 })
+    }
+
+    pub fn update(&mut self, event: AppEvent, network: &mut Network) {
+        match (event, network) {
+            (AppEvent::MoveUp, Network::Host(_)) => self.selected = self.selected.saturating_sub(1),
+            (AppEvent::MoveDown, Network::Host(host)) => self.selected = (self.selected + 1).min(host.seats().len() - 1),
+            (AppEvent::Select, Network::Host(host)) => host.start_game(),
+            (AppEvent::Kick, Network::Host(host)) => host.kick(self.selected),
+            (AppEvent::Select, Network::Client(client)) => {
+                let ready = client.lobby().get(client.seat()) == Some(&SeatStatus::Joined { ready: true });
+                client.set_ready(!ready);
+            }
+            _ => ()
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, game: &Game, network: &Network) {
+        let (seats, own_seat) = match network {
+            Network::Host(host) => (host.seats(), 0),
+            Network::Client(client) => (client.lobby(), client.seat()),
+            Network::Local => return
+        };
+        let mut lines = vec![];
+        for (seat, (status, player)) in seats.iter().zip(game.players()).enumerate() {
+            let status = match status {
+                SeatStatus::Host => "host",
+                SeatStatus::Bot => "bot",
+                SeatStatus::Open => "waiting...",
+                SeatStatus::Joined { ready: true } => "ready",
+                SeatStatus::Joined { ready: false } => "not ready"
+            };
+            let marker = match network {
+                Network::Host(_) if seat == self.selected => ">",
+                _ if seat == own_seat => "*",
+                _ => " "
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{marker}{}. ", seat + 1)),
+                Span::styled(format!("{BLOCK} {:<12}", player.name), Style::default().fg(player.color)),
+                Span::raw(status),
+            ]));
+        }
+        lines.push(Line::from(""));
+        match network {
+            Network::Host(host) => {
+                lines.push(Line::from(match host.can_start() {
+                    true => "Enter: start the game",
+                    false => "Waiting for players..."
+                }));
+                lines.push(Line::from("x: remove the selected player"));
+                if let Some(notice) = host.notice() {
+                    lines.push(Line::styled(notice.to_string(), Style::default().fg(Color::DarkGray)));
+                }
+            }
+            Network::Client(client) => lines.push(match (client.refusal(), client.is_connected()) {
+                (Some(reason), _) => Line::styled(format!("Not seated: {reason}"), Style::default().fg(Color::Red)),
+                (None, false) => Line::styled("The host closed the game", Style::default().fg(Color::Red)),
+                (None, true) => Line::from("Enter: toggle ready")
+            }),
+            Network::Local => ()
+        }
+
+        let size = frame.size();
+        let width = 44.min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("Lobby").borders(Borders::ALL)), area);
+    }
+}
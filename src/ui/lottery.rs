@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::game::{Game, Player};
+use crate::ui::BLOCK;
+
+/// How many full rounds the highlight makes before it settles on the drawn player.
+const ROUNDS: usize = 3;
+const FIRST_STEP: Duration = Duration::from_millis(40);
+/// Every step of the highlight takes this much longer than the one before, so it slows down.
+const STEP_SLOWDOWN: Duration = Duration::from_millis(8);
+
+/// Shows the draw of the starting player at the beginning of a game. The highlight runs around
+/// the seats and stops on the player the seeded draw picked, so the outcome is the same as in
+/// the game itself.
+pub(crate) struct Lottery {
+    players: Vec<(String, Color)>,
+    seed: u64,
+    winner: usize,
+    started: Instant,
+    skipped: bool,
+}
+
+impl Lottery {
+    /// A lottery for games that have not started yet, `None` for games that are under way or
+    /// have nobody to draw from.
+    pub fn for_game(game: &Game, now: Instant) -> Option<Self> {
+        let draw = game.starting_player_draw()?;
+        if game.moves_played() > 0 || game.players().len() < 2 {
+            return None;
+        }
+        Some(Lottery {
+            players: game.players().iter().map(|Player { name, color, .. }| (name.clone(), *color)).collect(),
+            seed: game.seed(),
+            winner: draw.value as usize,
+            started: now,
+            skipped: false,
+        })
+    }
+
+    fn total_steps(&self) -> usize {
+        ROUNDS * self.players.len() + self.winner
+    }
+
+    fn steps_taken(&self, now: Instant) -> usize {
+        if self.skipped {
+            return self.total_steps();
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        let mut step_end = Duration::ZERO;
+        (0..self.total_steps())
+            .take_while(|step| {
+                step_end += FIRST_STEP + STEP_SLOWDOWN * *step as u32;
+                step_end <= elapsed
+            })
+            .count()
+    }
+
+    pub fn is_settled(&self, now: Instant) -> bool {
+        self.steps_taken(now) == self.total_steps()
+    }
+
+    /// Skips the rest of the animation.
+    pub fn settle(&mut self) {
+        self.skipped = true;
+    }
+
+    pub fn render(&self, frame: &mut Frame, now: Instant) {
+        let highlighted = self.steps_taken(now) % self.players.len();
+        let settled = self.is_settled(now);
+
+        let mut lines = vec![Line::from("Seats in turn order:")];
+        lines.extend(self.players.iter().enumerate().map(|(seat, (name, color))| {
+            let style = if seat == highlighted { Style::default().fg(Color::Black).bg(*color) } else { Style::default().fg(*color) };
+            Line::from(vec![Span::raw(format!(" {}. ", seat + 1)), Span::styled(format!("{BLOCK} {name}"), style)])
+        }));
+        lines.push(Line::from(""));
+        lines.push(match settled {
+            true => Line::from(format!("{} starts. Press any key.", self.players[self.winner].0)),
+            false => Line::from("Drawing lots...")
+        });
+        lines.push(Line::styled(format!("seed {}", self.seed), Style::default().fg(Color::DarkGray)));
+
+        let size = frame.size();
+        let width = 34.min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("Starting player").borders(Borders::ALL)), area);
+    }
+}
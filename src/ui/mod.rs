@@ -1,33 +1,89 @@
 use std::collections::{HashMap, VecDeque};
-use std::io::{self, stdout};
+use std::io::{self, stdout, Stdout};
+use std::path::PathBuf;
+#[cfg(feature = "serde")]
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
 
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, MouseButton, MouseEventKind},
     ExecutableCommand,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use crossterm::event::KeyCode;
 use ratatui::layout::Position;
 use ratatui::prelude::*;
 
+use crate::bot::{self, Decision, Seat};
+use crate::bot::turn::{self, BotTurn};
 use crate::game::Game;
+use crate::game::draft::Draft;
+#[cfg(feature = "serde")]
+use crate::net::client::Client;
+#[cfg(feature = "serde")]
+use crate::net::host::Host;
+#[cfg(feature = "serde")]
+use crate::net::protocol::{Link, Rejection};
 use crate::ui::board_module::BoardDisplay;
+#[cfg(feature = "serde")]
+use crate::ui::chat_module::ChatDisplay;
+#[cfg(feature = "serde")]
+use crate::ui::lobby::Lobby;
+use crate::ui::lottery::Lottery;
 use crate::ui::piece_module::PieceDisplay;
 use crate::ui::player_module::PlayerDisplay;
+use crate::ui::profiler::{Phase, Profiler};
+#[cfg(feature = "serde")]
+use crate::ui::rejection::RejectionNotice;
+use crate::ui::results::Results;
+use crate::ui::reveal::Reveal;
+use crate::ui::status_module::StatusDisplay;
+use crate::ui::takeback::TakebackRequest;
+use crate::ui::too_small::TooSmall;
 
 mod scrollbars;
 mod board_module;
 mod player_module;
 mod piece_module;
+mod status_module;
+mod profiler;
+mod lottery;
+mod takeback;
+mod too_small;
+mod reveal;
+mod results;
+mod keymap;
+mod setup_screen;
+#[cfg(feature = "serde")]
+mod lobby;
+#[cfg(feature = "serde")]
+mod chat_module;
+#[cfg(feature = "serde")]
+mod join_screen;
+#[cfg(feature = "serde")]
+mod rejection;
+
+#[cfg(feature = "serde")]
+pub use crate::ui::join_screen::pick_game;
+pub use crate::ui::keymap::Keymap;
+pub use crate::ui::setup_screen::{setup_game, Setup};
 
 const BLOCK: &str = "██";
 const SHADED_BLOCK: &str = "░░";
+const BLOCKED_TILE: &str = "╳╳";
 const UI_OFFSET: u16 = 2;
+const MIN_PIECE_AREA_HEIGHT: u16 = 12;
+const SIDE_MENU_WIDTH: u16 = 26;
+/// How many cells the cursor on the board moves at once with the jump keys.
+const JUMP_DISTANCE: u16 = 5;
+#[cfg(feature = "serde")]
+const MIN_CHAT_AREA_HEIGHT: u16 = 6;
+/// How long a bot waits before it moves, so that the other players can follow its moves.
+const BOT_MOVE_DELAY: Duration = Duration::from_millis(400);
 
-const COLORS: [Color; 4] = [Color::Blue, Color::Yellow, Color::Red, Color::Green];
-
-#[derive(Default)]
 struct App {
-    modules: HashMap<ModuleKind, Box<dyn Module>>
+    modules: HashMap<ModuleKind, Box<dyn Module>>,
+    profiler: Profiler
 }
 
 pub(crate) trait Module {
@@ -40,11 +96,14 @@ pub trait RenderCanvas {
     fn render(&self) -> Vec<Line<'_>>;
 }
 
-#[derive(Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub(crate) enum ModuleKind {
     Board,
     Player,
-    Piece
+    Piece,
+    Status,
+    #[cfg(feature = "serde")]
+    Chat
 }
 
 #[derive(Clone, Default)]
@@ -61,86 +120,620 @@ pub(crate) enum AppEvent {
     MoveDown,
     MoveLeft,
     MoveRight,
+    /// Moves the cursor on the board by `JUMP_DISTANCE` cells.
+    JumpUp,
+    JumpDown,
+    JumpLeft,
+    JumpRight,
+    /// Moves the selection in the piece panel by a screenful.
+    PageUp,
+    PageDown,
+    /// Selects the first or the last piece in the piece panel.
+    Home,
+    End,
     OpenPieceSelection,
+    /// Sorts the piece panel by the next order.
+    CyclePieceOrder,
+    /// Selects the listed piece with this index by its label.
+    QuickSelect(usize),
     PieceSelected(usize),
+    DraftPick(usize),
+    /// A bot started to think about the turn of this player.
+    BotThinking(usize),
+    /// A bot finished thinking, its decision is waiting to be played.
+    BotMoveReady,
     PiecePlaced,
+    TurnPassed,
+    /// A message for the status bar, e.g. why a piece could not be placed.
+    Status(&'static str),
     Select,
     Rotate,
-    PlayerSwitched(usize),
+    /// Mirrors the selected piece, if the rules allow it.
+    Flip,
+    PreviewRotation,
+    ToggleDebugOverlay,
+    Pass,
+    ToggleMoveNumbers,
+    ToggleUnplacedPieces,
+    /// Shows the next suggested move, or the solution in puzzles.
+    Hint,
+    ToggleHeatmap,
+    /// Highlights the cells the active player may attach their next piece to.
+    ToggleAnchors,
+    RequestTakeback,
+    Confirm,
+    Decline,
+    #[cfg(feature = "serde")]
+    Save,
+    /// Removes a client from its seat in the lobby of a network game.
+    #[cfg(feature = "serde")]
+    Kick,
+    #[cfg(feature = "serde")]
+    Chat(ChatInput),
+    /// How the seat is connected to the host of a network game, with every heartbeat.
+    #[cfg(feature = "serde")]
+    Link(usize, Link),
+    Mouse(MouseInput),
+    /// A key without a binding, it only dismisses overlays.
+    OtherKey,
     None
 }
 
-pub fn run(game: &mut Game) -> io::Result<()> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+/// What the keys do while the chat has the input focus.
+#[cfg(feature = "serde")]
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum ChatInput {
+    /// Gives the chat the input focus.
+    Open,
+    /// Gives the input focus back to the board.
+    Leave,
+    Char(char),
+    Backspace,
+    Send,
+    ScrollUp,
+    ScrollDown,
+}
+
+/// What the mouse did, at a cell of the terminal. Every module checks whether the cell is part
+/// of the area it rendered last.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum MouseInput {
+    Click(Position),
+    ScrollUp(Position),
+    ScrollDown(Position),
+}
+
+/// Where the keys go. While the chat has the focus, every key is typed into it, so that typing
+/// neither moves the cursor nor quits the game.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Focus {
+    Board,
+    /// The piece panel is open with this many pieces, their labels select them.
+    Pieces(usize),
+    #[cfg(feature = "serde")]
+    Chat,
+}
+
+impl AppEvent {
+    /// Whether the event makes or prepares a move, which only the player to move may do.
+    fn is_move_input(&self) -> bool {
+        matches!(self, AppEvent::MoveUp | AppEvent::MoveDown | AppEvent::MoveLeft | AppEvent::MoveRight
+            | AppEvent::JumpUp | AppEvent::JumpDown | AppEvent::JumpLeft | AppEvent::JumpRight
+            | AppEvent::PageUp | AppEvent::PageDown | AppEvent::Home | AppEvent::End | AppEvent::QuickSelect(_)
+            | AppEvent::OpenPieceSelection | AppEvent::Select | AppEvent::Rotate | AppEvent::Flip | AppEvent::PreviewRotation | AppEvent::Pass
+            | AppEvent::Hint | AppEvent::Mouse(MouseInput::Click(_)))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub save_path: PathBuf,
+    /// If set, the time every module spends rendering and handling events is appended to this
+    /// file.
+    pub profile_log: Option<PathBuf>,
+    /// Shows the board mirrored horizontally, the arrow keys move the cursor as displayed.
+    pub mirror_board: bool,
+    /// Places the player and piece panels left of the board.
+    pub panel_left: bool,
+    pub cursor_style: CursorStyle,
+    /// Lets the board cursor blink, if the terminal supports it.
+    pub cursor_blink: bool,
+    /// Enables analysis aids like the heatmap of the board.
+    pub analysis: bool,
+    /// Asks for confirmation before a placement that looks like a blunder, see `Game::blunder`.
+    pub warn_blunders: bool,
+    pub keymap: Keymap,
+}
+
+/// How the cursor on the board is drawn when no piece is selected.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum CursorStyle {
+    /// A block in the color of the active player.
+    #[default]
+    Solid,
+    /// The tile below the cursor in inverted colors.
+    Inverse,
+    /// A bracket marker that stands out on every color theme.
+    Brackets,
+}
+
+impl std::str::FromStr for CursorStyle {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "solid" => Ok(CursorStyle::Solid),
+            "inverse" => Ok(CursorStyle::Inverse),
+            "brackets" => Ok(CursorStyle::Brackets),
+            _ => Err("expected 'solid', 'inverse' or 'brackets'".to_string())
+        }
+    }
+}
+
+/// The network game the UI takes part in, if any.
+#[derive(Default)]
+pub enum Network {
+    #[default]
+    Local,
+    /// The game is hosted at this machine, clients play the remote seats.
+    #[cfg(feature = "serde")]
+    Host(Host),
+    /// The game is hosted at another machine, which sends the moves of the remote seats.
+    #[cfg(feature = "serde")]
+    Client(Client),
+}
+
+/// Runs the terminal UI until the player quits. Every player has a seat, the moves of players
+/// seated with an agent are made by the agent.
+pub fn run(game: &mut Game, options: &Options, seats: Vec<Seat>, mut network: Network) -> io::Result<()> {
+    let profiler = options.profile_log.as_deref().map(Profiler::with_log).transpose()?.unwrap_or_default();
+    let mut terminal = enter_screen()?;
     let mut event_queue = VecDeque::new();
-    let mut app = App::default();
+    let mut app = App { modules: HashMap::new(), profiler };
 
-    app.add_module(BoardDisplay::new(game.width(), game.height(), game.active_player_index()));
-    app.add_module(PlayerDisplay);
-    app.add_module(PieceDisplay::new());
+    app.add_game_modules(game, options);
 
     let name_area_height = game.players().len() as u16 + UI_OFFSET;
-    let piece_area_height = game.height() - name_area_height + UI_OFFSET;
-
-    let horizontal = Layout::horizontal([Constraint::Max((game.width() * 2) + UI_OFFSET), Constraint::Max(20)]);
-    let vertical = Layout::vertical([Constraint::Max(name_area_height), Constraint::Max(piece_area_height)]);
+    // small boards like puzzles still need room for the tallest piece
+    let piece_area_height = (game.height().saturating_sub(name_area_height) + UI_OFFSET).max(MIN_PIECE_AREA_HEIGHT);
+
+    let board_constraint = Constraint::Max((game.width() * 2) + UI_OFFSET);
+    let side_menu_constraint = Constraint::Max(SIDE_MENU_WIDTH);
+    let horizontal = match options.panel_left {
+        true => Layout::horizontal([side_menu_constraint, board_constraint]),
+        false => Layout::horizontal([board_constraint, side_menu_constraint])
+    };
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    let mut side_menu_constraints = vec![Constraint::Max(name_area_height), Constraint::Max(piece_area_height)];
+    // the messages typed into the chat, and those of the other players, go through the network
+    #[cfg(feature = "serde")]
+    let chat = (!matches!(network, Network::Local)).then(|| {
+        let (received, shown) = mpsc::channel();
+        let (typed, outgoing) = mpsc::channel();
+        app.add_module(ChatDisplay::new(shown, typed));
+        side_menu_constraints.push(Constraint::Min(MIN_CHAT_AREA_HEIGHT));
+        (received, outgoing)
+    });
+    let vertical = Layout::vertical(side_menu_constraints);
+    let status_bar = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]);
+    // the largest piece has to fit onto the board, and with its caption into the piece panel
+    // below the names
+    let largest_piece = game.players().iter()
+        .flat_map(|player| &player.available_pieces)
+        .chain(game.draft().map_or(&[][..], Draft::pool))
+        .map(|piece| piece.num_lines().max(piece.num_columns()))
+        .max()
+        .unwrap_or(1);
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    let mut side_menu_height = name_area_height + largest_piece + 1 + UI_OFFSET;
+    #[cfg(feature = "serde")]
+    if chat.is_some() {
+        side_menu_height += MIN_CHAT_AREA_HEIGHT;
+    }
+    let too_small = TooSmall::new(SIDE_MENU_WIDTH + largest_piece * 2 + UI_OFFSET, side_menu_height.max(largest_piece + UI_OFFSET) + 1);
+    let mut lottery = Lottery::for_game(game, Instant::now());
+    #[cfg(feature = "serde")]
+    let mut lobby = Lobby::for_network(&network);
+    let mut takeback: Option<TakebackRequest> = None;
+    let mut reveal: Option<Reveal> = None;
+    let mut results: Option<Results> = None;
+    // the board stays visible once the results are closed, Enter brings them back
+    let mut results_closed = false;
+    #[cfg(feature = "serde")]
+    let mut rejection: Option<RejectionNotice> = None;
+    let mut shown_round = game.revealed().to_vec();
+    let remote_seats = seats.iter().map(|seat| matches!(seat, Seat::Remote)).collect::<Vec<_>>();
+    let mut bots = seats.into_iter().map(Seat::into_agent).collect::<Vec<_>>();
+    let mut bot_turn_started: Option<Instant> = None;
+    // the turn of the active bot, and stale turns until their bots come back
+    let mut bot_turns: Vec<BotTurn> = vec![];
+    let mut bot_decision: Option<Decision> = None;
+    let mut focus = Focus::Board;
 
     'main_loop: loop {
         terminal.draw(|frame| {
-            let [board_area, side_menu_area] = horizontal.areas(frame.size());
-            let [player_area, piece_area] = vertical.areas(side_menu_area);
-
-            let areas = vec![
+            if !too_small.fits(frame.size()) {
+                return too_small.render(frame);
+            }
+            let [game_area, status_area] = status_bar.areas(frame.size());
+            let [board_area, side_menu_area] = match horizontal.areas(game_area) {
+                [side_menu_area, board_area] if options.panel_left => [board_area, side_menu_area],
+                areas => areas
+            };
+            let side_menu_areas = vertical.split(side_menu_area);
+
+            #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+            let mut areas = vec![
                 (ModuleKind::Board, board_area),
-                (ModuleKind::Player, player_area),
-                (ModuleKind::Piece, piece_area)
+                (ModuleKind::Player, side_menu_areas[0]),
+                (ModuleKind::Piece, side_menu_areas[1]),
+                (ModuleKind::Status, status_area)
             ].into_iter().collect::<HashMap<ModuleKind, Rect>>();
-            app.render_modules(frame, game, areas)
+            #[cfg(feature = "serde")]
+            if let Some(chat_area) = side_menu_areas.get(2) {
+                areas.insert(ModuleKind::Chat, *chat_area);
+            }
+            app.render_modules(frame, game, areas);
+            app.profiler.render_overlay(frame);
+            if let Some(lottery) = &lottery {
+                lottery.render(frame, Instant::now());
+            }
+            #[cfg(feature = "serde")]
+            if let Some(lobby) = &lobby {
+                lobby.render(frame, game, &network);
+            }
+            if let Some(takeback) = &takeback {
+                takeback.render(frame, game);
+            }
+            if let Some(reveal) = &reveal {
+                reveal.render(frame, Instant::now());
+            }
+            if let Some(results) = &results {
+                results.render(frame, game);
+            }
+            #[cfg(feature = "serde")]
+            if let Some(rejection) = &rejection {
+                rejection.render(frame);
+            }
         })?;
 
-        event_queue.push_back(poll_event()?);
+        network.receive(game, &mut event_queue);
+        #[cfg(feature = "serde")]
+        if let Some(chat) = &chat {
+            network.relay_chat(chat);
+        }
+        #[cfg(feature = "serde")]
+        if let Some(rejected) = network.take_rejection() {
+            rejection = Some(RejectionNotice::new(rejected));
+        }
+        #[cfg(feature = "serde")]
+        if let Some(current) = &mut lobby {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                event => current.update(event, &mut network)
+            }
+            if !network.in_lobby() {
+                lobby = None;
+                // the starting player is drawn for everybody at once
+                lottery = Lottery::for_game(game, Instant::now());
+            }
+            continue;
+        }
+
+        if let Some(current) = &mut lottery {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::None => (),
+                // the first key skips the animation, the second one starts the game
+                _ if !current.is_settled(Instant::now()) => current.settle(),
+                _ => {
+                    lottery = None;
+                    // nobody has moved yet, so the clocks start fresh once the game begins
+                    if let Some(time_control) = game.clock().map(|clock| clock.time_control().clone()) {
+                        game.start_clock(time_control, Instant::now());
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(current) = &mut reveal {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::None => (),
+                _ if !current.is_settled(Instant::now()) => current.settle(),
+                _ => reveal = None
+            }
+            continue;
+        }
+
+        #[cfg(feature = "serde")]
+        if rejection.is_some() {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::None => (),
+                _ => rejection = None
+            }
+            continue;
+        }
+
+        if game.is_over() && !results_closed && results.is_none() {
+            // the remote players of network games are not around for another game
+            results = Some(Results::new(matches!(network, Network::Local)));
+        }
+        if results.is_some() {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::Decline => {
+                    results = None;
+                    results_closed = true;
+                }
+                AppEvent::Select if matches!(network, Network::Local) => {
+                    results = None;
+                    game.rematch();
+                    app.add_game_modules(game, options);
+                    event_queue.clear();
+                    shown_round = game.revealed().to_vec();
+                    bot_decision = None;
+                    bot_turn_started = None;
+                }
+                _ => ()
+            }
+            continue;
+        }
+
+        // requests without anybody to ask, e.g. in solo games, are accepted right away
+        if takeback.as_ref().is_some_and(TakebackRequest::is_accepted) {
+            takeback = None;
+            game.take_back();
+            event_queue.push_back(AppEvent::TurnPassed);
+        }
+        if let Some(request) = &mut takeback {
+            match poll_event(Focus::Board, &options.keymap)? {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::Confirm => request.accept(),
+                AppEvent::Decline => takeback = None,
+                _ => ()
+            }
+            continue;
+        }
+
+        if game.tick(Instant::now()).is_some() {
+            event_queue.push_back(AppEvent::TurnPassed);
+        }
+        let active_player_index = game.active_player_index();
+        // only the turn of the active player holds up the keyboard, not one that went stale
+        let is_thinking = bot_turns.iter().any(|turn| turn.player_index() == active_player_index);
+        let is_bot_turn = !game.is_over() && (bots[active_player_index].is_some() || is_thinking);
+        let is_remote_turn = !game.is_over() && remote_seats[active_player_index];
+        if is_bot_turn && !is_thinking && bot_turn_started.get_or_insert_with(Instant::now).elapsed() >= BOT_MOVE_DELAY {
+            bot_turn_started = None;
+            let bot = bots[active_player_index].take().expect("Bots are put back after their turn");
+            bot_turns.push(BotTurn::start(bot, game));
+            event_queue.push_back(AppEvent::BotThinking(active_player_index));
+        }
+        for (finished, bot, decision) in turn::take_finished(&mut bot_turns) {
+            bots[finished.player_index()] = Some(bot);
+            // the decision of a stale turn is dropped
+            if finished.is_current(game) {
+                bot_decision = Some(decision);
+                event_queue.push_back(AppEvent::BotMoveReady);
+            }
+        }
+        match poll_event(focus, &options.keymap)? {
+            // nobody at the keyboard may move for a bot or a player at another machine
+            event if (is_bot_turn || is_remote_turn) && event.is_move_input() => (),
+            event => event_queue.push_back(event)
+        }
         while let Some(event) = event_queue.pop_front() {
-            if let AppEvent::Quit = event { break 'main_loop }
-            app.update_modules(event, game, &mut event_queue);
+            match event {
+                AppEvent::OpenPieceSelection => focus = Focus::Pieces(PieceDisplay::listed_pieces(game).len()),
+                AppEvent::PieceSelected(_) | AppEvent::PiecePlaced | AppEvent::TurnPassed if matches!(focus, Focus::Pieces(_)) => focus = Focus::Board,
+                _ => ()
+            }
+            match event {
+                AppEvent::Quit => break 'main_loop,
+                AppEvent::ToggleDebugOverlay => app.profiler.toggle_overlay(),
+                // network games have no takebacks, the key opens the chat instead
+                #[cfg(feature = "serde")]
+                AppEvent::RequestTakeback if chat.is_some() => event_queue.push_back(AppEvent::Chat(ChatInput::Open)),
+                AppEvent::RequestTakeback => takeback = TakebackRequest::for_game(game),
+                AppEvent::Select if game.is_over() => results_closed = false,
+                AppEvent::BotMoveReady => {
+                    if let Some(decision) = bot_decision.take() {
+                        bot::apply(decision, game);
+                    }
+                    app.update_modules(event, game, &mut event_queue);
+                    event_queue.push_back(AppEvent::TurnPassed);
+                }
+                AppEvent::DraftPick(pool_index) => {
+                    game.draft_pick(pool_index).expect("Out of range");
+                    event_queue.push_back(AppEvent::TurnPassed);
+                }
+                AppEvent::Pass if !game.rules().skip_blocked_players && !game.is_over() && game.draft().is_none() => {
+                    game.pass();
+                    event_queue.push_back(AppEvent::TurnPassed);
+                }
+                #[cfg(feature = "serde")]
+                AppEvent::Chat(input) => {
+                    match input {
+                        ChatInput::Open => focus = Focus::Chat,
+                        ChatInput::Leave => focus = Focus::Board,
+                        _ => ()
+                    }
+                    app.update_modules(event, game, &mut event_queue);
+                }
+                #[cfg(feature = "serde")]
+                AppEvent::Save => network.save(game, &options.save_path)?,
+                _ => app.update_modules(event, game, &mut event_queue)
+            }
+        }
+        if game.revealed() != shown_round {
+            shown_round = game.revealed().to_vec();
+            reveal = Reveal::for_game(game, Instant::now());
         }
     }
 
+    leave_screen()
+}
+
+/// Takes the terminal over for one of the screens of the UI, the game itself or one of the
+/// screens before it. `leave_screen` hands the terminal back.
+fn enter_screen() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?.execute(EnableMouseCapture)?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+fn leave_screen() -> io::Result<()> {
     disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+    stdout().execute(DisableMouseCapture)?.execute(LeaveAlternateScreen)?;
     Ok(())
 }
 
-fn poll_event() -> io::Result<AppEvent> {
+impl Network {
+    /// Whether the network game waits in the lobby for its players.
+    #[cfg(feature = "serde")]
+    fn in_lobby(&self) -> bool {
+        match self {
+            Network::Local => false,
+            #[cfg(feature = "serde")]
+            Network::Host(host) => !host.is_started(),
+            #[cfg(feature = "serde")]
+            Network::Client(client) => !client.is_started()
+        }
+    }
+
+    /// Sends the messages typed into the chat and passes on those of the other players.
+    #[cfg(feature = "serde")]
+    fn relay_chat(&mut self, (received, outgoing): &(Sender<(usize, String)>, Receiver<String>)) {
+        let messages = match self {
+            Network::Local => vec![],
+            Network::Host(host) => {
+                outgoing.try_iter().for_each(|text| host.send_chat(text));
+                host.take_chat()
+            }
+            Network::Client(client) => {
+                outgoing.try_iter().for_each(|text| client.send_chat(text));
+                client.take_chat()
+            }
+        };
+        for message in messages {
+            let _ = received.send(message);
+        }
+    }
+
+    /// Saves the game, and once a game joined at another machine is over, the replay its host
+    /// recorded next to it.
+    #[cfg(feature = "serde")]
+    fn save(&self, game: &Game, path: &std::path::Path) -> io::Result<()> {
+        game.save(path)?;
+        match self {
+            Network::Client(client) => client.replay().map_or(Ok(()), |replay| replay.save(path.with_extension("replay.json"))),
+            _ => Ok(())
+        }
+    }
+
+    /// Why the host did not play the last move of this machine, if it rejected one.
+    #[cfg(feature = "serde")]
+    fn take_rejection(&mut self) -> Option<Rejection> {
+        match self {
+            Network::Client(client) => client.take_rejection(),
+            _ => None
+        }
+    }
+
+    /// Handles what the other machines sent since the last call.
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    fn receive(&mut self, game: &mut Game, event_queue: &mut VecDeque<AppEvent>) {
+        match self {
+            Network::Local => (),
+            #[cfg(feature = "serde")]
+            Network::Host(host) => {
+                if host.poll(game) {
+                    event_queue.push_back(AppEvent::TurnPassed);
+                }
+                if let Some(links) = host.take_links() {
+                    event_queue.extend(links.into_iter().enumerate().map(|(seat, link)| AppEvent::Link(seat, link)));
+                }
+            }
+            #[cfg(feature = "serde")]
+            Network::Client(client) => {
+                if client.poll(game) {
+                    event_queue.push_back(AppEvent::TurnPassed);
+                }
+                if let Some(links) = client.take_links() {
+                    event_queue.extend(links.into_iter().enumerate().map(|(seat, link)| AppEvent::Link(seat, link)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+fn poll_event(focus: Focus, keymap: &Keymap) -> io::Result<AppEvent> {
     if event::poll(std::time::Duration::from_millis(50))? {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == event::KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(AppEvent::Quit),
-                    KeyCode::Up => return Ok(AppEvent::MoveUp),
-                    KeyCode::Down => return Ok(AppEvent::MoveDown),
-                    KeyCode::Left => return Ok(AppEvent::MoveLeft),
-                    KeyCode::Right => return Ok(AppEvent::MoveRight),
-                    KeyCode::Char('i') => return Ok(AppEvent::OpenPieceSelection),
-                    KeyCode::Enter => return Ok(AppEvent::Select),
-                    KeyCode::Char('c') => return Ok(AppEvent::Rotate),
-                    _ => ()
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            let position = Position { x: mouse.column, y: mouse.row };
+            return Ok(match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => AppEvent::Mouse(MouseInput::Click(position)),
+                MouseEventKind::ScrollUp => AppEvent::Mouse(MouseInput::ScrollUp(position)),
+                MouseEventKind::ScrollDown => AppEvent::Mouse(MouseInput::ScrollDown(position)),
+                // moving the mouse neither counts as a key nor dismisses overlays
+                _ => AppEvent::None
+            });
+        }
+        if let Event::Key(key) = event {
+            #[cfg(feature = "serde")]
+            if key.kind == event::KeyEventKind::Press && focus == Focus::Chat {
+                let input = match key.code {
+                    KeyCode::Esc => ChatInput::Leave,
+                    KeyCode::Enter => ChatInput::Send,
+                    KeyCode::Backspace => ChatInput::Backspace,
+                    KeyCode::Up => ChatInput::ScrollUp,
+                    KeyCode::Down => ChatInput::ScrollDown,
+                    // shortcuts of the terminal are not part of the message
+                    KeyCode::Char(char) if !key.modifiers.intersects(event::KeyModifiers::CONTROL | event::KeyModifiers::ALT) => ChatInput::Char(char),
+                    _ => return Ok(AppEvent::OtherKey)
+                };
+                return Ok(AppEvent::Chat(input));
+            }
+            // the labels of the listed pieces are keys without an action, see `Keymap::quick_keys`
+            if let (event::KeyEventKind::Press, Focus::Pieces(listed), KeyCode::Char(char)) = (key.kind, focus, key.code) {
+                if let Some(index) = keymap.quick_keys().iter().position(|key| *key == char).filter(|index| *index < listed) {
+                    return Ok(AppEvent::QuickSelect(index));
                 }
             }
+            if key.kind == event::KeyEventKind::Press {
+                return Ok(keymap.event(key.code));
+            }
         }
     }
     Ok(AppEvent::None)
 }
 
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
 impl App {
     fn add_module(&mut self, module: impl Module + 'static) {
         self.modules.insert(module.kind(), Box::new(module));
     }
 
+    /// The modules that show the game, fresh ones replace those of a previous game.
+    fn add_game_modules(&mut self, game: &Game, options: &Options) {
+        self.add_module(BoardDisplay::new(game.width(), game.height(), game.active_player_index(), options));
+        self.add_module(PlayerDisplay::default());
+        self.add_module(PieceDisplay::new(&options.keymap));
+        self.add_module(StatusDisplay::new(options.keymap.clone()));
+    }
+
     fn update_modules(&mut self, event: AppEvent, game: &mut Game, event_queue: &mut VecDeque<AppEvent>) {
-        for (_, module) in self.modules.iter_mut() {
-            if let Some(event) = module.update(event, game) {
+        for (kind, module) in self.modules.iter_mut() {
+            if let Some(event) = self.profiler.measure(*kind, Phase::Update, || module.update(event, game)) {
                 event_queue.push_back(event);
             }
         }
@@ -148,7 +741,7 @@ impl App {
 
     fn render_modules(&mut self, frame: &mut Frame, game: &mut Game, areas: HashMap<ModuleKind, Rect>) {
         for (kind, module) in self.modules.iter_mut() {
-            module.render(frame, *areas.get(kind).unwrap(), game)
+            self.profiler.measure(*kind, Phase::Render, || module.render(frame, *areas.get(kind).unwrap(), game))
         }
     }
 }
@@ -172,20 +765,10 @@ impl Cursor {
         }
     }
 
-    fn new(max_x: u16, max_y: u16, area: Rect) -> Self {
-        Cursor {
-            max_x,
-            max_y,
-            area
-        }
-    }
-
     fn move_down(&mut self, distance: u16) {
-        if self.area.y <= self.max_y - self.area.height - distance {
-            self.area.y += distance
-        } else {
-            self.area.y = self.max_y - self.area.height
-        }
+        // pieces that are larger than the board stay at its top left corner
+        let bound = self.max_y.saturating_sub(self.area.height);
+        self.area.y = self.area.y.saturating_add(distance).min(bound)
     }
 
     fn move_up(&mut self, distance: u16) {
@@ -197,11 +780,8 @@ impl Cursor {
     }
 
     fn move_right(&mut self, distance: u16) {
-        if self.area.x <= self.max_x - self.area.width - distance {
-            self.area.x += distance
-        } else {
-            self.area.x = self.max_x - self.area.width
-        }
+        let bound = self.max_x.saturating_sub(self.area.width);
+        self.area.x = self.area.x.saturating_add(distance).min(bound)
     }
 
     fn move_left(&mut self, distance: u16) {
@@ -213,11 +793,37 @@ impl Cursor {
     }
 
     fn move_cursor(&mut self, x: i32, y: i32) {
-        if x < 0 { self.move_left(x.abs() as u16) } else { self.move_right(x as u16) }
-        if y < 0 { self.move_up(y.abs() as u16) } else { self.move_down(y as u16) }
+        if x < 0 { self.move_left(x.unsigned_abs() as u16) } else { self.move_right(x as u16) }
+        if y < 0 { self.move_up(y.unsigned_abs() as u16) } else { self.move_down(y as u16) }
     }
 
     fn rotate_cursor(&mut self) {
         std::mem::swap(&mut self.area.width, &mut self.area.height);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_the_cursor_on_a_board_as_large_as_the_piece() {
+        let mut cursor = Cursor { area: Rect::new(0, 0, 3, 1), max_x: 3, max_y: 1 };
+        cursor.move_down(1);
+        cursor.move_right(1);
+        assert_eq!(cursor.area, Rect::new(0, 0, 3, 1));
+
+        // a piece taller than the board does not move either
+        cursor.rotate_cursor();
+        cursor.move_down(1);
+        assert_eq!(cursor.area, Rect::new(0, 0, 1, 3));
+    }
+
+    #[test]
+    fn should_stop_jumps_at_the_edge_of_a_small_board() {
+        let mut cursor = Cursor { area: Rect::new(1, 1, 2, 2), max_x: JUMP_DISTANCE + 2, max_y: JUMP_DISTANCE };
+        cursor.move_down(JUMP_DISTANCE);
+        cursor.move_right(JUMP_DISTANCE);
+        assert_eq!(cursor.area, Rect::new(JUMP_DISTANCE, JUMP_DISTANCE - 2, 2, 2));
+    }
+}
@@ -1,27 +1,100 @@
 use ratatui::Frame;
-use ratatui::layout::Rect;
+use ratatui::layout::{Margin, Position, Rect};
 use ratatui::prelude::{Line, Span};
 use ratatui::style::{Color, Style};
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::game::{Game, Piece};
-use crate::ui::{AppEvent, BLOCK, Cursor, Module, ModuleKind, RenderCanvas, UI_OFFSET};
-use crate::ui::scrollbars::VerticalScrollBar;
+use crate::game::draft::Draft;
+use crate::ui::{AppEvent, BLOCK, Cursor, Keymap, Module, ModuleKind, MouseInput, RenderCanvas, UI_OFFSET};
+use crate::ui::scrollbars::ScrollBar;
 
 pub struct PieceDisplay {
     selection_index: usize,
     cursor: Cursor,
-    scrollbar: VerticalScrollBar,
-    enabled: bool
+    scrollbar: ScrollBar,
+    /// Where the pieces were rendered last, to find the piece that is clicked.
+    area: Rect,
+    enabled: bool,
+    /// After the game, shows the unplaced pieces of every player instead of the active one.
+    show_unplaced_pieces: bool,
+    /// The keys that select the listed pieces while the panel is open, in the order of the list.
+    quick_keys: Vec<char>,
+    piece_order: PieceOrder
+}
+
+/// How the pieces in the panel are sorted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum PieceOrder {
+    /// The order of the piece set, or of the draft pool.
+    Listed,
+    LargestFirst,
+    SmallestFirst,
+}
+
+impl PieceOrder {
+    fn next(self) -> Self {
+        match self {
+            PieceOrder::Listed => PieceOrder::LargestFirst,
+            PieceOrder::LargestFirst => PieceOrder::SmallestFirst,
+            PieceOrder::SmallestFirst => PieceOrder::Listed,
+        }
+    }
 }
 
 impl PieceDisplay {
-    pub fn new() -> Self {
+    pub fn new(keymap: &Keymap) -> Self {
         PieceDisplay {
             selection_index: 0,
             cursor: Cursor::default(),
-            scrollbar: VerticalScrollBar::default(),
-            enabled: false
+            scrollbar: ScrollBar::vertical(),
+            area: Rect::default(),
+            enabled: false,
+            show_unplaced_pieces: false,
+            quick_keys: keymap.quick_keys(),
+            piece_order: PieceOrder::Listed
+        }
+    }
+
+    /// The pieces to choose from: the pool while drafting, otherwise the rack of the active
+    /// player.
+    pub(crate) fn listed_pieces(game: &Game) -> &[Piece] {
+        game.draft().map_or(game.active_player_pieces(), Draft::pool)
+    }
+
+    /// The indices of the listed pieces in the order they are shown.
+    fn order(&self, game: &Game) -> Vec<usize> {
+        let pieces = Self::listed_pieces(game);
+        let mut order = (0..pieces.len()).collect::<Vec<_>>();
+        // pieces of the same size stay in the order of the list
+        match self.piece_order {
+            PieceOrder::Listed => (),
+            PieceOrder::LargestFirst => order.sort_by_key(|index| std::cmp::Reverse(pieces[*index].size())),
+            PieceOrder::SmallestFirst => order.sort_by_key(|index| pieces[*index].size()),
+        }
+        order
+    }
+
+    fn shown_pieces<'a>(&self, game: &'a Game) -> Vec<&'a Piece> {
+        let pieces = Self::listed_pieces(game);
+        self.order(game).into_iter().map(|index| &pieces[index]).collect()
+    }
+
+    fn title(&self, game: &Game) -> String {
+        let title = if game.draft().is_some() { "Draft pool" } else { "Pieces" };
+        match self.piece_order {
+            PieceOrder::Listed => title.to_string(),
+            PieceOrder::LargestFirst => format!("{title} - largest first"),
+            PieceOrder::SmallestFirst => format!("{title} - smallest first"),
+        }
+    }
+
+    /// Switches to the next order and keeps the selected piece selected.
+    fn cycle_order(&mut self, game: &Game) {
+        let selected = self.order(game).get(self.selection_index).copied();
+        self.piece_order = self.piece_order.next();
+        if let Some(position) = selected.and_then(|selected| self.order(game).iter().position(|index| *index == selected)) {
+            self.move_cursor_to(position, game);
         }
     }
 
@@ -32,22 +105,88 @@ impl PieceDisplay {
     }
 
     fn move_cursor_down(&mut self, game: &Game) {
-        if self.selection_index < game.active_player_pieces().len() - 1 {
-            self.cursor.area.y += game.active_player_pieces()[self.selection_index].num_lines() + 1;
+        let pieces = self.shown_pieces(game);
+        if self.selection_index + 1 < pieces.len() {
+            self.cursor.area.y += entry_height(pieces[self.selection_index]);
             self.selection_index += 1;
-            self.update_cursor_dimensions(&game.active_player_pieces()[self.selection_index]);
+            self.update_cursor_dimensions(pieces[self.selection_index]);
         }
     }
 
     fn move_cursor_up(&mut self, game: &Game) {
         if self.selection_index > 0 {
             self.selection_index -= 1;
-            let active_piece = &game.active_player_pieces()[self.selection_index];
-            self.cursor.area.y = self.cursor.area.y.saturating_sub(active_piece.num_lines() + 1);
+            let active_piece = self.shown_pieces(game)[self.selection_index];
+            self.cursor.area.y = self.cursor.area.y.saturating_sub(entry_height(active_piece));
             self.update_cursor_dimensions(active_piece);
         }
     }
 
+    fn move_cursor_to(&mut self, index: usize, game: &Game) {
+        self.cursor.area.y = self.first_line(index, game);
+        self.selection_index = index;
+        self.update_cursor_dimensions(self.shown_pieces(game)[index]);
+    }
+
+    /// The line of the list the listed piece starts at.
+    fn first_line(&self, index: usize, game: &Game) -> u16 {
+        self.shown_pieces(game)[..index].iter().map(|piece| entry_height(piece)).sum()
+    }
+
+    /// How many lines of the list are shown at once.
+    fn page_height(&self) -> u16 {
+        self.area.height.saturating_sub(UI_OFFSET)
+    }
+
+    /// Selects the last piece that still ends within a screenful below the selected one, or at
+    /// least the next one.
+    fn move_page_down(&mut self, game: &Game) {
+        let pieces = self.shown_pieces(game);
+        let page_end = self.cursor.area.y + self.page_height();
+        let index = (self.selection_index + 1..pieces.len())
+            .take_while(|index| self.first_line(*index, game) + shown_height(pieces[*index]) <= page_end)
+            .last()
+            .unwrap_or(self.selection_index + 1);
+        if index < pieces.len() {
+            self.move_cursor_to(index, game);
+        }
+    }
+
+    /// Selects the first piece that starts within a screenful above the selected one, or at
+    /// least the previous one.
+    fn move_page_up(&mut self, game: &Game) {
+        if self.shown_pieces(game).is_empty() {
+            return;
+        }
+        let page_start = self.cursor.area.y.saturating_sub(self.page_height());
+        let index = (0..self.selection_index)
+            .find(|index| self.first_line(*index, game) >= page_start)
+            .unwrap_or(self.selection_index.saturating_sub(1));
+        self.move_cursor_to(index, game);
+    }
+
+    fn move_cursor_to_end(&mut self, game: &Game) {
+        if let Some(last) = self.shown_pieces(game).len().checked_sub(1) {
+            self.move_cursor_to(last, game);
+        }
+    }
+
+    /// The index of the listed piece shown at a cell of the terminal.
+    fn clicked_piece(&self, position: Position, game: &Game) -> Option<usize> {
+        let inner = self.area.inner(&Margin::new(1, 1));
+        if !inner.contains(position) {
+            return None;
+        }
+        let mut line = position.y - inner.y + self.scrollbar.offset();
+        for (index, piece) in self.shown_pieces(game).iter().enumerate() {
+            if line < shown_height(piece) {
+                return Some(index);
+            }
+            line = line.checked_sub(entry_height(piece))?;
+        }
+        None
+    }
+
     fn reset_cursor(&mut self) {
         self.cursor.area.y = 0;
         self.cursor.area.x = 0;
@@ -55,28 +194,82 @@ impl PieceDisplay {
     }
 
     fn update_cursor_dimensions(&mut self, piece: &Piece) {
-        self.cursor.area.height = piece.num_lines();
+        self.cursor.area.height = shown_height(piece);
         self.cursor.area.width = piece.num_columns();
     }
 
-    fn select_piece(&mut self) -> usize {
+    fn render_unplaced_pieces(&mut self, frame: &mut Frame, widget_area: Rect, game: &Game) {
+        // every piece is highlighted by pretending it is the selected one
+        let render_pieces = game.players().iter()
+            .map(|player| (player, player.available_pieces.iter().map(|piece| RenderPiece::new(piece, &player.color, 0, 0)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        let mut text = vec![];
+        for (player, pieces) in &render_pieces {
+            text.push(Line::styled(format!("{} ({} left)", player.name, pieces.len()), Style::default().fg(player.color)));
+            text.extend(pieces.iter().flat_map(Self::render_piece));
+        }
+        frame.render_widget(
+            Paragraph::new(text)
+                .centered()
+                .block(Block::default().borders(Borders::ALL).title("Unplaced pieces")),
+            widget_area
+        );
+    }
+
+    fn select_piece(&mut self, game: &Game) -> AppEvent {
         self.enabled = false;
-        self.selection_index
+        let index = self.order(game)[self.selection_index];
+        match game.draft() {
+            Some(_) => AppEvent::DraftPick(index),
+            None => AppEvent::PieceSelected(index)
+        }
     }
 }
 
+/// The lines of a piece in the list, its shape and the caption below.
+fn shown_height(piece: &Piece) -> u16 {
+    piece.num_lines() + 1
+}
+
+/// The lines of a piece in the list up to the next piece, which is separated by an empty line.
+fn entry_height(piece: &Piece) -> u16 {
+    shown_height(piece) + 1
+}
+
 impl Module for PieceDisplay {
     fn update(&mut self, event: AppEvent, game: &mut Game) -> Option<AppEvent> {
         match event {
+            AppEvent::ToggleUnplacedPieces if game.is_over() => self.show_unplaced_pieces = !self.show_unplaced_pieces,
             AppEvent::OpenPieceSelection => self.enabled = true,
+            AppEvent::CyclePieceOrder if !game.is_over() => self.cycle_order(game),
             AppEvent::PiecePlaced => self.reset_cursor(),
+            AppEvent::TurnPassed => {
+                self.enabled = false;
+                self.reset_cursor();
+            }
+            AppEvent::Mouse(MouseInput::Click(position)) if !game.is_over() => {
+                if let Some(index) = self.clicked_piece(position, game) {
+                    self.move_cursor_to(index, game);
+                    return Some(self.select_piece(game));
+                }
+            }
+            AppEvent::Mouse(MouseInput::ScrollDown(position)) if !game.is_over() && self.area.contains(position) => self.move_cursor_down(game),
+            AppEvent::Mouse(MouseInput::ScrollUp(position)) if !game.is_over() && self.area.contains(position) => self.move_cursor_up(game),
             _ => ()
         }
         if self.enabled {
             match event {
                 AppEvent::MoveDown => self.move_cursor_down(game),
                 AppEvent::MoveUp => self.move_cursor_up(game),
-                AppEvent::Select => return Some(AppEvent::PieceSelected(self.select_piece())),
+                AppEvent::PageDown => self.move_page_down(game),
+                AppEvent::PageUp => self.move_page_up(game),
+                AppEvent::Home if !self.shown_pieces(game).is_empty() => self.move_cursor_to(0, game),
+                AppEvent::End => self.move_cursor_to_end(game),
+                AppEvent::QuickSelect(index) if index < self.shown_pieces(game).len() => {
+                    self.move_cursor_to(index, game);
+                    return Some(self.select_piece(game));
+                }
+                AppEvent::Select => return Some(self.select_piece(game)),
                 AppEvent::PiecePlaced => self.reset_cursor(),
                 _ => ()
             }
@@ -86,11 +279,15 @@ impl Module for PieceDisplay {
     }
 
     fn render(&mut self, frame: &mut Frame, widget_area: Rect, game: &mut Game) {
-        let pieces = game.active_player_pieces();
+        self.area = widget_area;
+        if self.show_unplaced_pieces && game.is_over() {
+            return self.render_unplaced_pieces(frame, widget_area, game);
+        }
+        let pieces = self.shown_pieces(game);
         let player_color = &game.active_player().color;
         let render_pieces = pieces.iter()
             .enumerate()
-            .map(|(row, piece)| RenderPiece::new(piece, player_color, self.selection_index, row))
+            .map(|(row, piece)| RenderPiece::new(piece, player_color, self.selection_index, row).labelled(&self.quick_keys))
             .collect::<Vec<_>>();
         let text = render_pieces.iter()
             .flat_map(Self::render_piece)
@@ -105,10 +302,9 @@ impl Module for PieceDisplay {
                 .centered()
                 .scroll((self.scrollbar.offset(), 0))
                 .block(Block::default()
-                    .title(format!("{} - {}", self.selection_index, self.cursor.area.y))
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(border_color))
-                    .title("Pieces")
+                    .title(self.title(game))
                 ),
             widget_area
         );
@@ -126,6 +322,8 @@ struct RenderPiece<'a> {
     color: &'a Color,
     selection_index: usize,
     position: usize,
+    /// The key that selects the piece, shown to the left of it.
+    label: Option<char>,
 }
 
 impl<'a> RenderPiece<'a> {
@@ -134,9 +332,14 @@ impl<'a> RenderPiece<'a> {
             piece,
             color,
             selection_index,
-            position
+            position,
+            label: None
         }
     }
+
+    fn labelled(self, quick_keys: &[char]) -> Self {
+        RenderPiece { label: quick_keys.get(self.position).copied(), ..self }
+    }
 }
 
 impl<'a> RenderCanvas for RenderPiece<'a> {
@@ -150,6 +353,16 @@ impl<'a> RenderCanvas for RenderPiece<'a> {
         for block in self.piece.blocks() {
             canvas[block.y as usize][block.x as usize] = Span::styled(BLOCK, Style::default().fg(color))
         }
-        canvas.into_iter().map(|line| line.into()).collect()
+        if let Some(label) = self.label {
+            // the piece stays centered with the label on its left
+            for (index, line) in canvas.iter_mut().enumerate() {
+                let label = if index == 0 { format!("{label} ") } else { "  ".to_string() };
+                line.insert(0, Span::styled(label, Style::default().fg(Color::DarkGray)));
+                line.push(Span::raw("  "));
+            }
+        }
+        let mut lines = canvas.into_iter().map(|line| line.into()).collect::<Vec<Line>>();
+        lines.push(Line::styled(format!("{} — {}", self.piece.name(), self.piece.size()), Style::default().fg(color)));
+        lines
     }
 }
\ No newline at end of file
@@ -4,19 +4,59 @@ use ratatui::prelude::Line;
 use ratatui::style::{Color, Style};
 use ratatui::text::Span;
 use ratatui::widgets::{Block, Borders, Paragraph};
-use crate::game::{Game, Player};
-use crate::ui::{AppEvent, BLOCK, Module, ModuleKind, RenderCanvas};
+use crate::game::{Game, Player, PlayerStatus};
+use crate::game::rules::Scoring;
+#[cfg(feature = "serde")]
+use crate::net::protocol::Link;
+use std::time::{Duration, Instant};
 
-pub struct PlayerDisplay;
+use crate::ui::{AppEvent, BLOCK, format_duration, Module, ModuleKind, RenderCanvas};
+
+#[derive(Default)]
+pub struct PlayerDisplay {
+    /// The bot that is thinking about its turn, if any.
+    thinking: Option<usize>,
+    /// How the seats of a network game are connected to the host.
+    #[cfg(feature = "serde")]
+    links: Vec<Link>
+}
 
 impl Module for PlayerDisplay {
-    fn update(&mut self, _event: AppEvent, _game: &mut Game) -> Option<AppEvent> {
+    fn update(&mut self, event: AppEvent, _game: &mut Game) -> Option<AppEvent> {
+        match event {
+            AppEvent::BotThinking(player_index) => self.thinking = Some(player_index),
+            AppEvent::BotMoveReady => self.thinking = None,
+            #[cfg(feature = "serde")]
+            AppEvent::Link(seat, link) => {
+                if self.links.len() <= seat {
+                    self.links.resize(seat + 1, Link::Local);
+                }
+                self.links[seat] = link;
+            }
+            _ => ()
+        }
         None
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect, game: &mut Game) {
+        let now = Instant::now();
+        let time_odds = game.clock()
+            .map(|clock| clock.time_control())
+            .filter(|time_control| time_control.has_time_odds(game.players().len()));
         let stateful_players = game.players().iter()
-            .map(|player| StatefulPlayer { player, is_active: player == game.active_player() })
+            .enumerate()
+            .map(|(index, player)| StatefulPlayer {
+                player,
+                is_active: player == game.active_player(),
+                remaining_time: game.remaining_time(index, now),
+                budget: time_odds.map(|time_control| time_control.budget_for(index)),
+                squares_placed: (game.rules().scoring == Scoring::SquaresPlaced).then(|| game.squares_placed(index)),
+                squares: (game.rules().scoring == Scoring::RemainingSquares && game.draft().is_none())
+                    .then(|| (game.squares_placed(index), game.squares_remaining(index))),
+                drafted: game.draft().map(|_| player.available_pieces.len()),
+                thinking: self.thinking == Some(index),
+                connection: self.connection(index)
+            })
             .collect::<Vec<_>>();
         let text: Vec<Line<'_>> = stateful_players.iter().flat_map(StatefulPlayer::render).collect();
         frame.render_widget(
@@ -30,14 +70,68 @@ impl Module for PlayerDisplay {
     }
 }
 
+impl PlayerDisplay {
+    /// The ping of a remote seat, colored by how long it takes, or that its connection is lost.
+    #[cfg(feature = "serde")]
+    fn connection(&self, seat: usize) -> Option<(String, Color)> {
+        match self.links.get(seat)? {
+            Link::Local => None,
+            Link::Online { ping_millis: None } => Some(("  ●".to_string(), Color::Green)),
+            Link::Online { ping_millis: Some(ping) } => Some((format!("  ● {ping} ms"), match ping {
+                0..=149 => Color::Green,
+                150..=499 => Color::Yellow,
+                _ => Color::Red
+            })),
+            Link::Lost => Some(("  ● lost".to_string(), Color::Red))
+        }
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn connection(&self, _seat: usize) -> Option<(String, Color)> {
+        None
+    }
+}
+
 struct StatefulPlayer<'a> {
     player: &'a Player,
-    is_active: bool
+    is_active: bool,
+    remaining_time: Option<Duration>,
+    /// The starting budget of the player, only shown when seats play with time odds.
+    budget: Option<Duration>,
+    /// The running score of the solo challenge.
+    squares_placed: Option<usize>,
+    /// The squares placed and the squares left in the rack, which count against the player.
+    squares: Option<(usize, usize)>,
+    /// The number of pieces picked so far while drafting.
+    drafted: Option<usize>,
+    thinking: bool,
+    /// The connection indicator of a remote seat, with its color.
+    connection: Option<(String, Color)>
 }
 
 impl <'a> RenderCanvas for StatefulPlayer<'a> {
     fn render(&self) -> Vec<Line<'_>> {
-        let color = if self.is_active { self.player.color } else { Color::default() };
-        vec![Span::styled(format!("{}  {}", BLOCK, self.player.name), Style::default().fg(color)).into()]
+        let color = match self.player.status {
+            PlayerStatus::Active if self.is_active => self.player.color,
+            PlayerStatus::Active => Color::default(),
+            _ => Color::DarkGray
+        };
+        let clock = match (self.remaining_time, self.budget) {
+            (Some(remaining), Some(budget)) => format!("  {}/{}", format_duration(remaining), format_duration(budget)),
+            (Some(remaining), None) => format!("  {}", format_duration(remaining)),
+            _ => String::new()
+        };
+        let score = match (self.squares_placed, self.squares) {
+            (Some(squares), _) => format!("  {squares} squares"),
+            (None, Some((placed, remaining))) => format!("  {placed}/{remaining}"),
+            _ => String::new()
+        };
+        let drafted = self.drafted.map(|pieces| format!("  {pieces} picked")).unwrap_or_default();
+        let thinking = if self.thinking { "  thinking…" } else { "" };
+        let mut spans = vec![Span::styled(format!("{}  {}{}{}{}{}", BLOCK, self.player.name, clock, score, drafted, thinking), Style::default().fg(color))];
+        if let Some((connection, connection_color)) = &self.connection {
+            spans.push(Span::styled(connection.clone(), Style::default().fg(*connection_color)));
+        }
+        vec![Line::from(spans)]
     }
 }
\ No newline at end of file
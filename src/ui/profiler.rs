@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::ui::ModuleKind;
+
+/// Weight of the newest sample in the moving averages shown in the overlay.
+const SMOOTHING: f64 = 0.1;
+
+/// Measures how long each module takes to render and to handle events. The averages are shown in
+/// the debug overlay, every sample can additionally be appended to a log file.
+#[derive(Default)]
+pub(crate) struct Profiler {
+    timings: HashMap<ModuleKind, Timings>,
+    overlay_visible: bool,
+    log: Option<File>,
+}
+
+#[derive(Default)]
+struct Timings {
+    render: Duration,
+    update: Duration,
+    slowest_render: Duration,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum Phase {
+    Render,
+    Update,
+}
+
+impl Profiler {
+    pub fn with_log(path: &Path) -> io::Result<Self> {
+        Ok(Profiler { log: Some(File::create(path)?), ..Profiler::default() })
+    }
+
+    pub fn measure<T>(&mut self, kind: ModuleKind, phase: Phase, function: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = function();
+        self.record(kind, phase, start.elapsed());
+        result
+    }
+
+    pub fn toggle_overlay(&mut self) {
+        self.overlay_visible = !self.overlay_visible;
+    }
+
+    fn record(&mut self, kind: ModuleKind, phase: Phase, elapsed: Duration) {
+        let timings = self.timings.entry(kind).or_default();
+        match phase {
+            Phase::Render => {
+                timings.render = moving_average(timings.render, elapsed);
+                timings.slowest_render = timings.slowest_render.max(elapsed);
+            }
+            Phase::Update => timings.update = moving_average(timings.update, elapsed)
+        }
+        if let Some(log) = &mut self.log {
+            // profiling must never take the game down, a failing log is simply dropped
+            if writeln!(log, "{kind:?} {phase:?} {}us", elapsed.as_micros()).is_err() {
+                self.log = None;
+            }
+        }
+    }
+
+    pub fn render_overlay(&self, frame: &mut Frame) {
+        if !self.overlay_visible {
+            return;
+        }
+        let mut kinds = self.timings.keys().collect::<Vec<_>>();
+        kinds.sort_by_key(|kind| format!("{kind:?}"));
+        let lines = kinds.into_iter()
+            .map(|kind| {
+                let timings = &self.timings[kind];
+                Line::from(format!(
+                    "{:<6} render {:>6}us (max {:>6}us)  update {:>5}us",
+                    format!("{kind:?}"),
+                    timings.render.as_micros(),
+                    timings.slowest_render.as_micros(),
+                    timings.update.as_micros()
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let size = frame.size();
+        let width = 52.min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: size.width - width, y: size.height - height, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().title("Debug").borders(Borders::ALL).style(Style::default().fg(Color::Gray))),
+            area
+        );
+    }
+}
+
+fn moving_average(average: Duration, sample: Duration) -> Duration {
+    if average.is_zero() {
+        return sample;
+    }
+    average.mul_f64(1.0 - SMOOTHING) + sample.mul_f64(SMOOTHING)
+}
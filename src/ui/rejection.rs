@@ -0,0 +1,32 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use crate::net::protocol::Rejection;
+
+/// Tells the player why the host did not play their move. The host already sent its game, so the
+/// move is taken back by the time this is shown.
+pub(crate) struct RejectionNotice {
+    rejection: Rejection,
+}
+
+impl RejectionNotice {
+    pub fn new(rejection: Rejection) -> Self {
+        RejectionNotice { rejection }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let lines = vec![
+            Line::styled(self.rejection.to_string(), Style::default().fg(Color::Red)),
+            Line::from(""),
+            Line::from("Press any key to try again"),
+        ];
+        let size = frame.size();
+        let width = 44.min(size.width);
+        let height = 7.min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(Block::default().title("Move rejected").borders(Borders::ALL)), area);
+    }
+}
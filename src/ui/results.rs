@@ -0,0 +1,59 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Line, Span, Style, Stylize};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::game::Game;
+use crate::game::rules::Scoring;
+use crate::ui::BLOCK;
+
+/// The final standings once the game is over. Local games can be played again from here.
+pub(crate) struct Results {
+    rematch_offered: bool,
+}
+
+impl Results {
+    pub fn new(rematch_offered: bool) -> Self {
+        Results { rematch_offered }
+    }
+
+    pub fn render(&self, frame: &mut Frame, game: &Game) {
+        let scores = (0..game.players().len()).map(|player_index| game.score(player_index)).collect::<Vec<_>>();
+        let mut standings = (0..scores.len()).collect::<Vec<_>>();
+        standings.sort_by_key(|player_index| -scores[*player_index]);
+
+        let mut lines = vec![Line::from(format!("{:<17}{:>6}{:>7}{:>7}", "", "left", "bonus", "score")).dark_gray()];
+        for player_index in standings {
+            let player = &game.players()[player_index];
+            // players with the same score share their place
+            let place = 1 + scores.iter().filter(|score| **score > scores[player_index]).count();
+            let squares_left = game.squares_remaining(player_index);
+            let bonus = match game.rules().scoring {
+                Scoring::RemainingSquares if squares_left == 0 => format!("+{}", scores[player_index]),
+                _ => "-".to_string()
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{place}. ")),
+                Span::styled(format!("{BLOCK} {:<11}", player.name), Style::default().fg(player.color)),
+                Span::raw(format!("{squares_left:>6}{bonus:>7}")),
+                Span::raw(format!("{:>7}", scores[player_index])).bold(),
+            ]));
+        }
+        for (team, score) in game.rules().teams.iter().zip(game.team_scores()) {
+            let names = team.iter().map(|player_index| game.players()[*player_index].name.as_str()).collect::<Vec<_>>();
+            lines.push(Line::from(format!("   Team {}: {score}", names.join(" + "))));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(match self.rematch_offered {
+            true => "Enter: rematch, Esc: board, q: quit",
+            false => "Esc: board, q: quit"
+        }));
+
+        let size = frame.size();
+        let width = 40.min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("Game over").borders(Borders::ALL)), area);
+    }
+}
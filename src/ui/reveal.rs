@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::game::Game;
+use crate::ui::BLOCK;
+
+/// How long it takes until the next commitment is turned over.
+const REVEAL_STEP: Duration = Duration::from_millis(500);
+
+/// Turns over the commitments of a round of the simultaneous variant one after another, then
+/// shows which pieces made it onto the board.
+pub(crate) struct Reveal {
+    /// Name, color and outcome of every commitment.
+    lines: Vec<(String, Color, String)>,
+    started: Instant,
+    skipped: bool,
+}
+
+impl Reveal {
+    /// The reveal of the last round, `None` if no round has been resolved yet.
+    pub fn for_game(game: &Game, now: Instant) -> Option<Self> {
+        if game.revealed().is_empty() {
+            return None;
+        }
+        let lines = game.revealed().iter()
+            .map(|commitment| {
+                let player = &game.players()[commitment.player];
+                let outcome = match (&commitment.played, commitment.placed) {
+                    (None, _) => "passed".to_string(),
+                    (Some(_), true) => format!("{} placed", commitment.piece_name),
+                    (Some(_), false) => format!("{} cancelled", commitment.piece_name),
+                };
+                (player.name.clone(), player.color, outcome)
+            })
+            .collect();
+        Some(Reveal { lines, started: now, skipped: false })
+    }
+
+    fn revealed_lines(&self, now: Instant) -> usize {
+        if self.skipped {
+            return self.lines.len();
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        ((elapsed.as_millis() / REVEAL_STEP.as_millis()) as usize).min(self.lines.len())
+    }
+
+    pub fn is_settled(&self, now: Instant) -> bool {
+        self.revealed_lines(now) == self.lines.len()
+    }
+
+    /// Skips the rest of the animation.
+    pub fn settle(&mut self) {
+        self.skipped = true;
+    }
+
+    pub fn render(&self, frame: &mut Frame, now: Instant) {
+        let revealed = self.revealed_lines(now);
+        let mut lines = self.lines.iter().enumerate()
+            .map(|(index, (name, color, outcome))| {
+                let outcome = if index < revealed { outcome.as_str() } else { "?" };
+                Line::from(vec![Span::styled(format!("{BLOCK} {name}"), Style::default().fg(*color)), Span::raw(format!("  {outcome}"))])
+            })
+            .collect::<Vec<_>>();
+        lines.push(Line::from(""));
+        lines.push(match self.is_settled(now) {
+            true => Line::from("Press any key."),
+            false => Line::from("Revealing...")
+        });
+
+        let size = frame.size();
+        let width = 34.min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("Round revealed").borders(Borders::ALL)), area);
+    }
+}
@@ -4,56 +4,84 @@ use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
 
 use crate::ui::{Cursor, UI_OFFSET};
 
-#[derive(Default)]
-pub struct VerticalScrollBar {
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Axis {
+    Vertical,
+    /// Scrolls by cells of the board, which are two characters wide.
+    Horizontal,
+}
+
+/// Keeps the cursor in view by scrolling along one axis. The offset is counted in lines or in
+/// cells of the board.
+pub struct ScrollBar {
+    axis: Axis,
     offset: u16,
     scrollbar_state: ScrollbarState,
     enabled: bool
 }
 
-impl VerticalScrollBar {
+impl ScrollBar {
+    pub fn vertical() -> Self {
+        ScrollBar { axis: Axis::Vertical, offset: 0, scrollbar_state: ScrollbarState::default(), enabled: false }
+    }
+
+    pub fn horizontal() -> Self {
+        ScrollBar { axis: Axis::Horizontal, ..ScrollBar::vertical() }
+    }
+
     pub fn offset(&self) -> u16 {
         self.offset
     }
 
-    pub fn update_scrollbar(&mut self, board_render_area: Rect, cursor: &Cursor) {
-        let rows_displayed = board_render_area.height - UI_OFFSET;
+    /// How many characters a step of the offset is wide or high.
+    fn unit(&self) -> u16 {
+        match self.axis {
+            Axis::Vertical => 1,
+            Axis::Horizontal => 2
+        }
+    }
+
+    pub fn update_scrollbar(&mut self, render_area: Rect, cursor: &Cursor) {
+        let (displayed, start, length) = match self.axis {
+            Axis::Vertical => (render_area.height.saturating_sub(UI_OFFSET), cursor.area.y, cursor.area.height),
+            Axis::Horizontal => (render_area.width.saturating_sub(UI_OFFSET) / 2, cursor.area.x, cursor.area.width)
+        };
 
-        if rows_displayed < cursor.area.height {
-            // TODO: switch to warning display mode
-            panic!("Display too small!")
+        // the UI shows a warning instead while the terminal is too small
+        if displayed < length {
+            return;
         }
 
         // scroll up
-        if (rows_displayed + self.offset) < (cursor.area.y + cursor.area.height) {
-            self.offset = (cursor.area.y + cursor.area.height) - rows_displayed;
-            self.scrollbar_state = self.scrollbar_state.position(cursor.area.y as usize + 1);
+        if (displayed + self.offset) < (start + length) {
+            self.offset = (start + length) - displayed;
+            self.scrollbar_state = self.scrollbar_state.position(((start + 1) * self.unit()) as usize);
         }
 
         // scroll down
-        if cursor.area.y < self.offset {
-            let diff = self.offset - cursor.area.y;
+        if start < self.offset {
+            let diff = self.offset - start;
             self.offset -= diff;
-            self.scrollbar_state = self.scrollbar_state.position(cursor.area.y as usize + 1);
+            self.scrollbar_state = self.scrollbar_state.position(((start + 1) * self.unit()) as usize);
         }
     }
 
-    pub fn render_scrollbar(&mut self, frame: &mut Frame, content_height: u16, widget_area: Rect) {
-        let remaining_height = content_height.saturating_sub(widget_area.height);
+    pub fn render_scrollbar(&mut self, frame: &mut Frame, content_length: u16, widget_area: Rect) {
+        let (orientation, viewport_length) = match self.axis {
+            Axis::Vertical => (ScrollbarOrientation::VerticalRight, widget_area.height),
+            Axis::Horizontal => (ScrollbarOrientation::HorizontalBottom, widget_area.width)
+        };
+        let remaining_length = content_length.saturating_sub(viewport_length);
 
-        self.enabled = remaining_height > 0;
+        self.enabled = remaining_length > 0;
         if self.enabled {
             frame.render_stateful_widget(
-                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                Scrollbar::new(orientation),
                 widget_area,
                 &mut self.scrollbar_state
-                    .viewport_content_length(widget_area.height as usize)
-                    .content_length(content_height as usize)
+                    .viewport_content_length(viewport_length as usize)
+                    .content_length(content_length as usize)
             );
         }
     }
-
-    pub fn enabled(&self) -> bool {
-        self.enabled
-    }
-}
\ No newline at end of file
+}
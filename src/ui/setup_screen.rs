@@ -0,0 +1,227 @@
+use std::io;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::game::{Player, PlayerKind};
+use crate::game::rules::{GameRules, MAX_PLAYERS, PRESETS, Start};
+use crate::ui::{AppEvent, enter_screen, Focus, Keymap, leave_screen, poll_event};
+
+const MAX_NAME_LENGTH: usize = 12;
+const BOARD_SIZES: RangeInclusive<u16> = 6..=30;
+/// The colors to pick for the players, with the lighter shade their selected pieces are shown in.
+const COLORS: [(Color, Color); 6] = [
+    (Color::Green, Color::LightGreen),
+    (Color::Blue, Color::LightBlue),
+    (Color::Yellow, Color::LightYellow),
+    (Color::Red, Color::LightRed),
+    (Color::Magenta, Color::LightMagenta),
+    (Color::Cyan, Color::LightCyan),
+];
+/// Who may play a seat, by the names of `--bots`. `mcts` is left out as it is the same bot as
+/// `hard`.
+const KINDS: [&str; 8] = ["human", "easy", "medium", "hard", "random", "greedy", "maxn", "paranoid"];
+
+/// What the players agree on before a game starts. There is a player for every seat a game may
+/// have, only the first `rules.num_players` of them take part.
+pub struct Setup {
+    pub rules: GameRules,
+    pub players: Vec<Player>,
+}
+
+/// A line of the setup screen that can be changed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Row {
+    Variant,
+    BoardSize,
+    NumPlayers,
+    Name(usize),
+    Color(usize),
+    Kind(usize),
+}
+
+struct SetupScreen {
+    setup: Setup,
+    selected: usize,
+    /// Whether the keys are typed into the name of the selected seat.
+    editing: bool,
+}
+
+/// Shows the setup until the player starts the game with it. Returns `None` if the player quit
+/// instead.
+pub fn setup_game(setup: Setup, keymap: &Keymap) -> io::Result<Option<Setup>> {
+    let mut terminal = enter_screen()?;
+    let mut screen = SetupScreen { setup, selected: 0, editing: false };
+    let started = loop {
+        terminal.draw(|frame| screen.render(frame))?;
+        // names are typed, so the keys must not move the selection or quit
+        if screen.editing {
+            screen.edit_name()?;
+            continue;
+        }
+        match poll_event(Focus::Board, keymap)? {
+            AppEvent::Quit | AppEvent::Decline => break false,
+            AppEvent::MoveUp => screen.selected = screen.selected.saturating_sub(1),
+            AppEvent::MoveDown => screen.selected = (screen.selected + 1).min(screen.rows().len() - 1),
+            AppEvent::MoveLeft => screen.change(-1),
+            AppEvent::MoveRight => screen.change(1),
+            AppEvent::Select if matches!(screen.row(), Row::Name(_)) => screen.editing = true,
+            AppEvent::Select => break true,
+            _ => ()
+        }
+    };
+    leave_screen()?;
+    Ok(started.then_some(screen.setup))
+}
+
+impl SetupScreen {
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![Row::Variant, Row::BoardSize, Row::NumPlayers];
+        for seat in 0..self.setup.rules.num_players {
+            rows.extend([Row::Name(seat), Row::Color(seat), Row::Kind(seat)]);
+        }
+        rows
+    }
+
+    fn row(&self) -> Row {
+        self.rows()[self.selected]
+    }
+
+    /// The preset the rules are, apart from the options that every preset can be played with.
+    fn variant(&self) -> Option<usize> {
+        let rules = &self.setup.rules;
+        PRESETS.iter().position(|name| GameRules::preset(name)
+            .is_some_and(|preset| GameRules { draft: rules.draft, simultaneous: rules.simultaneous, ..preset } == *rules))
+    }
+
+    /// Steps through the values of the selected row, `step` is negative to step back.
+    fn change(&mut self, step: isize) {
+        let (row, variant) = (self.row(), self.variant());
+        let rules = &mut self.setup.rules;
+        match row {
+            Row::Variant => {
+                let variant = match variant {
+                    Some(variant) => (variant as isize + step).rem_euclid(PRESETS.len() as isize) as usize,
+                    None => 0
+                };
+                let preset = GameRules::preset(PRESETS[variant]).expect("Presets have rules");
+                *rules = GameRules { draft: rules.draft, simultaneous: rules.simultaneous, ..preset };
+            }
+            // the start squares of other variants are where they are for the size of their board
+            Row::BoardSize if rules.start == Start::Corners => {
+                let size = (rules.width as isize + step).clamp(*BOARD_SIZES.start() as isize, *BOARD_SIZES.end() as isize) as u16;
+                rules.width = size;
+                rules.height = size;
+                rules.blocked_cells.retain(|cell| cell.x < size as i32 && cell.y < size as i32);
+            }
+            Row::BoardSize => (),
+            Row::NumPlayers => rules.num_players = (rules.num_players as isize + step).clamp(1, MAX_PLAYERS as isize) as usize,
+            Row::Name(_) => (),
+            Row::Color(seat) => {
+                let taken = self.setup.players.iter()
+                    .take(rules.num_players)
+                    .enumerate()
+                    .filter(|(other, _)| *other != seat)
+                    .map(|(_, player)| player.color)
+                    .collect::<Vec<_>>();
+                let player = &mut self.setup.players[seat];
+                let current = COLORS.iter().position(|(color, _)| *color == player.color).unwrap_or(0) as isize;
+                let next = (1..=COLORS.len() as isize)
+                    .map(|offset| COLORS[(current + offset * step).rem_euclid(COLORS.len() as isize) as usize])
+                    .find(|(color, _)| !taken.contains(color));
+                if let Some((color, secondary_color)) = next {
+                    player.color = color;
+                    player.secondary_color = secondary_color;
+                }
+            }
+            Row::Kind(seat) => {
+                let player = &mut self.setup.players[seat];
+                let current = KINDS.iter().position(|name| name.parse() == Ok(player.kind)).map_or(-step, |index| index as isize);
+                let next = (current + step).rem_euclid(KINDS.len() as isize) as usize;
+                player.kind = KINDS[next].parse().expect("The kinds to pick are valid");
+            }
+        }
+    }
+
+    /// Types the next key into the name of the selected seat, Enter or Esc finish the name.
+    fn edit_name(&mut self) -> io::Result<()> {
+        if !event::poll(Duration::from_millis(50))? {
+            return Ok(());
+        }
+        let (Event::Key(key), Row::Name(seat)) = (event::read()?, self.row()) else {
+            return Ok(());
+        };
+        let name = &mut self.setup.players[seat].name;
+        match key.code {
+            _ if key.kind != KeyEventKind::Press => (),
+            KeyCode::Enter | KeyCode::Esc => {
+                self.editing = false;
+                if name.trim().is_empty() {
+                    *name = format!("Player {}", seat + 1);
+                }
+            }
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Char(char) if name.chars().count() < MAX_NAME_LENGTH => name.push(char),
+            _ => ()
+        }
+        Ok(())
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let rules = &self.setup.rules;
+        let mut lines = vec![];
+        for (index, row) in self.rows().into_iter().enumerate() {
+            let selected = index == self.selected;
+            let value = |value: String| match selected {
+                true => Span::styled(format!("< {value} >"), Style::default().bold()),
+                false => Span::raw(format!("  {value}  "))
+            };
+            let (label, spans) = match row {
+                Row::Variant => ("Variant".to_string(), vec![value(self.variant().map_or("custom", |variant| PRESETS[variant]).to_string())]),
+                Row::BoardSize => ("Board".to_string(), vec![value(format!("{} x {}", rules.width, rules.height))]),
+                Row::NumPlayers => ("Players".to_string(), vec![value(rules.num_players.to_string())]),
+                Row::Name(seat) => {
+                    let name = &self.setup.players[seat].name;
+                    let name = match selected && self.editing {
+                        true => Span::styled(format!("  {name}_"), Style::default().underlined()),
+                        false => value(name.clone())
+                    };
+                    (format!("Seat {}", seat + 1), vec![name])
+                }
+                Row::Color(seat) => {
+                    let player = &self.setup.players[seat];
+                    ("  Color".to_string(), vec![value("██".to_string()).fg(player.color)])
+                }
+                Row::Kind(seat) => ("  Played by".to_string(), vec![value(kind_name(self.setup.players[seat].kind))]),
+            };
+            let marker = if selected { ">" } else { " " };
+            let mut line = vec![Span::raw(format!("{marker} {label:<12}"))];
+            line.extend(spans);
+            lines.push(Line::from(line));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(match self.editing {
+            true => "Type the name, Enter: done",
+            false => "Left/Right: change, Enter: start, q: quit"
+        }));
+        lines.push(Line::from("Enter on a seat edits its name").fg(Color::DarkGray));
+
+        let area = frame.size();
+        let width = 46.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height };
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().title("New game").borders(Borders::ALL)), popup);
+    }
+}
+
+fn kind_name(kind: PlayerKind) -> String {
+    KINDS.iter()
+        .find(|name| name.parse() == Ok(kind))
+        .map_or_else(|| format!("{kind:?}"), |name| name.to_string())
+}
@@ -0,0 +1,90 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style, Stylize};
+use ratatui::widgets::Paragraph;
+
+use crate::game::Game;
+use crate::ui::{AppEvent, Keymap, Module, ModuleKind};
+
+/// What the player is doing, to know which keys to hint at.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Activity {
+    Moving,
+    Choosing,
+    Placing,
+}
+
+/// A line below the board that tells whose turn it is and which keys do what, or why the last
+/// action did not work.
+pub struct StatusDisplay {
+    keymap: Keymap,
+    activity: Activity,
+    /// The bot that is thinking about its turn, if any.
+    thinking: Option<usize>,
+    /// Shown instead of the hints until the player does something else.
+    message: Option<&'static str>,
+}
+
+impl StatusDisplay {
+    pub fn new(keymap: Keymap) -> Self {
+        StatusDisplay { keymap, activity: Activity::Moving, thinking: None, message: None }
+    }
+
+    fn hints(&self, game: &Game) -> String {
+        let actions = match self.activity {
+            _ if game.is_over() => vec![(AppEvent::Select, "results"), (AppEvent::ToggleMoveNumbers, "move numbers"), (AppEvent::ToggleUnplacedPieces, "unplaced pieces")],
+            Activity::Moving => vec![(AppEvent::OpenPieceSelection, "choose a piece"), (AppEvent::Pass, "pass"), (AppEvent::Hint, "hint")],
+            Activity::Choosing => vec![(AppEvent::MoveUp, "previous"), (AppEvent::MoveDown, "next"), (AppEvent::Select, "select")],
+            Activity::Placing if game.rules().allow_flips => vec![(AppEvent::Select, "place"), (AppEvent::Rotate, "rotate"), (AppEvent::Flip, "flip"), (AppEvent::OpenPieceSelection, "other piece")],
+            Activity::Placing => vec![(AppEvent::Select, "place"), (AppEvent::Rotate, "rotate"), (AppEvent::OpenPieceSelection, "other piece")],
+        };
+        // unbound actions are left out
+        actions.into_iter()
+            .filter_map(|(event, description)| self.keymap.key_for(event).map(|key| format!("{key}: {description}")))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+impl Module for StatusDisplay {
+    fn update(&mut self, event: AppEvent, _game: &mut Game) -> Option<AppEvent> {
+        if event.is_move_input() {
+            self.message = None;
+        }
+        match event {
+            AppEvent::Status(message) => self.message = Some(message),
+            AppEvent::OpenPieceSelection => self.activity = Activity::Choosing,
+            AppEvent::PieceSelected(_) => self.activity = Activity::Placing,
+            AppEvent::DraftPick(_) | AppEvent::PiecePlaced | AppEvent::TurnPassed => {
+                self.activity = Activity::Moving;
+                self.message = None;
+            }
+            AppEvent::BotThinking(player_index) => self.thinking = Some(player_index),
+            AppEvent::BotMoveReady => self.thinking = None,
+            _ => ()
+        }
+        None
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, game: &mut Game) {
+        let player = game.active_player();
+        let turn = match self.thinking {
+            _ if game.is_over() => Span::raw("Game over"),
+            // a bot whose turn went stale may still be thinking
+            Some(player_index) if player_index == game.active_player_index() => {
+                let player = &game.players()[player_index];
+                Span::styled(format!("{} is thinking", player.name), Style::default().fg(player.color))
+            }
+            _ => Span::styled(format!("{} to move", player.name), Style::default().fg(player.color)),
+        };
+        let line = match self.message {
+            Some(message) => Line::from(vec![turn, Span::raw("  "), Span::raw(message).fg(Color::Red)]),
+            None => Line::from(vec![turn, Span::raw("  "), Span::raw(self.hints(game)).fg(Color::DarkGray)]),
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    fn kind(&self) -> ModuleKind {
+        ModuleKind::Status
+    }
+}
@@ -0,0 +1,61 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Line, Span, Style};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use crate::game::{Game, PlayerKind, PlayerStatus};
+
+/// A request of the player who moved last to take their move back. Every other player who is
+/// still part of the game has to accept it, one after another at the same keyboard. Bots do not
+/// mind.
+pub(crate) struct TakebackRequest {
+    requester: usize,
+    pending: Vec<usize>,
+}
+
+impl TakebackRequest {
+    /// `None` if no move has been played yet, or in the simultaneous variant where whole rounds
+    /// are played at once.
+    pub fn for_game(game: &Game) -> Option<Self> {
+        if game.rules().simultaneous {
+            return None;
+        }
+        let requester = game.last_move()?.player;
+        let pending = game.players().iter()
+            .enumerate()
+            .filter(|(player_index, player)| *player_index != requester && player.status != PlayerStatus::Resigned && player.kind == PlayerKind::Human)
+            .map(|(player_index, _)| player_index)
+            .collect();
+        Some(TakebackRequest { requester, pending })
+    }
+
+    /// Whether everybody has accepted the takeback.
+    pub fn is_accepted(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Records that the next player accepted the takeback.
+    pub fn accept(&mut self) {
+        if !self.pending.is_empty() {
+            self.pending.remove(0);
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, game: &Game) {
+        let requester = &game.players()[self.requester];
+        let mut lines = vec![
+            Line::from(vec![Span::styled(requester.name.clone(), Style::default().fg(requester.color)), Span::raw(" asks to take back their last move.")]),
+            Line::from(""),
+        ];
+        if let Some(player) = self.pending.first().map(|player_index| &game.players()[*player_index]) {
+            lines.push(Line::from(vec![Span::styled(player.name.clone(), Style::default().fg(player.color)), Span::raw(": y accepts, Esc declines")]));
+        }
+
+        let size = frame.size();
+        let width = 40.min(size.width);
+        let height = 6.min(size.height);
+        let area = Rect { x: (size.width - width) / 2, y: (size.height - height) / 2, width, height };
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(Block::default().title("Takeback").borders(Borders::ALL)), area);
+    }
+}
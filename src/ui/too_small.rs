@@ -0,0 +1,32 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Line, Stylize};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+/// Shown instead of the game while the terminal is smaller than the game needs, the game comes
+/// back as soon as the terminal is large enough again.
+pub(crate) struct TooSmall {
+    width: u16,
+    height: u16,
+}
+
+impl TooSmall {
+    pub fn new(width: u16, height: u16) -> Self {
+        TooSmall { width, height }
+    }
+
+    pub fn fits(&self, area: Rect) -> bool {
+        area.width >= self.width && area.height >= self.height
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let size = frame.size();
+        let lines = vec![
+            Line::from("Enlarge your terminal to at least").bold(),
+            Line::from(format!("{}×{}", self.width, self.height)).bold(),
+            Line::from(""),
+            Line::from(format!("It is {}×{} now.", size.width, size.height)),
+        ];
+        frame.render_widget(Paragraph::new(lines).centered().wrap(Wrap { trim: true }).block(Block::default().title("Terminal too small").borders(Borders::ALL)), size);
+    }
+}